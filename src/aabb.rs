@@ -0,0 +1,127 @@
+use crate::{point3::Point3, ray::Ray};
+
+/// An axis-aligned bounding box, the space between two corners
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Aabb {
+    pub minimum: Point3,
+    pub maximum: Point3,
+}
+
+impl Aabb {
+    pub fn new(minimum: Point3, maximum: Point3) -> Self {
+        Self { minimum, maximum }
+    }
+
+    /// The smallest box around both boxes
+    pub fn surrounding(self, other: Aabb) -> Self {
+        Self::new(
+            Point3::new(
+                self.minimum.x.min(other.minimum.x),
+                self.minimum.y.min(other.minimum.y),
+                self.minimum.z.min(other.minimum.z),
+            ),
+            Point3::new(
+                self.maximum.x.max(other.maximum.x),
+                self.maximum.y.max(other.maximum.y),
+                self.maximum.z.max(other.maximum.z),
+            ),
+        )
+    }
+
+    /// A box that is at least `delta` thick along every axis, so that flat objects can still be hit
+    pub fn padded(self, delta: f64) -> Self {
+        let mut padded = self;
+        for axis in 0..3 {
+            let missing = delta - (self.maximum[axis] - self.minimum[axis]);
+            if missing > 0. {
+                padded.minimum[axis] -= missing / 2.;
+                padded.maximum[axis] += missing / 2.;
+            }
+        }
+        padded
+    }
+
+    pub fn centroid(self) -> Point3 {
+        (self.minimum + self.maximum) / 2.
+    }
+
+    pub fn surface_area(self) -> f64 {
+        let size = self.maximum - self.minimum;
+        2. * (size.x * size.y + size.y * size.z + size.z * size.x)
+    }
+
+    // Whether the ray passes through the box between t_min and t_max
+    //
+    // Each pair of planes bounding an axis is crossed between two values of t. The ray passes through the
+    // box when these intervals overlap for all three axes.
+    pub fn hit(&self, ray: Ray, mut t_min: f64, mut t_max: f64) -> bool {
+        for axis in 0..3 {
+            let inverse_direction = 1. / ray.direction[axis];
+            let mut t0 = (self.minimum[axis] - ray.origin[axis]) * inverse_direction;
+            let mut t1 = (self.maximum[axis] - ray.origin[axis]) * inverse_direction;
+            if inverse_direction < 0. {
+                std::mem::swap(&mut t0, &mut t1);
+            }
+            t_min = t0.max(t_min);
+            t_max = t1.min(t_max);
+            if t_max <= t_min {
+                return false;
+            }
+        }
+        true
+    }
+}
+
+#[cfg(test)]
+use crate::vec3::Vec3;
+
+#[test]
+fn test_aabb_hit() {
+    // Given a unit box
+    let aabb = Aabb::new(Point3::new(0., 0., 0.), Point3::new(1., 1., 1.));
+
+    // When rays pass through it, past it, or point along an axis
+    let through = Ray::new(Point3::new(-1., 0.5, 0.5), Vec3::new(1., 0.1, 0.));
+    let past = Ray::new(Point3::new(-1., 2., 0.5), Vec3::new(1., 0., 0.));
+    let along_axis = Ray::new(Point3::new(0.5, 0.5, -3.), Vec3::new(0., 0., 1.));
+
+    // Then only the rays that go through it hit it, within the range of t
+    assert!(aabb.hit(through, 0., f64::INFINITY));
+    assert!(!aabb.hit(through, 0., 0.5));
+    assert!(!aabb.hit(past, 0., f64::INFINITY));
+    assert!(aabb.hit(along_axis, 0., f64::INFINITY));
+    let away = Ray::new(along_axis.origin, -along_axis.direction);
+    assert!(!aabb.hit(away, 0., f64::INFINITY));
+}
+
+#[test]
+fn test_aabb_surrounding() {
+    // Given two boxes
+    let a = Aabb::new(Point3::new(0., 0., 0.), Point3::new(1., 1., 1.));
+    let b = Aabb::new(Point3::new(-1., 0.5, 0.5), Point3::new(0.5, 2., 0.5));
+
+    // When I surround them
+    let surrounding = a.surrounding(b);
+
+    // Then the box fits around both
+    assert_eq!(surrounding.minimum, Point3::new(-1., 0., 0.));
+    assert_eq!(surrounding.maximum, Point3::new(1., 2., 1.));
+    assert_eq!(surrounding.centroid(), Point3::new(0., 1., 0.5));
+    assert_eq!(surrounding.surface_area(), 2. * (4. + 2. + 2.));
+}
+
+#[test]
+fn test_aabb_padded() {
+    // Given a flat box
+    let flat = Aabb::new(Point3::new(0., 0., 1.), Point3::new(1., 1., 1.));
+
+    // When I pad it
+    let padded = flat.padded(0.5);
+
+    // Then it is thick enough along the flat axis only, and a ray straight at it hits it
+    assert_eq!(padded.minimum, Point3::new(0., 0., 0.75));
+    assert_eq!(padded.maximum, Point3::new(1., 1., 1.25));
+    let ray = Ray::new(Point3::new(0.5, 0.5, 0.), Vec3::new(0., 0., 1.));
+    assert!(!flat.hit(ray, 0., f64::INFINITY));
+    assert!(padded.hit(ray, 0., f64::INFINITY));
+}
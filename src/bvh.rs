@@ -0,0 +1,388 @@
+use std::cmp::Ordering;
+use std::error::Error;
+
+use crate::{
+    aabb::Aabb,
+    hittable::{HitRecord, Hittable},
+    hittable_list::HittableList,
+    point3::Point3,
+    ray::Ray,
+    scene_file::{ExportedMaterials, ObjectDescription},
+};
+
+// Estimated cost of testing a ray against a box, and against an object, for the surface area heuristic
+const TRAVERSAL_COST: f64 = 0.5;
+const INTERSECTION_COST: f64 = 1.;
+
+// Leaves can hold a few objects when splitting them up further isn't worth it
+const MAX_LEAF_SIZE: usize = 4;
+
+/// A bounding volume hierarchy: a tree of boxes around objects, so that a ray is only tested against the
+/// objects inside the boxes it passes through
+///
+/// Objects are split where the surface area heuristic expects rays to do the least work. They keep the
+/// `object_id` they would have in a `HittableList` of the same objects. Objects without a bounding box,
+/// like infinite planes, are tested against every ray.
+pub struct BvhNode {
+    // None for nodes that are always tested, because they are empty or hold unbounded objects
+    bounding_box: Option<Aabb>,
+    contents: Contents,
+}
+
+enum Contents {
+    // Objects with the id they are reported as
+    Leaf(Vec<(u32, Box<dyn Hittable>)>),
+    Branch(Box<BvhNode>, Box<BvhNode>),
+}
+
+// An object waiting to be placed in the tree
+struct Primitive {
+    id: u32,
+    object: Box<dyn Hittable>,
+    bounding_box: Aabb,
+    centroid: Point3,
+}
+
+impl BvhNode {
+    pub fn new(objects: Vec<Box<dyn Hittable>>) -> Self {
+        let mut bounded = Vec::new();
+        let mut unbounded = Vec::new();
+        for (i, object) in objects.into_iter().enumerate() {
+            let id = i as u32 + 1;
+            let mut bounding_box = Aabb::new(Point3::new(0., 0., 0.), Point3::new(0., 0., 0.));
+            if object.bounding_box(&mut bounding_box) {
+                bounded.push(Primitive {
+                    id,
+                    object,
+                    bounding_box,
+                    centroid: bounding_box.centroid(),
+                });
+            } else {
+                unbounded.push((id, object));
+            }
+        }
+
+        let tree = Self::build(bounded);
+        if unbounded.is_empty() {
+            return tree;
+        }
+        let always_tested = Self {
+            bounding_box: None,
+            contents: Contents::Leaf(unbounded),
+        };
+        Self {
+            bounding_box: None,
+            contents: Contents::Branch(Box::new(tree), Box::new(always_tested)),
+        }
+    }
+
+    fn build(mut primitives: Vec<Primitive>) -> Self {
+        let bounding_box = primitives
+            .iter()
+            .map(|primitive| primitive.bounding_box)
+            .reduce(Aabb::surrounding);
+        let count = primitives.len();
+        let leaf = |primitives: Vec<Primitive>| Self {
+            bounding_box,
+            contents: Contents::Leaf(
+                primitives
+                    .into_iter()
+                    .map(|primitive| (primitive.id, primitive.object))
+                    .collect(),
+            ),
+        };
+        let parent_box = match bounding_box {
+            Some(parent_box) if count > 1 => parent_box,
+            _ => return leaf(primitives),
+        };
+
+        // Find the axis and position at which splitting the sorted objects is cheapest
+        let parent_area = parent_box.surface_area().max(f64::MIN_POSITIVE);
+        let mut best_split = None;
+        let mut best_cost = f64::INFINITY;
+        for axis in 0..3 {
+            sort_by_centroid(&mut primitives, axis);
+
+            // The areas of the boxes around everything left of every split, and everything right of it
+            let sweep = |primitives: &mut dyn Iterator<Item = &Primitive>| -> Vec<f64> {
+                let mut areas = Vec::with_capacity(count);
+                let mut swept: Option<Aabb> = None;
+                for primitive in primitives {
+                    let swept_box = swept.map_or(primitive.bounding_box, |swept_box| {
+                        swept_box.surrounding(primitive.bounding_box)
+                    });
+                    areas.push(swept_box.surface_area());
+                    swept = Some(swept_box);
+                }
+                areas
+            };
+            let left_areas = sweep(&mut primitives.iter());
+            let mut right_areas = sweep(&mut primitives.iter().rev());
+            right_areas.reverse();
+
+            for split in 1..count {
+                let cost = TRAVERSAL_COST
+                    + INTERSECTION_COST
+                        * (left_areas[split - 1] * split as f64
+                            + right_areas[split] * (count - split) as f64)
+                        / parent_area;
+                if cost < best_cost {
+                    best_cost = cost;
+                    best_split = Some((axis, split));
+                }
+            }
+        }
+
+        let leaf_cost = INTERSECTION_COST * count as f64;
+        let (axis, split) = match best_split {
+            Some(_) if count <= MAX_LEAF_SIZE && leaf_cost <= best_cost => return leaf(primitives),
+            Some(best_split) => best_split,
+            None => return leaf(primitives),
+        };
+        sort_by_centroid(&mut primitives, axis);
+        let right = primitives.split_off(split);
+        Self {
+            bounding_box,
+            contents: Contents::Branch(
+                Box::new(Self::build(primitives)),
+                Box::new(Self::build(right)),
+            ),
+        }
+    }
+
+    // Every object in the tree, with its id
+    fn objects<'a>(&'a self, objects: &mut Vec<(u32, &'a dyn Hittable)>) {
+        match &self.contents {
+            Contents::Leaf(leaf) => {
+                objects.extend(leaf.iter().map(|(id, object)| (*id, object.as_ref())))
+            }
+            Contents::Branch(left, right) => {
+                left.objects(objects);
+                right.objects(objects);
+            }
+        }
+    }
+}
+
+fn sort_by_centroid(primitives: &mut [Primitive], axis: u8) {
+    primitives.sort_by(|a, b| {
+        a.centroid[axis]
+            .partial_cmp(&b.centroid[axis])
+            .unwrap_or(Ordering::Equal)
+    });
+}
+
+impl From<HittableList> for BvhNode {
+    fn from(list: HittableList) -> Self {
+        Self::new(list.into_objects())
+    }
+}
+
+impl Hittable for BvhNode {
+    fn hit(&self, ray: Ray, t_min: f64, t_max: f64, record: &mut HitRecord) -> bool {
+        if let Some(bounding_box) = self.bounding_box {
+            if !bounding_box.hit(ray, t_min, t_max) {
+                return false;
+            }
+        }
+
+        match &self.contents {
+            Contents::Leaf(objects) => {
+                let temp_rec = &mut record.clone();
+                let mut was_anything_hit = false;
+                let mut closest_so_far = t_max;
+                for (id, object) in objects {
+                    if object.hit(ray, t_min, closest_so_far, temp_rec) {
+                        was_anything_hit = true;
+                        closest_so_far = temp_rec.t;
+                        *record = temp_rec.clone();
+                        record.object_id = *id;
+                    }
+                }
+                was_anything_hit
+            }
+            Contents::Branch(left, right) => {
+                let hit_left = left.hit(ray, t_min, t_max, record);
+                let closest_so_far = if hit_left { record.t } else { t_max };
+                let hit_right = right.hit(ray, t_min, closest_so_far, record);
+                hit_left || hit_right
+            }
+        }
+    }
+
+    fn bounding_box(&self, output_box: &mut Aabb) -> bool {
+        match self.bounding_box {
+            Some(bounding_box) => {
+                *output_box = bounding_box;
+                true
+            }
+            None => false,
+        }
+    }
+
+    // Written as the list the tree was built from, which builds the same tree again
+    fn export(
+        &self,
+        materials: &mut ExportedMaterials,
+    ) -> Result<ObjectDescription, Box<dyn Error>> {
+        let mut objects = Vec::new();
+        self.objects(&mut objects);
+        objects.sort_by_key(|(id, _)| *id);
+        let objects = objects
+            .into_iter()
+            .map(|(_, object)| object.export(materials))
+            .collect::<Result<_, _>>()?;
+        Ok(ObjectDescription::Group { objects })
+    }
+}
+
+#[cfg(test)]
+use crate::{lambertian::Lambertian, sphere::Sphere, vec3::Vec3};
+
+#[cfg(test)]
+fn random_spheres(count: usize) -> Vec<Box<dyn Hittable>> {
+    use rand::Rng;
+    use std::sync::Arc;
+
+    let mut rng = crate::rng::rng_from_seed(3);
+    let material = Arc::new(Lambertian::new(Vec3::new(0.5, 0.5, 0.5)));
+    (0..count)
+        .map(|_| {
+            let center = Point3::random_in_range(&mut rng, -10., 10.);
+            let radius = rng.gen_range(0.1..1.);
+            Box::new(Sphere::new(center, radius, material.clone())) as Box<dyn Hittable>
+        })
+        .collect()
+}
+
+// An infinite plane at y = 0, which has no bounding box
+#[cfg(test)]
+struct Floor;
+
+#[cfg(test)]
+impl Hittable for Floor {
+    fn hit(&self, ray: Ray, t_min: f64, t_max: f64, record: &mut HitRecord) -> bool {
+        let t = -ray.origin.y / ray.direction.y;
+        if !(t_min..t_max).contains(&t) {
+            return false;
+        }
+        record.t = t;
+        record.point = ray.at(t);
+        record.set_face_normal(ray, Vec3::new(0., 1., 0.));
+        true
+    }
+
+    fn bounding_box(&self, _: &mut Aabb) -> bool {
+        false
+    }
+}
+
+// An unbounded object that is never hit, but leaves its mark on the record when it is missed
+#[cfg(test)]
+struct Scribbler;
+
+#[cfg(test)]
+impl Hittable for Scribbler {
+    fn hit(&self, _: Ray, _: f64, _: f64, record: &mut HitRecord) -> bool {
+        record.t = -1.;
+        record.normal = Vec3::new(0., 0., 0.);
+        false
+    }
+
+    fn bounding_box(&self, _: &mut Aabb) -> bool {
+        false
+    }
+}
+
+#[test]
+fn test_bvh_hits_like_a_list() {
+    // Given many spheres, both in a list and in a BVH
+    let mut list = HittableList::new();
+    for sphere in random_spheres(300) {
+        list.add(sphere);
+    }
+    let bvh = BvhNode::new(random_spheres(300));
+
+    // When I shoot rays through them from all over
+    let mut rng = crate::rng::rng_from_seed(4);
+    for _ in 0..1000 {
+        let ray = Ray::new(
+            Point3::random_in_range(&mut rng, -15., 15.),
+            Vec3::random_in_unit_sphere(&mut rng),
+        );
+        let mut list_record = HitRecord::new();
+        let mut bvh_record = HitRecord::new();
+        let list_hit = list.hit(ray, 0.001, f64::INFINITY, &mut list_record);
+        let bvh_hit = bvh.hit(ray, 0.001, f64::INFINITY, &mut bvh_record);
+
+        // Then the BVH hits the same objects at the same places
+        assert_eq!(list_hit, bvh_hit);
+        if list_hit {
+            assert_eq!(list_record.t, bvh_record.t);
+            assert_eq!(list_record.object_id, bvh_record.object_id);
+        }
+    }
+
+    // And it fits in the same box
+    let mut list_box = Aabb::new(Point3::new(0., 0., 0.), Point3::new(0., 0., 0.));
+    let mut bvh_box = list_box;
+    assert!(list.bounding_box(&mut list_box));
+    assert!(bvh.bounding_box(&mut bvh_box));
+    assert_eq!(list_box, bvh_box);
+}
+
+#[test]
+fn test_bvh_with_unbounded_objects() {
+    // Given a BVH of a sphere above an infinite floor
+    let material = std::sync::Arc::new(Lambertian::new(Vec3::new(0.5, 0.5, 0.5)));
+    let sphere = Sphere::new(Point3::new(0., 5., 0.), 1., material);
+    let bvh = BvhNode::new(vec![Box::new(Floor), Box::new(sphere)]);
+
+    // When I shoot a ray down through the sphere, and one far away from it
+    let through_sphere = Ray::new(Point3::new(0., 100., 0.), Vec3::new(0., -1., 0.));
+    let far_away = Ray::new(Point3::new(1000., 1., 1000.), Vec3::new(0., -1., 0.));
+    let mut sphere_record = HitRecord::new();
+    let mut floor_record = HitRecord::new();
+
+    // Then the first hits the sphere, and the second the floor
+    assert!(bvh.hit(through_sphere, 0.001, f64::INFINITY, &mut sphere_record));
+    assert_eq!(sphere_record.object_id, 2);
+    assert!(bvh.hit(far_away, 0.001, f64::INFINITY, &mut floor_record));
+    assert_eq!(floor_record.object_id, 1);
+
+    assert_eq!(sphere_record.t, 94.);
+
+    // And the BVH has no bounding box, like the floor
+    let mut bvh_box = Aabb::new(Point3::new(0., 0., 0.), Point3::new(0., 0., 0.));
+    assert!(!bvh.bounding_box(&mut bvh_box));
+}
+
+#[test]
+fn test_bvh_keeps_hits_from_objects_it_misses_after() {
+    // Given a BVH of a sphere, and an object that writes to the record even when it is missed
+    let material = std::sync::Arc::new(Lambertian::new(Vec3::new(0.5, 0.5, 0.5)));
+    let sphere = Sphere::new(Point3::new(0., 5., 0.), 1., material);
+    let bvh = BvhNode::new(vec![Box::new(sphere), Box::new(Scribbler)]);
+
+    // When I shoot a ray down through the sphere
+    let ray = Ray::new(Point3::new(0., 100., 0.), Vec3::new(0., -1., 0.));
+    let mut record = HitRecord::new();
+
+    // Then it hits the top of the sphere, as the missed object leaves it alone
+    assert!(bvh.hit(ray, 0.001, f64::INFINITY, &mut record));
+    assert_eq!(record.object_id, 1);
+    assert_eq!(record.t, 94.);
+    assert_eq!(record.normal, Vec3::new(0., 1., 0.));
+}
+
+#[test]
+fn test_empty_bvh() {
+    // Given a BVH without objects
+    let bvh = BvhNode::new(Vec::new());
+
+    // When I shoot a ray
+    let ray = Ray::new(Point3::new(0., 0., 0.), Vec3::new(1., 0., 0.));
+
+    // Then it doesn't hit anything
+    assert!(!bvh.hit(ray, 0.001, f64::INFINITY, &mut HitRecord::new()));
+}
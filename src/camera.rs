@@ -1,17 +1,26 @@
+use rand::Rng;
+
 use crate::point3::Point3;
 use crate::ray::Ray;
 use crate::vec3::Vec3;
 
 #[derive(Debug, Clone, Copy)]
 pub struct Camera {
-    origin: Point3,
+    pub(crate) origin: Point3,
     lower_left_corner: Point3,
     horizontal: Vec3,
     vertical: Vec3,
-    w: Vec3,
     u: Vec3,
     v: Vec3,
     lens_radius: f64,
+    // What the camera was made with, to write it to scene files
+    pub(crate) look_at: Point3,
+    pub(crate) vup: Vec3,
+    pub(crate) vertical_field_of_view: f64,
+    pub(crate) aperture: f64,
+    pub(crate) focus_distance: f64,
+    pub(crate) shutter_open: f64,
+    pub(crate) shutter_close: f64,
 }
 
 impl Camera {
@@ -45,20 +54,68 @@ impl Camera {
             horizontal,
             vertical,
             lower_left_corner,
-            w,
             u,
             v,
             lens_radius,
+            look_at,
+            vup,
+            vertical_field_of_view,
+            aperture,
+            focus_distance,
+            shutter_open: 0.,
+            shutter_close: 0.,
+        }
+    }
+
+    /// The same camera with its shutter open from `open` to `close`, sending rays at random times in
+    /// between so that moving objects are blurred
+    pub fn with_shutter(self, open: f64, close: f64) -> Self {
+        Self {
+            shutter_open: open,
+            shutter_close: close,
+            ..self
         }
     }
 
-    pub fn get_ray(self, u: f64, v: f64) -> Ray {
-        let rd = self.lens_radius * Vec3::random_in_unit_disk();
+    pub fn get_ray<R: Rng + ?Sized>(self, u: f64, v: f64, rng: &mut R) -> Ray {
+        let rd = self.lens_radius * Vec3::random_in_unit_disk(rng);
         let offset: Vec3 = self.u * rd.x + self.v * rd.y;
+        // Only draw a time when there is a choice, so still images get the same random numbers as before
+        let time = if self.shutter_close > self.shutter_open {
+            rng.gen_range(self.shutter_open..self.shutter_close)
+        } else {
+            self.shutter_open
+        };
 
-        Ray::new(
+        Ray::at_time(
             self.origin + offset,
             self.lower_left_corner + u * self.horizontal + v * self.vertical - self.origin - offset,
+            time,
         )
     }
 }
+
+#[test]
+fn test_rays_are_sent_while_the_shutter_is_open() {
+    // Given a camera with its shutter open from 1 to 2
+    let camera = Camera::new(
+        Point3::new(0., 0., 0.),
+        Point3::new(0., 0., -1.),
+        Vec3::new(0., 1., 0.),
+        90.,
+        1.,
+        0.,
+        1.,
+    )
+    .with_shutter(1., 2.);
+    let mut rng = crate::rng::rng_from_seed(1);
+
+    // When I send rays through it
+    let times: Vec<f64> = (0..100)
+        .map(|_| camera.get_ray(0.5, 0.5, &mut rng).time)
+        .collect();
+
+    // Then they are sent at different times in the interval
+    assert!(times.iter().all(|time| (1. ..2.).contains(time)));
+    assert!(times.iter().any(|&time| time != times[0]));
+}
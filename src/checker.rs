@@ -0,0 +1,111 @@
+use std::sync::Arc;
+
+use crate::{
+    color::Color,
+    point3::Point3,
+    scene_file::{PatternDescription, TextureDescription},
+    solid_color::SolidColor,
+    texture::Texture,
+};
+
+/// How a `Checker` lays out its squares
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CheckerMapping {
+    /// Cubes of `size` filling space, so that the pattern doesn't depend on surface coordinates
+    Space { size: f64 },
+    /// `columns` by `rows` squares over the surface coordinates from 0 to 1
+    Uv { columns: f64, rows: f64 },
+}
+
+/// A checkerboard alternating between two textures
+#[derive(Clone)]
+pub struct Checker {
+    even: Arc<dyn Texture>,
+    odd: Arc<dyn Texture>,
+    mapping: CheckerMapping,
+}
+
+impl Checker {
+    pub fn new(even: Arc<dyn Texture>, odd: Arc<dyn Texture>, mapping: CheckerMapping) -> Self {
+        Self { even, odd, mapping }
+    }
+
+    pub fn from_colors(even: Color, odd: Color, mapping: CheckerMapping) -> Self {
+        Self::new(
+            Arc::new(SolidColor::new(even)),
+            Arc::new(SolidColor::new(odd)),
+            mapping,
+        )
+    }
+}
+
+impl Texture for Checker {
+    fn value(&self, u: f64, v: f64, point: Point3) -> Color {
+        let cell = match self.mapping {
+            CheckerMapping::Space { size } => {
+                (point.x / size).floor() + (point.y / size).floor() + (point.z / size).floor()
+            }
+            CheckerMapping::Uv { columns, rows } => (u * columns).floor() + (v * rows).floor(),
+        };
+        if cell.rem_euclid(2.) < 1. {
+            self.even.value(u, v, point)
+        } else {
+            self.odd.value(u, v, point)
+        }
+    }
+
+    fn describe(&self) -> Option<TextureDescription> {
+        let even = Box::new(self.even.describe()?);
+        let odd = Box::new(self.odd.describe()?);
+        let pattern = match self.mapping {
+            CheckerMapping::Space { size } => PatternDescription::Checker { even, odd, size },
+            CheckerMapping::Uv { columns, rows } => PatternDescription::UvChecker {
+                even,
+                odd,
+                columns,
+                rows,
+            },
+        };
+        Some(TextureDescription::Pattern(pattern))
+    }
+}
+
+#[test]
+fn test_space_checker() {
+    // Given a checker of unit cubes
+    let checker = Checker::from_colors(
+        Color::new(1., 1., 1.),
+        Color::new(0., 0., 0.),
+        CheckerMapping::Space { size: 1. },
+    );
+
+    // When I look up points in neighbouring cubes, on both sides of the origin
+    let value = |x, y, z| checker.value(0., 0., Point3::new(x, y, z));
+
+    // Then they alternate
+    assert_eq!(value(0.5, 0.5, 0.5), Color::new(1., 1., 1.));
+    assert_eq!(value(1.5, 0.5, 0.5), Color::new(0., 0., 0.));
+    assert_eq!(value(-0.5, 0.5, 0.5), Color::new(0., 0., 0.));
+    assert_eq!(value(-0.5, -0.5, 0.5), Color::new(1., 1., 1.));
+}
+
+#[test]
+fn test_uv_checker() {
+    // Given a checker of 4 by 2 squares
+    let checker = Checker::from_colors(
+        Color::new(1., 1., 1.),
+        Color::new(0., 0., 0.),
+        CheckerMapping::Uv {
+            columns: 4.,
+            rows: 2.,
+        },
+    );
+
+    // When I look up surface coordinates
+    let value = |u, v| checker.value(u, v, Point3::new(0., 0., 0.));
+
+    // Then the squares follow them, not the point
+    assert_eq!(value(0.1, 0.1), Color::new(1., 1., 1.));
+    assert_eq!(value(0.3, 0.1), Color::new(0., 0., 0.));
+    assert_eq!(value(0.3, 0.6), Color::new(1., 1., 1.));
+}
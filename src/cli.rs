@@ -0,0 +1,639 @@
+// Sys
+use std::error::Error;
+use std::path::{Path, PathBuf};
+
+// 3rd party
+use clap::{error::ErrorKind, Args, CommandFactory, Parser, Subcommand, ValueEnum};
+use rand::random;
+
+// Project
+use ray_tracing::{
+    load_scene,
+    rng::rng_from_seed,
+    scenes::{controlled_scene, random_scene, simple_light_scene},
+    Background, Camera, Color, ExrCompression, ExrSampleType, ExrSettings, HittableList, Pass,
+    Point3, RenderSettings, SceneDescription, TileOrder, Vec3, DEFAULT_PIXELS_PER_DEGREE,
+};
+
+/// Render a scene to an image
+#[derive(Debug, Parser)]
+#[command(name = "ray-tracing", version, args_conflicts_with_subcommands = true)]
+pub struct Cli {
+    #[command(subcommand)]
+    pub command: Option<Command>,
+
+    /// Width of the image in pixels [default: from the scene file, or 400]
+    #[arg(long, value_parser = clap::value_parser!(u16).range(2..))]
+    pub width: Option<u16>,
+
+    /// Height of the image in pixels [default: from the scene file, or the width at a 3:2 aspect ratio]
+    #[arg(long, value_parser = clap::value_parser!(u16).range(2..))]
+    pub height: Option<u16>,
+
+    /// Number of samples per pixel [default: from the scene file, or 100]
+    #[arg(long, value_parser = clap::value_parser!(u32).range(1..))]
+    pub spp: Option<u32>,
+
+    /// Maximum number of times a ray bounces off surfaces [default: from the scene file, or 50]
+    #[arg(long, value_parser = clap::value_parser!(i64).range(1..))]
+    pub max_depth: Option<i64>,
+
+    /// Which scene to render: random, controlled, simple-light, or a JSON scene file
+    #[arg(long, value_parser = parse_scene, default_value = "random")]
+    pub scene: SceneArg,
+
+    /// The scene file picked with --scene, once it is read
+    #[arg(skip)]
+    pub scene_file: Option<SceneDescription>,
+
+    /// Where the camera is, as x,y,z [default: depends on the scene]
+    #[arg(long, value_parser = parse_vec3, allow_hyphen_values = true)]
+    pub camera_look_from: Option<Vec3>,
+
+    /// What the camera looks at, as x,y,z [default: depends on the scene]
+    #[arg(long, value_parser = parse_vec3, allow_hyphen_values = true)]
+    pub camera_look_at: Option<Vec3>,
+
+    /// Which direction is up for the camera, as x,y,z [default: from the scene file, or 0,1,0]
+    #[arg(long, value_parser = parse_vec3, allow_hyphen_values = true)]
+    pub camera_vup: Option<Vec3>,
+
+    /// Vertical field of view in degrees [default: depends on the scene]
+    #[arg(long, value_parser = parse_field_of_view)]
+    pub camera_vfov: Option<f64>,
+
+    /// Diameter of the lens, 0 keeps everything in focus [default: depends on the scene]
+    #[arg(long, value_parser = parse_non_negative)]
+    pub camera_aperture: Option<f64>,
+
+    /// Distance from the camera at which things are in focus [default: depends on the scene]
+    #[arg(long, value_parser = parse_positive)]
+    pub camera_focus_distance: Option<f64>,
+
+    /// When the shutter opens, in the time of moving objects [default: from the scene file, or 0]
+    #[arg(long, allow_hyphen_values = true)]
+    pub camera_shutter_open: Option<f64>,
+
+    /// When the shutter closes, a later time blurs moving objects [default: from the scene file, or 0]
+    #[arg(long, allow_hyphen_values = true)]
+    pub camera_shutter_close: Option<f64>,
+
+    /// Write the scene to this JSON scene file instead of rendering it, to render it again later
+    #[arg(long)]
+    pub save_scene: Option<PathBuf>,
+
+    /// File to write the image to, stdout when left out or "-"
+    #[arg(long, short)]
+    pub output: Option<PathBuf>,
+
+    /// Image format [default: from the output file extension, ppm for stdout]
+    #[arg(long, value_enum)]
+    pub format: Option<OutputFormat>,
+
+    /// Number of render threads, 0 uses one thread per core
+    #[arg(long, default_value_t = 0)]
+    pub threads: usize,
+
+    /// Width and height in pixels of the tiles the image is split into
+    #[arg(long, default_value_t = 32, value_parser = clap::value_parser!(u16).range(1..))]
+    pub tile_size: u16,
+
+    /// The order in which tiles are rendered
+    #[arg(long, value_enum, default_value_t = TileOrderChoice::Scanline)]
+    pub tile_order: TileOrderChoice,
+
+    /// Render passes to store as layers of an OpenEXR image
+    #[arg(
+        long,
+        value_enum,
+        value_delimiter = ',',
+        default_value = "beauty,depth,normal,albedo,object-id"
+    )]
+    pub passes: Vec<PassChoice>,
+
+    /// Compression of OpenEXR images
+    #[arg(long, value_enum, default_value_t = ExrCompressionChoice::Zip)]
+    pub exr_compression: ExrCompressionChoice,
+
+    /// Precision of the floating point channels of OpenEXR images
+    #[arg(long, value_enum, default_value_t = ExrSampleTypeChoice::Half)]
+    pub exr_precision: ExrSampleTypeChoice,
+
+    /// What rays that don't hit anything see: sky, rainbow, or a color as r,g,b [default: depends on the scene]
+    #[arg(long, value_parser = parse_background)]
+    pub background: Option<Background>,
+
+    /// Seed for the scene and all sampling, the same seed gives the same image [default: from the scene file, or random]
+    #[arg(long)]
+    pub seed: Option<u64>,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum Command {
+    /// Compare an image with a reference, printing the RMSE, PSNR and a FLIP-style perceptual error
+    Diff(DiffArgs),
+}
+
+#[derive(Debug, Args)]
+pub struct DiffArgs {
+    /// The image to compare with, in any format that can be written
+    pub reference: PathBuf,
+
+    /// The image to compare
+    pub image: PathBuf,
+
+    /// File to write the absolute difference of every pixel to, in linear radiance
+    #[arg(long)]
+    pub difference: Option<PathBuf>,
+
+    /// File to write the perceptual error of every pixel to, from black for none to white for the most
+    #[arg(long)]
+    pub error_map: Option<PathBuf>,
+
+    /// How many pixels fit in one degree of vision where the images are looked at
+    #[arg(long, default_value_t = DEFAULT_PIXELS_PER_DEGREE, value_parser = parse_positive)]
+    pub pixels_per_degree: f64,
+
+    /// Fail when the perceptual error is above this value
+    #[arg(long, value_parser = parse_non_negative)]
+    pub max_flip: Option<f64>,
+}
+
+/// What --scene picks
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SceneArg {
+    Builtin(SceneChoice),
+    /// A JSON scene file, see `SceneDescription`
+    File(PathBuf),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum SceneChoice {
+    /// The final scene of the book, with lots of small random spheres
+    Random,
+    /// Three spheres of different materials on a ground sphere
+    Controlled,
+    /// A sphere lit only by glowing spheres, against a black background
+    SimpleLight,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum OutputFormat {
+    /// ASCII Portable PixMap (P3)
+    Ppm,
+    /// Binary Portable PixMap (P6) with 8 bits per channel
+    PpmBinary,
+    /// Binary Portable PixMap (P6) with 16 bits per channel
+    Ppm16,
+    /// Portable Network Graphics with 8 bits per channel
+    Png,
+    /// Portable Network Graphics with 16 bits per channel
+    Png16,
+    /// Portable Network Graphics with 8 bits per channel, transparent where the background shows
+    PngRgba,
+    /// Portable Network Graphics with 16 bits per channel, transparent where the background shows
+    Png16Rgba,
+    /// Radiance RGBE, with linear radiance
+    Hdr,
+    /// Portable Float Map, with linear radiance as 32 bit floats
+    Pfm,
+    /// OpenEXR, with linear radiance and the passes picked with --passes
+    Exr,
+}
+
+impl OutputFormat {
+    /// Whether the format stores linear radiance, instead of gamma corrected values in 0 - 1 range
+    pub fn is_high_dynamic_range(self) -> bool {
+        matches!(
+            self,
+            OutputFormat::Hdr | OutputFormat::Pfm | OutputFormat::Exr
+        )
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum TileOrderChoice {
+    Scanline,
+    Spiral,
+    Hilbert,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum PassChoice {
+    Beauty,
+    Depth,
+    Normal,
+    Albedo,
+    ObjectId,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum ExrCompressionChoice {
+    None,
+    Zip,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum ExrSampleTypeChoice {
+    Half,
+    Float,
+}
+
+impl From<PassChoice> for Pass {
+    fn from(choice: PassChoice) -> Self {
+        match choice {
+            PassChoice::Beauty => Pass::Beauty,
+            PassChoice::Depth => Pass::Depth,
+            PassChoice::Normal => Pass::Normal,
+            PassChoice::Albedo => Pass::Albedo,
+            PassChoice::ObjectId => Pass::ObjectId,
+        }
+    }
+}
+
+impl From<TileOrderChoice> for TileOrder {
+    fn from(choice: TileOrderChoice) -> Self {
+        match choice {
+            TileOrderChoice::Scanline => TileOrder::Scanline,
+            TileOrderChoice::Spiral => TileOrder::Spiral,
+            TileOrderChoice::Hilbert => TileOrder::Hilbert,
+        }
+    }
+}
+
+// Camera parameters that look good for a scene, used for every camera flag that isn't given
+struct CameraDefaults {
+    look_from: Point3,
+    look_at: Point3,
+    vup: Vec3,
+    vertical_field_of_view: f64,
+    aperture: f64,
+    focus_distance: f64,
+    shutter_open: f64,
+    shutter_close: f64,
+}
+
+impl SceneChoice {
+    pub fn build(self, seed: u64) -> HittableList {
+        match self {
+            SceneChoice::Random => random_scene(&mut rng_from_seed(seed)),
+            SceneChoice::Controlled => controlled_scene(),
+            SceneChoice::SimpleLight => simple_light_scene(),
+        }
+    }
+
+    fn background(self) -> Background {
+        match self {
+            SceneChoice::Random | SceneChoice::Controlled => Background::Sky,
+            SceneChoice::SimpleLight => Background::Solid(Color::new(0., 0., 0.)),
+        }
+    }
+
+    fn camera_defaults(self) -> CameraDefaults {
+        match self {
+            SceneChoice::Random => CameraDefaults {
+                look_from: Point3::new(13., 2., 3.),
+                look_at: Point3::new(0., 0., 0.),
+                vup: Vec3::new(0., 1., 0.),
+                vertical_field_of_view: 20.,
+                aperture: 0.1,
+                focus_distance: 10.,
+                shutter_open: 0.,
+                shutter_close: 0.,
+            },
+            SceneChoice::Controlled => CameraDefaults {
+                look_from: Point3::new(-2., 2., 1.),
+                look_at: Point3::new(0., 0., -1.),
+                vup: Vec3::new(0., 1., 0.),
+                vertical_field_of_view: 20.,
+                aperture: 0.,
+                focus_distance: 3.4,
+                shutter_open: 0.,
+                shutter_close: 0.,
+            },
+            SceneChoice::SimpleLight => CameraDefaults {
+                look_from: Point3::new(26., 3., 6.),
+                look_at: Point3::new(0., 2., 0.),
+                vup: Vec3::new(0., 1., 0.),
+                vertical_field_of_view: 20.,
+                aperture: 0.,
+                focus_distance: 10.,
+                shutter_open: 0.,
+                shutter_close: 0.,
+            },
+        }
+    }
+}
+
+impl Cli {
+    /// Parse the command line and read the scene file, exiting with a helpful message if the arguments
+    /// don't make sense together
+    pub fn parse_and_validate() -> Self {
+        let mut cli = Self::parse();
+        if let SceneArg::File(path) = &cli.scene {
+            match load_scene(path) {
+                Ok(scene) => cli.scene_file = Some(scene),
+                Err(error) => Self::command().error(ErrorKind::InvalidValue, error).exit(),
+            }
+        }
+        cli.seed = cli
+            .seed
+            .or_else(|| cli.scene_file.as_ref()?.render.seed)
+            .or_else(|| Some(random()));
+        if let Err(message) = cli.validate() {
+            Self::command()
+                .error(ErrorKind::ArgumentConflict, message)
+                .exit();
+        }
+        cli
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        if let Some(Command::Diff(args)) = &self.command {
+            for path in args.difference.iter().chain(args.error_map.iter()) {
+                format_for_path(path)?;
+            }
+            return Ok(());
+        }
+
+        let defaults = self.camera_defaults();
+        let look_from = self.camera_look_from.unwrap_or(defaults.look_from);
+        let look_at = self.camera_look_at.unwrap_or(defaults.look_at);
+        let vup = self.camera_vup.unwrap_or(defaults.vup);
+        let view_direction = look_at - look_from;
+
+        if view_direction.is_near_zero() {
+            return Err(format!(
+                "the camera looks from and at the same point ({})",
+                format_vec3(look_from)
+            ));
+        }
+        if vup.cross(view_direction).is_near_zero() {
+            return Err(format!(
+                "--camera-vup {} can't be parallel to the view direction {}",
+                format_vec3(vup),
+                format_vec3(view_direction)
+            ));
+        }
+        let shutter_open = self.camera_shutter_open.unwrap_or(defaults.shutter_open);
+        let shutter_close = self.camera_shutter_close.unwrap_or(defaults.shutter_close);
+        if shutter_close < shutter_open {
+            return Err(format!(
+                "the shutter closes at {} before it opens at {}",
+                shutter_close, shutter_open
+            ));
+        }
+        if self.format.is_none() {
+            self.output_format()?;
+        }
+
+        Ok(())
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.seed.unwrap_or(0)
+    }
+
+    pub fn image_width(&self) -> u16 {
+        self.width
+            .or_else(|| self.scene_file.as_ref()?.render.width)
+            .unwrap_or(400)
+    }
+
+    pub fn image_height(&self) -> u16 {
+        let aspect_ratio: f64 = 3. / 2.;
+        self.height
+            .or_else(|| self.scene_file.as_ref()?.render.height)
+            .unwrap_or_else(|| {
+                (f64::from(self.image_width()) / aspect_ratio)
+                    .floor()
+                    .max(2.) as u16
+            })
+    }
+
+    pub fn render_settings(&self) -> RenderSettings {
+        let render = self.scene_file.as_ref().map(|scene| &scene.render);
+        let mut settings = RenderSettings::new(
+            self.image_width(),
+            self.image_height(),
+            self.spp
+                .or_else(|| render?.samples_per_pixel)
+                .unwrap_or(100),
+            self.max_depth.or_else(|| render?.max_depth).unwrap_or(50),
+        );
+        settings.threads = self.threads;
+        settings.tile_size = self.tile_size.into();
+        settings.tile_order = self.tile_order.into();
+        settings.seed = self.seed();
+        settings.background = self
+            .background
+            .or_else(|| render?.background)
+            .unwrap_or(match self.scene {
+                SceneArg::Builtin(choice) => choice.background(),
+                SceneArg::File(_) => Background::Sky,
+            });
+        settings
+    }
+
+    /// The objects of the scene
+    pub fn build_world(&self) -> Result<HittableList, Box<dyn Error>> {
+        match (&self.scene, &self.scene_file) {
+            (SceneArg::Builtin(choice), _) => Ok(choice.build(self.seed())),
+            (SceneArg::File(path), Some(scene)) => scene
+                .build_world(path.parent().unwrap_or_else(|| Path::new("")))
+                .map_err(|error| format!("{}: {}", path.display(), error).into()),
+            (SceneArg::File(path), None) => {
+                Err(format!("the scene file '{}' isn't read yet", path.display()).into())
+            }
+        }
+    }
+
+    fn camera_defaults(&self) -> CameraDefaults {
+        match (&self.scene, &self.scene_file) {
+            (SceneArg::Builtin(choice), _) => choice.camera_defaults(),
+            (SceneArg::File(_), Some(scene)) => CameraDefaults {
+                look_from: scene.camera.look_from,
+                look_at: scene.camera.look_at,
+                vup: scene.camera.vup,
+                vertical_field_of_view: scene.camera.vertical_field_of_view,
+                aperture: scene.camera.aperture,
+                focus_distance: scene.camera.focus_distance(),
+                shutter_open: scene.camera.shutter_open,
+                shutter_close: scene.camera.shutter_close,
+            },
+            // Only until the file is read, this is what the camera of a scene file defaults to
+            (SceneArg::File(_), None) => CameraDefaults {
+                look_from: Point3::new(0., 0., 0.),
+                look_at: Point3::new(0., 0., -1.),
+                vup: Vec3::new(0., 1., 0.),
+                vertical_field_of_view: 20.,
+                aperture: 0.,
+                focus_distance: 1.,
+                shutter_open: 0.,
+                shutter_close: 0.,
+            },
+        }
+    }
+
+    pub fn camera(&self) -> Camera {
+        let defaults = self.camera_defaults();
+        Camera::new(
+            self.camera_look_from.unwrap_or(defaults.look_from),
+            self.camera_look_at.unwrap_or(defaults.look_at),
+            self.camera_vup.unwrap_or(defaults.vup),
+            self.camera_vfov.unwrap_or(defaults.vertical_field_of_view),
+            f64::from(self.image_width()) / f64::from(self.image_height()),
+            self.camera_aperture.unwrap_or(defaults.aperture),
+            self.camera_focus_distance
+                .unwrap_or(defaults.focus_distance),
+        )
+        .with_shutter(
+            self.camera_shutter_open.unwrap_or(defaults.shutter_open),
+            self.camera_shutter_close.unwrap_or(defaults.shutter_close),
+        )
+    }
+
+    pub fn exr_settings(&self) -> ExrSettings {
+        ExrSettings::new(
+            match self.exr_compression {
+                ExrCompressionChoice::None => ExrCompression::Uncompressed,
+                ExrCompressionChoice::Zip => ExrCompression::Zip,
+            },
+            match self.exr_precision {
+                ExrSampleTypeChoice::Half => ExrSampleType::Half,
+                ExrSampleTypeChoice::Float => ExrSampleType::Float,
+            },
+            self.passes.iter().map(|&pass| pass.into()).collect(),
+        )
+    }
+
+    /// The output path, or `None` when the image should go to stdout
+    pub fn output_path(&self) -> Option<&PathBuf> {
+        self.output.as_ref().filter(|path| path.as_os_str() != "-")
+    }
+
+    /// The requested format, or the one that matches the extension of the output file
+    pub fn output_format(&self) -> Result<OutputFormat, String> {
+        if let Some(format) = self.format {
+            return Ok(format);
+        }
+
+        match self.output_path() {
+            Some(path) => format_for_path(path)
+                .map_err(|message| format!("{}, use --format to pick one", message)),
+            None => Ok(OutputFormat::Ppm),
+        }
+    }
+}
+
+/// The image format that matches the extension of a file
+pub fn format_for_path(path: &Path) -> Result<OutputFormat, String> {
+    let extension = path
+        .extension()
+        .and_then(|extension| extension.to_str())
+        .unwrap_or("")
+        .to_lowercase();
+    OutputFormat::from_str(&extension, true).map_err(|_| {
+        format!(
+            "can't tell the image format from the extension of '{}'",
+            path.display()
+        )
+    })
+}
+
+fn format_vec3(vec3: Vec3) -> String {
+    format!("{},{},{}", vec3.x, vec3.y, vec3.z)
+}
+
+fn parse_vec3(value: &str) -> Result<Vec3, String> {
+    let components: Vec<&str> = value.split(',').map(str::trim).collect();
+    let error = || {
+        format!(
+            "expected three comma separated numbers like 13,2,3, got '{}'",
+            value
+        )
+    };
+    if components.len() != 3 {
+        return Err(error());
+    }
+
+    let mut vec3 = Vec3::new(0., 0., 0.);
+    for (i, component) in components.iter().enumerate() {
+        vec3[i as u8] = component
+            .parse::<f64>()
+            .ok()
+            .filter(|number| number.is_finite())
+            .ok_or_else(error)?;
+    }
+    Ok(vec3)
+}
+
+fn parse_scene(value: &str) -> Result<SceneArg, String> {
+    if let Ok(choice) = SceneChoice::from_str(value, true) {
+        return Ok(SceneArg::Builtin(choice));
+    }
+    let path = PathBuf::from(value);
+    if path
+        .extension()
+        .is_some_and(|extension| extension.eq_ignore_ascii_case("json"))
+    {
+        return Ok(SceneArg::File(path));
+    }
+    let names: Vec<String> = SceneChoice::value_variants()
+        .iter()
+        .filter_map(|choice| choice.to_possible_value())
+        .map(|value| value.get_name().to_string())
+        .collect();
+    Err(format!(
+        "possible values: {}, or a .json scene file",
+        names.join(", ")
+    ))
+}
+
+fn parse_background(value: &str) -> Result<Background, String> {
+    match value {
+        "sky" => Ok(Background::Sky),
+        "rainbow" => Ok(Background::Rainbow),
+        _ => parse_vec3(value).map(Background::Solid).map_err(|_| {
+            format!(
+                "expected sky, rainbow or a color like 0,0,0, got '{}'",
+                value
+            )
+        }),
+    }
+}
+
+fn parse_number(value: &str) -> Result<f64, String> {
+    value
+        .parse::<f64>()
+        .ok()
+        .filter(|number| number.is_finite())
+        .ok_or_else(|| format!("expected a number, got '{}'", value))
+}
+
+fn parse_non_negative(value: &str) -> Result<f64, String> {
+    let number = parse_number(value)?;
+    if number < 0. {
+        return Err(format!("expected a number of at least 0, got {}", number));
+    }
+    Ok(number)
+}
+
+fn parse_positive(value: &str) -> Result<f64, String> {
+    let number = parse_number(value)?;
+    if number <= 0. {
+        return Err(format!("expected a number greater than 0, got {}", number));
+    }
+    Ok(number)
+}
+
+fn parse_field_of_view(value: &str) -> Result<f64, String> {
+    let number = parse_number(value)?;
+    if number <= 0. || number >= 180. {
+        return Err(format!(
+            "expected an angle between 0 and 180 degrees, got {}",
+            number
+        ));
+    }
+    Ok(number)
+}
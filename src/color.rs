@@ -1,3 +1,45 @@
 pub use crate::vec3::Vec3;
 
 pub use Vec3 as Color;
+
+/// Convert linear radiance to display values with a gamma of 2, which is what 8 and 16 bit images expect
+pub fn gamma_correct(color: Color) -> Color {
+    Color::new(
+        f64::sqrt(color.x.max(0.)),
+        f64::sqrt(color.y.max(0.)),
+        f64::sqrt(color.z.max(0.)),
+    )
+}
+
+/// Convert display values with a gamma of 2 back to linear radiance, undoing `gamma_correct`
+pub fn linearize(color: Color) -> Color {
+    Color::new(
+        color.x.max(0.).powi(2),
+        color.y.max(0.).powi(2),
+        color.z.max(0.).powi(2),
+    )
+}
+
+#[test]
+fn test_gamma_correct() {
+    // Given a linear color
+    let color = Color::new(0.25, 1., 4.);
+
+    // When I gamma correct it
+    let corrected = gamma_correct(color);
+
+    // Then I get its square root, leaving values above 1 for the image writer to clamp
+    assert_eq!(corrected, Color::new(0.5, 1., 2.));
+}
+
+#[test]
+fn test_linearize() {
+    // Given a linear color
+    let color = Color::new(0.25, 1., 4.);
+
+    // When I gamma correct it and linearize the result
+    let round_trip = linearize(gamma_correct(color));
+
+    // Then I get the color I started with
+    assert_eq!(round_trip, color);
+}
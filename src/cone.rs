@@ -0,0 +1,130 @@
+use std::error::Error;
+use std::sync::Arc;
+
+use crate::{
+    aabb::Aabb,
+    cylinder::{around_y_axis, hit_cap, record_closest},
+    hittable::{HitRecord, Hittable},
+    material::Material,
+    point3::Point3,
+    polynomial::solve_quadratic,
+    ray::Ray,
+    scene_file::{ExportedMaterials, ObjectDescription},
+    vec3::Vec3,
+};
+
+/// A solid cone standing upright on the center of its round bottom cap, `base`, with its tip `height`
+/// above it
+///
+/// The texture coordinates go around its side from the -x side, with v going up to the tip, and across
+/// the cap like a decal.
+#[derive(Clone)]
+pub struct Cone {
+    base: Point3,
+    radius: f64,
+    height: f64,
+    material: Arc<dyn Material>,
+}
+
+impl Cone {
+    pub fn new(base: Point3, radius: f64, height: f64, material: Arc<dyn Material>) -> Self {
+        Self {
+            base,
+            radius,
+            height,
+            material,
+        }
+    }
+}
+
+impl Hittable for Cone {
+    // The side is where x² + z² = (k·(height - y))², with k = radius / height the slope of the side
+    fn hit(&self, ray: Ray, t_min: f64, t_max: f64, record: &mut HitRecord) -> bool {
+        let local = Ray::at_time(ray.origin - self.base, ray.direction, ray.time);
+        let (o, d) = (local.origin, local.direction);
+        let k2 = (self.radius / self.height).powi(2);
+        let below_tip = self.height - o.y;
+        let roots = solve_quadratic(
+            d.x * d.x + d.z * d.z - k2 * d.y * d.y,
+            2. * (o.x * d.x + o.z * d.z + k2 * below_tip * d.y),
+            o.x * o.x + o.z * o.z - k2 * below_tip * below_tip,
+        );
+        let side = roots
+            .into_iter()
+            .flat_map(|(first, second)| [first, second])
+            .filter_map(|t| {
+                let point = local.at(t);
+                if point.y < 0. || point.y > self.height {
+                    return None;
+                }
+                // The tip has no normal of its own, so it takes the one of the axis
+                let normal = Vec3::new(point.x, k2 * (self.height - point.y), point.z);
+                let normal = if normal.is_near_zero() {
+                    Vec3::new(0., 1., 0.)
+                } else {
+                    normal.unit_vector()
+                };
+                Some((t, normal, around_y_axis(point), point.y / self.height))
+            });
+        let cap = hit_cap(local, 0., self.radius, Vec3::new(0., -1., 0.));
+
+        record_closest(side.chain(cap), ray, t_min, t_max, &self.material, record)
+    }
+
+    fn bounding_box(&self, output_box: &mut Aabb) -> bool {
+        *output_box = Aabb::new(
+            self.base - Vec3::new(self.radius, 0., self.radius),
+            self.base + Vec3::new(self.radius, self.height, self.radius),
+        );
+        true
+    }
+
+    fn export(
+        &self,
+        materials: &mut ExportedMaterials,
+    ) -> Result<ObjectDescription, Box<dyn Error>> {
+        Ok(ObjectDescription::Cone {
+            base: self.base,
+            radius: self.radius,
+            height: self.height,
+            material: materials.name(&self.material)?,
+        })
+    }
+}
+
+#[test]
+fn test_cone_side_and_cap() {
+    // Given a cone as wide as it is high
+    let cone = Cone::new(
+        Point3::new(0., 0., 0.),
+        1.,
+        1.,
+        Arc::new(crate::lambertian::Lambertian::new(Vec3::new(0.5, 0.5, 0.5))),
+    );
+    let hit = |origin: Point3, direction: Vec3| {
+        let mut record = HitRecord::new();
+        let hit = cone.hit(
+            Ray::new(origin, direction),
+            0.001,
+            f64::INFINITY,
+            &mut record,
+        );
+        (hit, record)
+    };
+
+    // When rays come at it from the side halfway up, from below, and past its tip
+    let (side_hit, side) = hit(Point3::new(-5., 0.5, 0.), Vec3::new(1., 0., 0.));
+    let (bottom_hit, bottom) = hit(Point3::new(0.2, -1., 0.), Vec3::new(0., 1., 0.));
+    let (past_hit, _) = hit(Point3::new(-5., 0.9, 0.3), Vec3::new(1., 0., 0.));
+
+    // Then the side is hit where it is half as wide, with a normal leaning up
+    assert!(side_hit && bottom_hit && !past_hit);
+    assert!((side.t - 4.5).abs() < 1e-12);
+    let half = 0.5_f64.sqrt();
+    assert!((side.normal - Vec3::new(-half, half, 0.)).len() < 1e-12);
+    assert!((side.v - 0.5).abs() < 1e-12);
+
+    // And the bottom cap faces down
+    assert_eq!(bottom.t, 1.);
+    assert_eq!(bottom.normal, Vec3::new(0., -1., 0.));
+}
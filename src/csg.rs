@@ -0,0 +1,254 @@
+use std::error::Error;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    aabb::Aabb,
+    hittable::{HitRecord, Hittable},
+    point3::Point3,
+    ray::Ray,
+    scene_file::{ExportedMaterials, ObjectDescription},
+};
+
+// At most how many times a ray is followed through the surface of one side of a `Csg`, so that a
+// surface that isn't closed can't keep it going forever
+const MAX_CROSSINGS: usize = 64;
+
+/// How a `Csg` combines the space inside its two objects
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CsgOperation {
+    /// Inside either of them
+    Union,
+    /// Inside both of them
+    Intersection,
+    /// Inside the first but not the second, carving the second out of the first
+    Difference,
+}
+
+impl CsgOperation {
+    fn is_inside(self, in_left: bool, in_right: bool) -> bool {
+        match self {
+            CsgOperation::Union => in_left || in_right,
+            CsgOperation::Intersection => in_left && in_right,
+            CsgOperation::Difference => in_left && !in_right,
+        }
+    }
+}
+
+/// Two solids combined by a `CsgOperation`, like a hollow ball, a cut-away model or a carved block
+///
+/// Both objects have to be closed, with normals pointing out, so that hitting them from the front
+/// means going in. A ray is followed through every surface of both objects, keeping track of whether
+/// it is inside each of them, and the combination is hit where the ray goes in or out of it. Surfaces
+/// keep their material, and hits are from the front when the ray goes into the combination.
+pub struct Csg {
+    operation: CsgOperation,
+    left: Box<dyn Hittable>,
+    right: Box<dyn Hittable>,
+}
+
+impl Csg {
+    pub fn new(operation: CsgOperation, left: Box<dyn Hittable>, right: Box<dyn Hittable>) -> Self {
+        Self {
+            operation,
+            left,
+            right,
+        }
+    }
+}
+
+// Where a ray goes through the surface of an object from t_min on, up to and including the first
+// crossing past t_max, which tells whether the ray is inside when it doesn't cross before t_max
+fn crossings(object: &dyn Hittable, ray: Ray, t_min: f64, t_max: f64) -> Vec<HitRecord> {
+    let mut crossings = Vec::new();
+    let mut record = HitRecord::new();
+    let mut from = t_min;
+    while crossings.len() < MAX_CROSSINGS && object.hit(ray, from, f64::INFINITY, &mut record) {
+        // Just past this crossing, so that it isn't found again
+        from = record.t + 1e-9 * record.t.abs().max(1.);
+        crossings.push(record.clone());
+        if record.t > t_max {
+            break;
+        }
+    }
+    crossings
+}
+
+impl Hittable for Csg {
+    fn hit(&self, ray: Ray, t_min: f64, t_max: f64, record: &mut HitRecord) -> bool {
+        let left = crossings(self.left.as_ref(), ray, t_min, t_max);
+        let right = crossings(self.right.as_ref(), ray, t_min, t_max);
+
+        // A ray that first leaves an object started inside it
+        let starts_inside =
+            |crossings: &[HitRecord]| crossings.first().is_some_and(|c| !c.is_front_face);
+        let mut in_left = starts_inside(&left);
+        let mut in_right = starts_inside(&right);
+        let mut inside = self.operation.is_inside(in_left, in_right);
+
+        let (mut left, mut right) = (left.into_iter().peekable(), right.into_iter().peekable());
+        loop {
+            let from_left = match (left.peek(), right.peek()) {
+                (Some(l), Some(r)) => l.t <= r.t,
+                (Some(_), None) => true,
+                (None, Some(_)) => false,
+                (None, None) => return false,
+            };
+            let crossing = if from_left {
+                let crossing = left.next().unwrap();
+                in_left = crossing.is_front_face;
+                crossing
+            } else {
+                let crossing = right.next().unwrap();
+                in_right = crossing.is_front_face;
+                crossing
+            };
+            if crossing.t > t_max {
+                return false;
+            }
+
+            let was_inside = inside;
+            inside = self.operation.is_inside(in_left, in_right);
+            if inside != was_inside {
+                // The normal already faces the ray, only which side it comes from can change
+                *record = crossing;
+                record.is_front_face = inside;
+                return true;
+            }
+        }
+    }
+
+    fn bounding_box(&self, output_box: &mut Aabb) -> bool {
+        let origin = Point3::new(0., 0., 0.);
+        let mut left_box = Aabb::new(origin, origin);
+        let mut right_box = Aabb::new(origin, origin);
+        let has_left_box = self.left.bounding_box(&mut left_box);
+        let has_right_box = self.right.bounding_box(&mut right_box);
+        match self.operation {
+            CsgOperation::Union if has_left_box && has_right_box => {
+                *output_box = left_box.surrounding(right_box);
+                true
+            }
+            CsgOperation::Union => false,
+            // Only where both are, or one of them when the other one has no bounds
+            CsgOperation::Intersection if has_left_box && has_right_box => {
+                let minimum = Point3::new(
+                    left_box.minimum.x.max(right_box.minimum.x),
+                    left_box.minimum.y.max(right_box.minimum.y),
+                    left_box.minimum.z.max(right_box.minimum.z),
+                );
+                let maximum = Point3::new(
+                    left_box.maximum.x.min(right_box.maximum.x),
+                    left_box.maximum.y.min(right_box.maximum.y),
+                    left_box.maximum.z.min(right_box.maximum.z),
+                );
+                // Boxes that don't overlap leave nothing, which is kept as an empty box where they are
+                *output_box = Aabb::new(
+                    minimum,
+                    Point3::new(
+                        maximum.x.max(minimum.x),
+                        maximum.y.max(minimum.y),
+                        maximum.z.max(minimum.z),
+                    ),
+                );
+                true
+            }
+            CsgOperation::Intersection if has_right_box => {
+                *output_box = right_box;
+                true
+            }
+            CsgOperation::Intersection | CsgOperation::Difference => {
+                *output_box = left_box;
+                has_left_box
+            }
+        }
+    }
+
+    fn export(
+        &self,
+        materials: &mut ExportedMaterials,
+    ) -> Result<ObjectDescription, Box<dyn Error>> {
+        Ok(ObjectDescription::Csg {
+            operation: self.operation,
+            left: Box::new(self.left.export(materials)?),
+            right: Box::new(self.right.export(materials)?),
+        })
+    }
+}
+
+#[cfg(test)]
+fn ball(x: f64, radius: f64) -> Box<dyn Hittable> {
+    Box::new(crate::sphere::Sphere::new(
+        Point3::new(x, 0., 0.),
+        radius,
+        std::sync::Arc::new(crate::lambertian::Lambertian::new(crate::vec3::Vec3::new(
+            0.5, 0.5, 0.5,
+        ))),
+    ))
+}
+
+#[cfg(test)]
+fn csg_hits(csg: &Csg, origin_x: f64) -> Vec<(f64, bool)> {
+    // Every hit along a ray going along +x, from the front or not
+    let ray = Ray::new(
+        Point3::new(origin_x, 0., 0.),
+        crate::vec3::Vec3::new(1., 0., 0.),
+    );
+    let mut hits = Vec::new();
+    let mut record = HitRecord::new();
+    let mut from = 0.001;
+    while csg.hit(ray, from, f64::INFINITY, &mut record) {
+        hits.push((record.t, record.is_front_face));
+        from = record.t + 0.001;
+    }
+    hits
+}
+
+#[test]
+fn test_csg_operations() {
+    // Given two overlapping balls along x, from -2 to 2 and from 0 to 4, combined every way
+    let combined = |operation| Csg::new(operation, ball(0., 2.), ball(2., 2.));
+
+    // When a ray goes through them along x from the far left
+    // Then the union is one solid from -2 to 4, the intersection the overlap from 0 to 2, and the
+    // difference what is left of the first ball, from -2 to 0
+    assert_eq!(
+        csg_hits(&combined(CsgOperation::Union), -10.),
+        [(8., true), (14., false)]
+    );
+    assert_eq!(
+        csg_hits(&combined(CsgOperation::Intersection), -10.),
+        [(10., true), (12., false)]
+    );
+    assert_eq!(
+        csg_hits(&combined(CsgOperation::Difference), -10.),
+        [(8., true), (10., false)]
+    );
+
+    // And a ray starting inside the overlap leaves the union and the intersection, and enters the
+    // difference nowhere in front of it
+    assert_eq!(csg_hits(&combined(CsgOperation::Union), 1.), [(3., false)]);
+    assert_eq!(
+        csg_hits(&combined(CsgOperation::Intersection), 1.),
+        [(1., false)]
+    );
+    assert!(csg_hits(&combined(CsgOperation::Difference), 1.).is_empty());
+}
+
+#[test]
+fn test_hollow_ball() {
+    // Given a ball with a smaller ball carved out of its middle
+    let hollow = Csg::new(CsgOperation::Difference, ball(0., 1.), ball(0., 0.5));
+
+    // When a ray goes through it
+    let hits = csg_hits(&hollow, -5.);
+
+    // Then it goes into the shell, out into the hollow, into the shell and out again
+    assert_eq!(hits, [(4., true), (4.5, false), (5.5, true), (6., false)]);
+
+    // And its bounds are those of the outer ball
+    let mut output_box = Aabb::new(Point3::new(0., 0., 0.), Point3::new(0., 0., 0.));
+    assert!(hollow.bounding_box(&mut output_box));
+    assert_eq!(output_box.minimum, Point3::new(-1., -1., -1.));
+}
@@ -0,0 +1,127 @@
+use std::error::Error;
+use std::sync::Arc;
+
+use crate::{
+    aabb::Aabb,
+    hittable::{HitRecord, Hittable},
+    material::Material,
+    point3::Point3,
+    quad::Quad,
+    ray::Ray,
+    scene_file::{ExportedMaterials, ObjectDescription},
+    vec3::Vec3,
+};
+
+/// An axis-aligned box between two opposite corners, made of six `Quad`s facing out
+///
+/// It is called a box in scene files; `Box` is taken in Rust. Each side has its own texture coordinates
+/// from 0 to 1.
+#[derive(Clone)]
+pub struct Cuboid {
+    minimum: Point3,
+    maximum: Point3,
+    material: Arc<dyn Material>,
+    sides: [Quad; 6],
+}
+
+impl Cuboid {
+    /// A box between any two opposite corners
+    pub fn new(a: Point3, b: Point3, material: Arc<dyn Material>) -> Self {
+        let minimum = Point3::new(a.x.min(b.x), a.y.min(b.y), a.z.min(b.z));
+        let maximum = Point3::new(a.x.max(b.x), a.y.max(b.y), a.z.max(b.z));
+        let size = maximum - minimum;
+        let dx = Vec3::new(size.x, 0., 0.);
+        let dy = Vec3::new(0., size.y, 0.);
+        let dz = Vec3::new(0., 0., size.z);
+        let side = |corner: Point3, first_edge: Vec3, second_edge: Vec3| {
+            Quad::new(corner, first_edge, second_edge, Arc::clone(&material))
+        };
+        // The edges of each side turn counterclockwise seen from outside, so its normal points out
+        let sides = [
+            side(Point3::new(minimum.x, minimum.y, maximum.z), dx, dy),
+            side(Point3::new(maximum.x, minimum.y, maximum.z), -dz, dy),
+            side(Point3::new(maximum.x, minimum.y, minimum.z), -dx, dy),
+            side(minimum, dz, dy),
+            side(Point3::new(minimum.x, maximum.y, maximum.z), dx, -dz),
+            side(minimum, dx, dz),
+        ];
+        Self {
+            minimum,
+            maximum,
+            material,
+            sides,
+        }
+    }
+}
+
+impl Hittable for Cuboid {
+    // The sides are tried here rather than in a `HittableList`, which would set `object_id` per side
+    fn hit(&self, ray: Ray, t_min: f64, t_max: f64, record: &mut HitRecord) -> bool {
+        let mut was_anything_hit = false;
+        let mut closest_so_far = t_max;
+        for side in self.sides.iter() {
+            if side.hit(ray, t_min, closest_so_far, record) {
+                was_anything_hit = true;
+                closest_so_far = record.t;
+            }
+        }
+        was_anything_hit
+    }
+
+    fn bounding_box(&self, output_box: &mut Aabb) -> bool {
+        *output_box = Aabb::new(self.minimum, self.maximum);
+        true
+    }
+
+    fn export(
+        &self,
+        materials: &mut ExportedMaterials,
+    ) -> Result<ObjectDescription, Box<dyn Error>> {
+        Ok(ObjectDescription::Cuboid {
+            minimum: self.minimum,
+            maximum: self.maximum,
+            material: materials.name(&self.material)?,
+        })
+    }
+}
+
+#[test]
+fn test_box_sides_face_out() {
+    // Given a box given by its corners in any order
+    let cuboid = Cuboid::new(
+        Point3::new(1., 2., 3.),
+        Point3::new(-1., -2., -3.),
+        Arc::new(crate::lambertian::Lambertian::new(Vec3::new(0.5, 0.5, 0.5))),
+    );
+    let directions = [
+        Vec3::new(1., 0., 0.),
+        Vec3::new(-1., 0., 0.),
+        Vec3::new(0., 1., 0.),
+        Vec3::new(0., -1., 0.),
+        Vec3::new(0., 0., 1.),
+        Vec3::new(0., 0., -1.),
+    ];
+
+    for &direction in directions.iter() {
+        // When a ray comes at it from outside along each axis
+        let mut record = HitRecord::new();
+        let ray = Ray::new(Point3::new(0., 0., 0.) + 10. * direction, -direction);
+        assert!(cuboid.hit(ray, 0.001, f64::INFINITY, &mut record));
+
+        // Then it hits the near side from the front, facing back out
+        assert!(record.is_front_face, "{}", direction);
+        assert_eq!(record.normal, direction);
+        let extent = Vec3::new(1., 2., 3.);
+        let distance = direction.x.abs() * extent.x
+            + direction.y.abs() * extent.y
+            + direction.z.abs() * extent.z;
+        assert!((record.t - (10. - distance)).abs() < 1e-12);
+    }
+
+    // And a ray from inside hits a side from the back
+    let mut record = HitRecord::new();
+    let ray = Ray::new(Point3::new(0., 0., 0.), Vec3::new(0., 0., 1.));
+    assert!(cuboid.hit(ray, 0.001, f64::INFINITY, &mut record));
+    assert!(!record.is_front_face);
+    assert_eq!(record.t, 3.);
+}
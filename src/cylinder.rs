@@ -0,0 +1,187 @@
+use std::error::Error;
+use std::f64::consts::PI;
+use std::sync::Arc;
+
+use crate::{
+    aabb::Aabb,
+    hittable::{HitRecord, Hittable},
+    material::Material,
+    point3::Point3,
+    polynomial::solve_quadratic,
+    ray::Ray,
+    scene_file::{ExportedMaterials, ObjectDescription},
+    vec3::Vec3,
+};
+
+/// A solid cylinder standing upright on the center of its bottom cap, `base`, closed at both ends
+///
+/// The texture coordinates go around its side like a label from the -x side, with v going up, and
+/// across each cap like a decal. Turn it on its side with a `Transformed`.
+#[derive(Clone)]
+pub struct Cylinder {
+    base: Point3,
+    radius: f64,
+    height: f64,
+    material: Arc<dyn Material>,
+}
+
+impl Cylinder {
+    pub fn new(base: Point3, radius: f64, height: f64, material: Arc<dyn Material>) -> Self {
+        Self {
+            base,
+            radius,
+            height,
+            material,
+        }
+    }
+}
+
+// A hit on a round solid in its own space, where it stands on the origin around the y axis: how far it
+// is along the ray, its outward normal and its texture coordinates
+pub(crate) type LocalHit = (f64, Vec3, f64, f64);
+
+// How far around the y axis a point is, from 0 to 1, starting from -x like `Sphere`
+pub(crate) fn around_y_axis(point: Point3) -> f64 {
+    (f64::atan2(-point.z, point.x) + PI) / (2. * PI)
+}
+
+// Where a ray in the space of a round solid crosses its flat cap of `radius` at `height`
+pub(crate) fn hit_cap(ray: Ray, height: f64, radius: f64, normal: Vec3) -> Option<LocalHit> {
+    if ray.direction.y == 0. {
+        return None;
+    }
+    let t = (height - ray.origin.y) / ray.direction.y;
+    let point = ray.at(t);
+    if point.x * point.x + point.z * point.z > radius * radius {
+        return None;
+    }
+    let u = 0.5 + point.x / (2. * radius);
+    let v = 0.5 + point.z / (2. * radius);
+    Some((t, normal, u, v))
+}
+
+// Fill in the record with the closest of the hits between t_min and t_max. The hits are along the ray
+// in the space of the solid, which is only moved, so they are as far along `ray`.
+pub(crate) fn record_closest(
+    hits: impl IntoIterator<Item = LocalHit>,
+    ray: Ray,
+    t_min: f64,
+    t_max: f64,
+    material: &Arc<dyn Material>,
+    record: &mut HitRecord,
+) -> bool {
+    let closest = hits
+        .into_iter()
+        .filter(|(t, ..)| t_min <= *t && *t <= t_max)
+        .min_by(|a, b| a.0.partial_cmp(&b.0).unwrap());
+    let (t, outward_normal, u, v) = match closest {
+        Some(hit) => hit,
+        None => return false,
+    };
+
+    record.t = t;
+    record.point = ray.at(t);
+    record.u = u;
+    record.v = v;
+    record.set_face_normal(ray, outward_normal);
+    record.material = Arc::clone(material);
+
+    true
+}
+
+impl Hittable for Cylinder {
+    // The side is where x² + z² = radius², between the caps
+    fn hit(&self, ray: Ray, t_min: f64, t_max: f64, record: &mut HitRecord) -> bool {
+        let local = Ray::at_time(ray.origin - self.base, ray.direction, ray.time);
+        let (o, d) = (local.origin, local.direction);
+        let roots = solve_quadratic(
+            d.x * d.x + d.z * d.z,
+            2. * (o.x * d.x + o.z * d.z),
+            o.x * o.x + o.z * o.z - self.radius * self.radius,
+        );
+        let side = roots
+            .into_iter()
+            .flat_map(|(first, second)| [first, second])
+            .filter_map(|t| {
+                let point = local.at(t);
+                if point.y < 0. || point.y > self.height {
+                    return None;
+                }
+                let normal = Vec3::new(point.x, 0., point.z) / self.radius;
+                Some((t, normal, around_y_axis(point), point.y / self.height))
+            });
+        let caps = [
+            hit_cap(local, 0., self.radius, Vec3::new(0., -1., 0.)),
+            hit_cap(local, self.height, self.radius, Vec3::new(0., 1., 0.)),
+        ];
+
+        record_closest(
+            side.chain(caps.iter().flatten().copied()),
+            ray,
+            t_min,
+            t_max,
+            &self.material,
+            record,
+        )
+    }
+
+    fn bounding_box(&self, output_box: &mut Aabb) -> bool {
+        *output_box = Aabb::new(
+            self.base - Vec3::new(self.radius, 0., self.radius),
+            self.base + Vec3::new(self.radius, self.height, self.radius),
+        );
+        true
+    }
+
+    fn export(
+        &self,
+        materials: &mut ExportedMaterials,
+    ) -> Result<ObjectDescription, Box<dyn Error>> {
+        Ok(ObjectDescription::Cylinder {
+            base: self.base,
+            radius: self.radius,
+            height: self.height,
+            material: materials.name(&self.material)?,
+        })
+    }
+}
+
+#[test]
+fn test_cylinder_side_and_caps() {
+    // Given a cylinder of radius 1 and height 2 standing on y = 1
+    let cylinder = Cylinder::new(
+        Point3::new(0., 1., 0.),
+        1.,
+        2.,
+        Arc::new(crate::lambertian::Lambertian::new(Vec3::new(0.5, 0.5, 0.5))),
+    );
+    let hit = |origin: Point3, direction: Vec3| {
+        let mut record = HitRecord::new();
+        let hit = cylinder.hit(
+            Ray::new(origin, direction),
+            0.001,
+            f64::INFINITY,
+            &mut record,
+        );
+        (hit, record)
+    };
+
+    // When rays come at its side, its top, from inside it and past it
+    let (side_hit, side) = hit(Point3::new(-5., 2.5, 0.), Vec3::new(1., 0., 0.));
+    let (top_hit, top) = hit(Point3::new(0.5, 10., 0.), Vec3::new(0., -1., 0.));
+    let (inside_hit, inside) = hit(Point3::new(0., 2., 0.), Vec3::new(0., 1., 0.));
+    let (above_hit, _) = hit(Point3::new(-5., 3.5, 0.), Vec3::new(1., 0., 0.));
+
+    // Then the side is hit at the -x end of its label, three quarters up
+    assert!(side_hit && top_hit && inside_hit && !above_hit);
+    assert_eq!(side.t, 4.);
+    assert_eq!(side.normal, Vec3::new(-1., 0., 0.));
+    assert!(side.u.abs() < 1e-12 && (side.v - 0.75).abs() < 1e-12);
+
+    // And the caps face out, seen from outside or from inside
+    assert_eq!(top.t, 7.);
+    assert_eq!(top.normal, Vec3::new(0., 1., 0.));
+    assert!(top.is_front_face);
+    assert_eq!(inside.t, 1.);
+    assert!(!inside.is_front_face);
+}
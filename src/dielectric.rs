@@ -1,6 +1,9 @@
-use rand::random;
+use rand::{Rng, RngCore};
 
-use crate::{color::Color, hittable::HitRecord, material::Material, point3::Vec3, ray::Ray};
+use crate::{
+    color::Color, hittable::HitRecord, material::Material, point3::Vec3, ray::Ray,
+    scene_file::MaterialDescription,
+};
 
 #[derive(Debug, Clone, Copy)]
 pub struct Dielectric {
@@ -17,7 +20,7 @@ fn reflectance(cosine: f64, refraction_index: f64) -> f64 {
     // Use Schlick's approximation for reflectance.
     let mut r0 = (1. - refraction_index) / (1. + refraction_index);
     r0 = r0 * r0;
-    return r0 + (1. - r0) * f64::powi(1. - cosine, 5);
+    r0 + (1. - r0) * f64::powi(1. - cosine, 5)
 }
 
 impl Material for Dielectric {
@@ -27,6 +30,7 @@ impl Material for Dielectric {
         record: &HitRecord,
         attenuation: &mut Color,
         scattered: &mut Ray,
+        rng: &mut dyn RngCore,
     ) -> bool {
         *attenuation = Color::new(1., 1., 1.);
         let refraction_ratio = if record.is_front_face {
@@ -41,14 +45,20 @@ impl Material for Dielectric {
 
         let can_refract = refraction_ratio * sin_theta <= 1.;
         let direction =
-            if can_refract && reflectance(cos_theta, refraction_ratio) <= random::<f64>() {
+            if can_refract && reflectance(cos_theta, refraction_ratio) <= rng.gen::<f64>() {
                 unit_direction.refract(record.normal, refraction_ratio)
             } else {
                 unit_direction.reflect(record.normal)
             };
 
-        *scattered = Ray::new(record.point, direction);
+        *scattered = Ray::at_time(record.point, direction, ray_in.time);
 
         true
     }
+
+    fn describe(&self) -> Option<MaterialDescription> {
+        Some(MaterialDescription::Dielectric {
+            refraction_index: self.refraction_index,
+        })
+    }
 }
@@ -0,0 +1,40 @@
+use rand::RngCore;
+
+use crate::{
+    color::Color, hittable::HitRecord, material::Material, ray::Ray,
+    scene_file::MaterialDescription,
+};
+
+/// A light source that emits the same light in every direction and doesn't reflect anything
+#[derive(Debug, Clone, Copy)]
+pub struct DiffuseLight {
+    emit: Color,
+}
+
+impl DiffuseLight {
+    /// Colors brighter than 1 make lights that can light up a scene on their own
+    pub fn new(emit: Color) -> Self {
+        Self { emit }
+    }
+}
+
+impl Material for DiffuseLight {
+    fn scatter(
+        &self,
+        _: Ray,
+        _: &HitRecord,
+        _: &mut Color,
+        _: &mut Ray,
+        _: &mut dyn RngCore,
+    ) -> bool {
+        false
+    }
+
+    fn emitted(&self, _: &HitRecord) -> Color {
+        self.emit
+    }
+
+    fn describe(&self) -> Option<MaterialDescription> {
+        Some(MaterialDescription::DiffuseLight { emit: self.emit })
+    }
+}
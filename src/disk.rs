@@ -0,0 +1,119 @@
+use std::error::Error;
+use std::sync::Arc;
+
+use crate::{
+    aabb::Aabb,
+    hittable::{HitRecord, Hittable},
+    material::Material,
+    plane::{intersect_plane, perpendicular_axes},
+    point3::Point3,
+    ray::Ray,
+    scene_file::{ExportedMaterials, ObjectDescription},
+    triangle::BOUNDING_BOX_PADDING,
+    vec3::Vec3,
+};
+
+/// A flat round disk facing where `normal` points to, like a round table top or a spot light
+///
+/// Textures are laid over it like a decal: the texture coordinates go from 0 to 1 across the square
+/// around it.
+#[derive(Clone)]
+pub struct Disk {
+    center: Point3,
+    normal: Vec3,
+    radius: f64,
+    material: Arc<dyn Material>,
+}
+
+impl Disk {
+    pub fn new(center: Point3, normal: Vec3, radius: f64, material: Arc<dyn Material>) -> Self {
+        Self {
+            center,
+            normal: normal.unit_vector(),
+            radius,
+            material,
+        }
+    }
+}
+
+impl Hittable for Disk {
+    fn hit(&self, ray: Ray, t_min: f64, t_max: f64, record: &mut HitRecord) -> bool {
+        let t = match intersect_plane(self.center, self.normal, ray, t_min, t_max) {
+            Some(t) => t,
+            None => return false,
+        };
+        let point = ray.at(t);
+        let offset = point - self.center;
+        if offset.len_squared() > self.radius * self.radius {
+            return false;
+        }
+
+        record.t = t;
+        record.point = point;
+        let (first, second) = perpendicular_axes(self.normal);
+        record.u = 0.5 + offset.dot(first) / (2. * self.radius);
+        record.v = 0.5 + offset.dot(second) / (2. * self.radius);
+        record.set_face_normal(ray, self.normal);
+        record.material = Arc::clone(&self.material);
+
+        true
+    }
+
+    // Along each axis, the disk reaches as far as the radius times how much it faces sideways to it
+    fn bounding_box(&self, output_box: &mut Aabb) -> bool {
+        let extent = |axis: f64| self.radius * (1. - axis * axis).max(0.).sqrt();
+        let extent = Vec3::new(
+            extent(self.normal.x),
+            extent(self.normal.y),
+            extent(self.normal.z),
+        );
+        *output_box =
+            Aabb::new(self.center - extent, self.center + extent).padded(BOUNDING_BOX_PADDING);
+        true
+    }
+
+    fn export(
+        &self,
+        materials: &mut ExportedMaterials,
+    ) -> Result<ObjectDescription, Box<dyn Error>> {
+        Ok(ObjectDescription::Disk {
+            center: self.center,
+            normal: self.normal,
+            radius: self.radius,
+            material: materials.name(&self.material)?,
+        })
+    }
+}
+
+#[test]
+fn test_disk() {
+    // Given a disk of radius 2 at the origin, facing +z
+    let disk = Disk::new(
+        Point3::new(0., 0., 0.),
+        Vec3::new(0., 0., 1.),
+        2.,
+        Arc::new(crate::lambertian::Lambertian::new(Vec3::new(0.5, 0.5, 0.5))),
+    );
+
+    // When rays come at it through its center, inside its rim and just outside it
+    let hit = |x: f64, y: f64| {
+        let mut record = HitRecord::new();
+        let ray = Ray::new(Point3::new(x, y, 3.), Vec3::new(0., 0., -1.));
+        let hit = disk.hit(ray, 0.001, f64::INFINITY, &mut record);
+        (hit, record)
+    };
+    let (center_hit, center) = hit(0., 0.);
+
+    // Then the ones within the radius hit it, with the center in the middle of its texture
+    assert!(center_hit);
+    assert_eq!(center.t, 3.);
+    assert!((center.u - 0.5).abs() < 1e-12 && (center.v - 0.5).abs() < 1e-12);
+    assert!(hit(1.4, 1.4).0);
+    assert!(!hit(1.5, 1.5).0);
+
+    // And its bounding box is flat along z
+    let mut output_box = Aabb::new(Point3::new(0., 0., 0.), Point3::new(0., 0., 0.));
+    assert!(disk.bounding_box(&mut output_box));
+    assert_eq!(output_box.maximum.x, 2.);
+    assert!(output_box.maximum.z < 1e-3);
+}
@@ -0,0 +1,188 @@
+use std::error::Error;
+
+use crate::{
+    color::{gamma_correct, linearize, Color},
+    vec3::Vec3,
+};
+
+/// Everything that is rendered for one pixel: the color, and passes describing the first thing the camera sees
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Pixel {
+    /// Linear radiance
+    pub color: Color,
+    /// Fraction of the samples that hit an object instead of the background
+    pub alpha: f64,
+    /// Average distance from the camera to the objects that were hit, infinite when nothing was
+    pub depth: f64,
+    /// Average surface normal in world space, facing the camera
+    pub normal: Vec3,
+    /// Average color the first surface reflected
+    pub albedo: Color,
+    /// Position plus one of the top level object that was hit by the first sample that hit anything, 0 for the background
+    pub object_id: u32,
+}
+
+impl Pixel {
+    pub fn new(color: Color) -> Self {
+        Self {
+            color,
+            alpha: 1.,
+            depth: f64::INFINITY,
+            normal: Vec3::new(0., 0., 0.),
+            albedo: Color::new(0., 0., 0.),
+            object_id: 0,
+        }
+    }
+}
+
+impl Default for Pixel {
+    fn default() -> Self {
+        Self::new(Color::new(0., 0., 0.))
+    }
+}
+
+/// A rendered image: a grid of pixels stored row by row, from the top left to the bottom right
+#[derive(Debug, Clone, PartialEq)]
+pub struct Framebuffer {
+    width: usize,
+    height: usize,
+    pixels: Vec<Pixel>,
+}
+
+impl Framebuffer {
+    pub fn new(width: usize, height: usize) -> Self {
+        Self::from_pixels(width, height, vec![Pixel::default(); width * height])
+    }
+
+    pub fn from_pixels(width: usize, height: usize, pixels: Vec<Pixel>) -> Self {
+        Self::try_from_pixels(width, height, pixels).unwrap_or_else(|error| panic!("{}", error))
+    }
+
+    /// Like `from_pixels`, but with an error instead of a panic when the pixels don't fill the
+    /// framebuffer, for sizes read from files
+    pub fn try_from_pixels(
+        width: usize,
+        height: usize,
+        pixels: Vec<Pixel>,
+    ) -> Result<Self, Box<dyn Error>> {
+        let pixel_count = value_count(width, height, 1)?;
+        if pixels.len() != pixel_count {
+            return Err(format!(
+                "a {} by {} framebuffer needs {} pixels, got {}",
+                width,
+                height,
+                pixel_count,
+                pixels.len()
+            )
+            .into());
+        }
+        Ok(Self {
+            width,
+            height,
+            pixels,
+        })
+    }
+
+    /// A framebuffer holding only colors, given in rows from top to bottom
+    pub fn from_colors(rows: Vec<Vec<Color>>) -> Self {
+        let height = rows.len();
+        let width = rows.first().map_or(0, |row| row.len());
+        let pixels = rows.into_iter().flatten().map(Pixel::new).collect();
+        Self::from_pixels(width, height, pixels)
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn pixel(&self, x: usize, y: usize) -> &Pixel {
+        &self.pixels[y * self.width + x]
+    }
+
+    pub fn pixel_mut(&mut self, x: usize, y: usize) -> &mut Pixel {
+        &mut self.pixels[y * self.width + x]
+    }
+
+    pub fn pixels(&self) -> &[Pixel] {
+        &self.pixels
+    }
+
+    pub fn pixels_mut(&mut self) -> &mut [Pixel] {
+        &mut self.pixels
+    }
+
+    /// The rows of pixels, from top to bottom
+    pub fn rows(&self) -> impl DoubleEndedIterator<Item = &[Pixel]> {
+        self.pixels.chunks(self.width.max(1))
+    }
+
+    /// The colors of all pixels in rows from top to bottom
+    pub fn colors(&self) -> Vec<Vec<Color>> {
+        self.rows()
+            .map(|row| row.iter().map(|pixel| pixel.color).collect())
+            .collect()
+    }
+
+    /// A copy with gamma corrected colors, which is what 8 and 16 bit image formats expect
+    pub fn gamma_corrected(&self) -> Self {
+        let mut corrected = self.clone();
+        for pixel in corrected.pixels.iter_mut() {
+            pixel.color = gamma_correct(pixel.color);
+        }
+        corrected
+    }
+
+    /// A copy with linear colors, undoing `gamma_corrected` for images read from 8 and 16 bit formats
+    pub fn linearized(&self) -> Self {
+        let mut linear = self.clone();
+        for pixel in linear.pixels.iter_mut() {
+            pixel.color = linearize(pixel.color);
+        }
+        linear
+    }
+}
+
+/// How many values an image of `width` by `height` pixels holds with `per_pixel` values for each, or
+/// an error when that is too many to count, for sizes read from files
+pub(crate) fn value_count(
+    width: usize,
+    height: usize,
+    per_pixel: usize,
+) -> Result<usize, Box<dyn Error>> {
+    width
+        .checked_mul(height)
+        .and_then(|pixel_count| pixel_count.checked_mul(per_pixel))
+        .ok_or_else(|| format!("a {} by {} image is too big", width, height).into())
+}
+
+#[test]
+fn test_from_colors() {
+    // Given rows of colors
+    let rows = vec![
+        vec![Color::new(0., 0., 0.), Color::new(1., 0., 0.)],
+        vec![Color::new(0., 1., 0.), Color::new(0., 0., 1.)],
+    ];
+
+    // When I make a framebuffer out of them
+    let framebuffer = Framebuffer::from_colors(rows.clone());
+
+    // Then its pixels are in the same place
+    assert_eq!((framebuffer.width(), framebuffer.height()), (2, 2));
+    assert_eq!(framebuffer.pixel(1, 0).color, Color::new(1., 0., 0.));
+    assert_eq!(framebuffer.pixel(0, 1).color, Color::new(0., 1., 0.));
+    assert_eq!(framebuffer.colors(), rows);
+}
+
+#[test]
+fn test_try_from_pixels() {
+    // Given sizes that the pixels don't fill, or that are too big to count
+    // When I make framebuffers of them
+    // Then I get errors instead of panics
+    assert!(Framebuffer::try_from_pixels(2, 1, vec![Pixel::default()]).is_err());
+    assert!(Framebuffer::try_from_pixels(usize::MAX, 2, Vec::new()).is_err());
+    assert!(Framebuffer::try_from_pixels(1, 1, vec![Pixel::default()]).is_ok());
+}
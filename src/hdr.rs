@@ -0,0 +1,325 @@
+use std::error::Error;
+
+use crate::{
+    color::Color,
+    framebuffer::{value_count, Framebuffer, Pixel},
+};
+
+/// Write the image as a Radiance RGBE (`.hdr`) file, keeping the linear radiance of every pixel
+///
+/// See http://paulbourke.net/dataformats/pic/ for the format. Scanlines are run length encoded when
+/// they are between 8 and 32767 pixels wide, as other readers expect.
+pub fn write_hdr(
+    framebuffer: &Framebuffer,
+    mut writer: impl std::io::Write,
+) -> Result<(), Box<dyn Error>> {
+    // Write file header
+    write!(
+        writer,
+        "#?RADIANCE\nFORMAT=32-bit_rle_rgbe\n\n-Y {} +X {}\n",
+        framebuffer.height(),
+        framebuffer.width()
+    )?;
+
+    // Write pixels
+    for row in framebuffer.rows() {
+        let scanline: Vec<[u8; 4]> = row.iter().map(|pixel| to_rgbe(pixel.color)).collect();
+        if (8..0x8000).contains(&scanline.len()) {
+            write_rle_scanline(&scanline, &mut writer)?;
+        } else {
+            for pixel in scanline {
+                writer.write_all(&pixel)?;
+            }
+        }
+    }
+
+    // Exit
+    Ok(())
+}
+
+// Share one exponent between the three channels, each keeping an 8 bit mantissa
+fn to_rgbe(pixel: Color) -> [u8; 4] {
+    let (red, green, blue) = (pixel.x.max(0.), pixel.y.max(0.), pixel.z.max(0.));
+    let brightest = red.max(green).max(blue);
+    if brightest < 1e-32 || !brightest.is_finite() {
+        return [0, 0, 0, 0];
+    }
+
+    // Find the exponent for which brightest = mantissa * 2^exponent with a mantissa in 0.5 - 1 range,
+    // saturating at the largest exponent the byte can hold
+    let exponent = (brightest.log2().floor() as i32 + 1).min(127);
+    let scale = 256. / 2_f64.powi(exponent);
+    [
+        (red * scale).min(255.) as u8,
+        (green * scale).min(255.) as u8,
+        (blue * scale).min(255.) as u8,
+        (exponent + 128) as u8,
+    ]
+}
+
+// Write a scanline as four runs of channels, each split into runs of equal bytes and dumps of differing ones
+fn write_rle_scanline(
+    scanline: &[[u8; 4]],
+    writer: &mut impl std::io::Write,
+) -> Result<(), Box<dyn Error>> {
+    let width = scanline.len();
+    writer.write_all(&[2, 2, (width >> 8) as u8, (width & 0xff) as u8])?;
+
+    for channel in 0..4 {
+        let bytes: Vec<u8> = scanline.iter().map(|pixel| pixel[channel]).collect();
+        let mut start = 0;
+        while start < bytes.len() {
+            let run_length = bytes[start..]
+                .iter()
+                .take(127)
+                .take_while(|&&byte| byte == bytes[start])
+                .count();
+            if run_length >= 4 {
+                writer.write_all(&[128 + run_length as u8, bytes[start]])?;
+                start += run_length;
+                continue;
+            }
+
+            // Dump bytes until the next run of at least four equal bytes starts
+            let mut end = start;
+            while end < bytes.len() && end - start < 128 {
+                let upcoming_run = bytes[end..]
+                    .iter()
+                    .take(4)
+                    .take_while(|&&byte| byte == bytes[end])
+                    .count();
+                if upcoming_run >= 4 {
+                    break;
+                }
+                end += 1;
+            }
+            writer.write_all(&[(end - start) as u8])?;
+            writer.write_all(&bytes[start..end])?;
+            start = end;
+        }
+    }
+
+    Ok(())
+}
+
+/// Read a Radiance RGBE (`.hdr`) file, with flat or run length encoded scanlines
+///
+/// Only the standard orientation, rows from top to bottom with pixels from left to right, is supported.
+pub fn read_hdr(mut reader: impl std::io::Read) -> Result<Framebuffer, Box<dyn Error>> {
+    let mut data = Vec::new();
+    reader.read_to_end(&mut data)?;
+
+    // Read file header, lines of variables that end with an empty line
+    let mut lines = data.split(|&byte| byte == b'\n');
+    let mut position = 0;
+    let mut line = |position: &mut usize| {
+        let line = lines.next().unwrap_or(&[]);
+        *position += line.len() + 1;
+        String::from_utf8_lossy(line).into_owned()
+    };
+    if !line(&mut position).starts_with("#?") {
+        return Err("expected a Radiance file starting with #?".into());
+    }
+    loop {
+        let variable = line(&mut position);
+        if variable.is_empty() {
+            break;
+        }
+        if variable.starts_with("FORMAT=") && variable != "FORMAT=32-bit_rle_rgbe" {
+            return Err(format!("only RGBE Radiance files can be read, got {}", variable).into());
+        }
+        if position >= data.len() {
+            return Err("the Radiance file ends in the middle of its header".into());
+        }
+    }
+    let resolution = line(&mut position);
+    let (height, width) = match resolution.split_whitespace().collect::<Vec<_>>().as_slice() {
+        ["-Y", height, "+X", width] => (height.parse::<usize>()?, width.parse::<usize>()?),
+        _ => return Err(format!("unsupported Radiance image orientation '{}'", resolution).into()),
+    };
+
+    // Read pixels
+    // Every scanline takes at least 4 bytes, and room is only made for as many pixels as there could
+    // be bytes for, so that a corrupt header can't ask for more memory than the file takes
+    let data = data.get(position..).unwrap_or(&[]);
+    if data.len() / 4 < height {
+        return Err("the Radiance file ends before all of its pixels".into());
+    }
+    let mut bytes = data.iter().copied();
+    let mut pixels = Vec::with_capacity(value_count(width, height, 1)?.min(data.len()));
+    for _ in 0..height {
+        let first: Vec<u8> = bytes.by_ref().take(4).collect();
+        if first.len() < 4 {
+            return Err("the Radiance file ends before all of its pixels".into());
+        }
+        let scanline = if (8..0x8000).contains(&width) && first[..2] == [2, 2] {
+            read_rle_scanline(width, &mut bytes)?
+        } else {
+            let mut scanline = vec![[first[0], first[1], first[2], first[3]]];
+            for _ in 1..width {
+                let pixel: Vec<u8> = bytes.by_ref().take(4).collect();
+                if pixel.len() < 4 {
+                    return Err("the Radiance file ends before all of its pixels".into());
+                }
+                scanline.push([pixel[0], pixel[1], pixel[2], pixel[3]]);
+            }
+            scanline
+        };
+        pixels.extend(scanline.into_iter().map(|rgbe| Pixel::new(from_rgbe(rgbe))));
+    }
+
+    Framebuffer::try_from_pixels(width, height, pixels)
+}
+
+// Read the four runs of channels of a scanline written by `write_rle_scanline`
+fn read_rle_scanline(
+    width: usize,
+    bytes: &mut impl Iterator<Item = u8>,
+) -> Result<Vec<[u8; 4]>, Box<dyn Error>> {
+    let mut next = || {
+        bytes
+            .next()
+            .ok_or("the Radiance file ends before all of its pixels")
+    };
+    let mut channels: Vec<Vec<u8>> = (0..4).map(|_| Vec::with_capacity(width)).collect();
+    for channel in channels.iter_mut() {
+        while channel.len() < width {
+            let count = next()?;
+            if count > 128 {
+                let value = next()?;
+                channel.extend(std::iter::repeat_n(value, usize::from(count - 128)));
+            } else {
+                for _ in 0..count {
+                    channel.push(next()?);
+                }
+            }
+        }
+        if channel.len() > width {
+            return Err("a run in the Radiance file is longer than its scanline".into());
+        }
+    }
+    Ok((0..width)
+        .map(|x| {
+            [
+                channels[0][x],
+                channels[1][x],
+                channels[2][x],
+                channels[3][x],
+            ]
+        })
+        .collect())
+}
+
+// Scale the three mantissas by their shared exponent, from the middle of the range they stand for
+fn from_rgbe(pixel: [u8; 4]) -> Color {
+    if pixel[3] == 0 {
+        return Color::new(0., 0., 0.);
+    }
+    let scale = 2_f64.powi(i32::from(pixel[3]) - 128 - 8);
+    Color::new(
+        (f64::from(pixel[0]) + 0.5) * scale,
+        (f64::from(pixel[1]) + 0.5) * scale,
+        (f64::from(pixel[2]) + 0.5) * scale,
+    )
+}
+
+#[test]
+fn test_to_rgbe() {
+    // Given colors both inside and outside of 0 - 1 range
+    // When I convert them to RGBE
+    // Then they share an exponent that fits the brightest channel
+    assert_eq!(to_rgbe(Color::new(0., 0., 0.)), [0, 0, 0, 0]);
+    assert_eq!(to_rgbe(Color::new(1., 0.5, 0.25)), [128, 64, 32, 129]);
+    assert_eq!(to_rgbe(Color::new(12., 3., 0.)), [192, 48, 0, 132]);
+
+    // And colors too bright for it saturate instead of wrapping around to dark ones
+    assert_eq!(to_rgbe(Color::new(1e300, 0., 0.)), [255, 0, 0, 255]);
+    assert_eq!(
+        to_rgbe(Color::new(2_f64.powi(128), 1., 0.)),
+        [255, 0, 0, 255]
+    );
+}
+
+#[test]
+fn test_write_hdr() {
+    // Given a small image with radiance above 1
+    let mut result = Vec::new();
+    let image = vec![vec![Color::new(0.5, 2., 40.), Color::new(0.1, 0.2, 0.3)]];
+
+    // When I write it as an HDR file
+    write_hdr(&Framebuffer::from_colors(image.clone()), &mut result).unwrap();
+
+    // Then it has a header and the pixels come back within the precision of RGBE
+    assert!(result.starts_with(b"#?RADIANCE\nFORMAT=32-bit_rle_rgbe\n\n-Y 1 +X 2\n"));
+    let framebuffer = read_hdr(result.as_slice()).unwrap();
+    assert_eq!((framebuffer.width(), framebuffer.height()), (2, 1));
+    for (pixel, expected) in framebuffer.pixels().iter().zip(image.into_iter().flatten()) {
+        let pixel = pixel.color;
+        let brightest = expected.x.max(expected.y).max(expected.z);
+        assert!((pixel - expected).len() < brightest / 100.);
+    }
+}
+
+#[test]
+fn test_write_hdr_run_length_encoded() {
+    // Given an image wide enough for run length encoding, with runs and noise
+    let mut result = Vec::new();
+    let row: Vec<Color> = (0..300)
+        .map(|x| {
+            if x < 200 {
+                Color::new(1., 1., 1.)
+            } else {
+                Color::new(f64::from(x) / 300., 0.5, 3.)
+            }
+        })
+        .collect();
+
+    // When I write it as an HDR file
+    write_hdr(&Framebuffer::from_colors(vec![row.clone()]), &mut result).unwrap();
+
+    // Then the runs make it smaller than a flat file, and the pixels come back unchanged
+    assert!(result.len() < 300 * 4);
+    let framebuffer = read_hdr(result.as_slice()).unwrap();
+    let pixels: Vec<[u8; 4]> = framebuffer
+        .pixels()
+        .iter()
+        .map(|pixel| to_rgbe(pixel.color))
+        .collect();
+    let expected: Vec<[u8; 4]> = row.into_iter().map(to_rgbe).collect();
+    assert_eq!(pixels, expected);
+}
+
+#[test]
+fn test_read_hdr_from_other_writers() {
+    // Given a flat RGBE file with a comment and extra variables in its header
+    let mut data =
+        b"#?RGBE\n# made by hand\nFORMAT=32-bit_rle_rgbe\nEXPOSURE=1\n\n-Y 2 +X 1\n".to_vec();
+    data.extend_from_slice(&[128, 64, 0, 129, 0, 0, 0, 0]);
+
+    // When I read it
+    let framebuffer = read_hdr(data.as_slice()).unwrap();
+
+    // Then I get the radiance of every pixel
+    assert_eq!(framebuffer.pixel(0, 0).color, from_rgbe([128, 64, 0, 129]));
+    assert_eq!(framebuffer.pixel(0, 1).color, Color::new(0., 0., 0.));
+}
+
+#[test]
+fn test_read_hdr_with_corrupt_size() {
+    // Given flat RGBE files with more rows or columns than they have pixels for
+    let sizes = [
+        "-Y 18446744073709551615 +X 2",
+        "-Y 1 +X 18446744073709551615",
+    ];
+
+    for size in sizes.iter() {
+        let mut data = format!("#?RADIANCE\nFORMAT=32-bit_rle_rgbe\n\n{}\n", size).into_bytes();
+        data.extend_from_slice(&[128, 64, 0, 129, 0, 0, 0, 0]);
+
+        // When I read them
+        let result = read_hdr(data.as_slice());
+
+        // Then I get an error instead of a panic
+        assert!(result.is_err());
+    }
+}
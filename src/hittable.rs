@@ -1,16 +1,29 @@
-use std::rc::Rc;
+use std::error::Error;
+use std::sync::Arc;
 
 use crate::{
-    color::Color, lambertian::Lambertian, material::Material, point3::Point3, ray::Ray, vec3::Vec3,
+    aabb::Aabb,
+    color::Color,
+    lambertian::Lambertian,
+    material::Material,
+    point3::Point3,
+    ray::Ray,
+    scene_file::{ExportedMaterials, ObjectDescription},
+    vec3::Vec3,
 };
 
 #[derive(Clone)]
 pub struct HitRecord {
     pub point: Point3,
     pub normal: Vec3,
-    pub material: Rc<dyn Material>,
+    pub material: Arc<dyn Material>,
     pub t: f64,
+    /// Texture coordinates of the hit point on the surface
+    pub u: f64,
+    pub v: f64,
     pub is_front_face: bool,
+    /// Position plus one of the object in the list that was hit, see `HittableList`
+    pub object_id: u32,
 }
 
 impl HitRecord {
@@ -19,8 +32,11 @@ impl HitRecord {
             point: Point3::new(0., 0., 0.),
             normal: Vec3::new(0., 0., 0.),
             t: 0.,
-            material: Rc::new(Lambertian::new(Color::new(0.5, 0.5, 0.5))),
+            u: 0.,
+            v: 0.,
+            material: Arc::new(Lambertian::new(Color::new(0.5, 0.5, 0.5))),
             is_front_face: false,
+            object_id: 0,
         }
     }
 
@@ -34,6 +50,24 @@ impl HitRecord {
     }
 }
 
-pub trait Hittable {
+impl Default for HitRecord {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Anything a ray can hit. Hittables are shared between render threads, so they have to be `Send + Sync`.
+pub trait Hittable: Send + Sync {
     fn hit(&self, ray: Ray, t_min: f64, t_max: f64, record: &mut HitRecord) -> bool;
+
+    /// Set `output_box` to a box around the whole object, returning false for objects that are infinitely large
+    fn bounding_box(&self, output_box: &mut Aabb) -> bool;
+
+    /// Describe the object for a scene file, naming its materials in `materials`
+    fn export(
+        &self,
+        _materials: &mut ExportedMaterials,
+    ) -> Result<ObjectDescription, Box<dyn Error>> {
+        Err("the world has an object that can't be written to a scene file".into())
+    }
 }
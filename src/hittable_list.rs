@@ -1,44 +1,96 @@
-#[allow(unused_imports)]
-use crate::hittable::{self, HitRecord, Hittable};
+use std::error::Error;
+
+use crate::aabb::Aabb;
+use crate::hittable::{HitRecord, Hittable};
+use crate::point3::Point3;
 use crate::ray::Ray;
+use crate::scene_file::{ExportedMaterials, ObjectDescription};
 
+/// A collection of objects, hit where the closest of them is hit
+///
+/// The object that was hit is reported as its position in the list plus one, in `HitRecord::object_id`.
+/// For lists inside lists, the outermost list decides.
 pub struct HittableList {
     objects: Vec<Box<dyn Hittable>>,
 }
 
 impl HittableList {
-    #[allow(dead_code)]
     pub fn new() -> Self {
         HittableList {
             objects: Vec::new(),
         }
     }
 
-    #[allow(dead_code)]
     pub fn clear(&mut self) {
         self.objects.clear();
     }
 
-    #[allow(dead_code)]
     pub fn add(&mut self, object: Box<dyn Hittable>) {
         self.objects.push(object);
     }
+
+    pub fn len(&self) -> usize {
+        self.objects.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.objects.is_empty()
+    }
+
+    /// The objects in the order they were added, for building other structures like a `BvhNode`
+    pub fn into_objects(self) -> Vec<Box<dyn Hittable>> {
+        self.objects
+    }
+}
+
+impl Default for HittableList {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl Hittable for HittableList {
     fn hit(&self, ray: Ray, t_min: f64, t_max: f64, record: &mut HitRecord) -> bool {
         let temp_rec = &mut record.clone();
         let mut was_anything_hit = false;
-        let mut closest_so_far = t_max.clone();
+        let mut closest_so_far = t_max;
 
-        for object in self.objects.iter() {
+        for (i, object) in self.objects.iter().enumerate() {
             if object.hit(ray, t_min, closest_so_far, temp_rec) {
                 was_anything_hit = true;
                 closest_so_far = temp_rec.t;
                 *record = temp_rec.clone();
+                record.object_id = i as u32 + 1;
             }
         }
 
         was_anything_hit
     }
+
+    fn bounding_box(&self, output_box: &mut Aabb) -> bool {
+        let mut object_box = Aabb::new(Point3::new(0., 0., 0.), Point3::new(0., 0., 0.));
+        for (i, object) in self.objects.iter().enumerate() {
+            if !object.bounding_box(&mut object_box) {
+                return false;
+            }
+            *output_box = if i == 0 {
+                object_box
+            } else {
+                output_box.surrounding(object_box)
+            };
+        }
+        !self.objects.is_empty()
+    }
+
+    fn export(
+        &self,
+        materials: &mut ExportedMaterials,
+    ) -> Result<ObjectDescription, Box<dyn Error>> {
+        let objects = self
+            .objects
+            .iter()
+            .map(|object| object.export(materials))
+            .collect::<Result<_, _>>()?;
+        Ok(ObjectDescription::Group { objects })
+    }
 }
@@ -0,0 +1,471 @@
+use std::error::Error;
+use std::f64::consts::PI;
+
+use crate::{
+    color::{gamma_correct, Color},
+    framebuffer::{Framebuffer, Pixel},
+    vec3::Vec3,
+};
+
+/// How many pixels fit in one degree of vision when the image is seen on a 0.7 meter wide 4K monitor
+/// from 0.7 meters away, which is what FLIP assumes when nothing else is known
+pub const DEFAULT_PIXELS_PER_DEGREE: f64 = 67.;
+
+/// How much an image differs from a reference
+#[derive(Debug, Clone, PartialEq)]
+pub struct ImageDiff {
+    /// Root mean square error of the gamma corrected channels, clamped to 0 - 1 range
+    pub rmse: f64,
+    /// Peak signal to noise ratio in decibels, infinite when the images are the same
+    pub psnr: f64,
+    /// Mean perceptual error, from 0 for images that look the same to 1 for the largest difference
+    pub flip: f64,
+    /// Absolute difference between the linear colors of every pixel
+    pub difference: Framebuffer,
+    /// Perceptual error of every pixel, as shades of gray
+    pub error_map: Framebuffer,
+}
+
+/// Compare an image with a reference, both holding linear radiance
+///
+/// The perceptual error follows FLIP (Andersson et al., 2020): both images are filtered the way the
+/// eye blurs detail at the given viewing distance, and the difference in color is combined with the
+/// difference in edges and points, which stand out more than a change in color alone.
+pub fn diff_images(
+    reference: &Framebuffer,
+    test: &Framebuffer,
+    pixels_per_degree: f64,
+) -> Result<ImageDiff, Box<dyn Error>> {
+    let (width, height) = (reference.width(), reference.height());
+    if (test.width(), test.height()) != (width, height) {
+        return Err(format!(
+            "can't compare a {}x{} image to a {}x{} reference",
+            test.width(),
+            test.height(),
+            width,
+            height
+        )
+        .into());
+    }
+
+    // Measure the error on display values, so it agrees between 8 bit and floating point images
+    let displayed = |pixel: &Pixel| {
+        let color = gamma_correct(pixel.color);
+        Color::new(color.x.min(1.), color.y.min(1.), color.z.min(1.))
+    };
+    let squared_error: f64 = reference
+        .pixels()
+        .iter()
+        .zip(test.pixels())
+        .map(|(reference, test)| (displayed(reference) - displayed(test)).len_squared())
+        .sum();
+    let rmse = (squared_error / (3 * width * height).max(1) as f64).sqrt();
+
+    let difference = reference
+        .pixels()
+        .iter()
+        .zip(test.pixels())
+        .map(|(reference, test)| {
+            let difference = reference.color - test.color;
+            Pixel::new(Color::new(
+                difference.x.abs(),
+                difference.y.abs(),
+                difference.z.abs(),
+            ))
+        })
+        .collect();
+
+    let errors = flip_errors(reference, test, pixels_per_degree);
+    let flip = errors.iter().sum::<f64>() / errors.len().max(1) as f64;
+    let error_map = errors
+        .into_iter()
+        .map(|error| Pixel::new(Color::new(error, error, error)))
+        .collect();
+
+    Ok(ImageDiff {
+        rmse,
+        psnr: -20. * rmse.log10(),
+        flip,
+        difference: Framebuffer::from_pixels(width, height, difference),
+        error_map: Framebuffer::from_pixels(width, height, error_map),
+    })
+}
+
+// One channel of an image, stored row by row
+struct Plane {
+    width: usize,
+    height: usize,
+    values: Vec<f64>,
+}
+
+impl Plane {
+    fn from_pixels(framebuffer: &Framebuffer, channel: impl Fn(&Pixel) -> f64) -> Self {
+        Self {
+            width: framebuffer.width(),
+            height: framebuffer.height(),
+            values: framebuffer.pixels().iter().map(channel).collect(),
+        }
+    }
+
+    // Filter the rows with one kernel and the columns with another, repeating the pixels at the edges
+    fn convolve(&self, row_kernel: &[f64], column_kernel: &[f64]) -> Self {
+        let clamp = |i: isize, size: usize| i.max(0).min(size as isize - 1) as usize;
+        let filter = |values: &[f64], kernel: &[f64], step: (isize, isize)| {
+            let radius = (kernel.len() / 2) as isize;
+            let mut filtered = vec![0.; values.len()];
+            for y in 0..self.height {
+                for x in 0..self.width {
+                    filtered[y * self.width + x] = kernel
+                        .iter()
+                        .enumerate()
+                        .map(|(i, weight)| {
+                            let offset = i as isize - radius;
+                            let sample_x = clamp(x as isize + offset * step.0, self.width);
+                            let sample_y = clamp(y as isize + offset * step.1, self.height);
+                            weight * values[sample_y * self.width + sample_x]
+                        })
+                        .sum();
+                }
+            }
+            filtered
+        };
+        let rows_filtered = filter(&self.values, row_kernel, (1, 0));
+        Self {
+            width: self.width,
+            height: self.height,
+            values: filter(&rows_filtered, column_kernel, (0, 1)),
+        }
+    }
+}
+
+// The perceptual error of every pixel
+fn flip_errors(reference: &Framebuffer, test: &Framebuffer, pixels_per_degree: f64) -> Vec<f64> {
+    let color_errors = color_errors(reference, test, pixels_per_degree);
+    let feature_errors = feature_errors(reference, test, pixels_per_degree);
+    color_errors
+        .into_iter()
+        .zip(feature_errors)
+        .map(|(color_error, feature_error)| color_error.powf(1. - feature_error))
+        .collect()
+}
+
+// Difference in color after filtering both images by the contrast sensitivity of the eye
+fn color_errors(reference: &Framebuffer, test: &Framebuffer, pixels_per_degree: f64) -> Vec<f64> {
+    let filtered_lab = |framebuffer: &Framebuffer| -> Vec<Vec3> {
+        let opponent: Vec<Plane> = (0..3u8)
+            .map(|channel| {
+                let plane = Plane::from_pixels(framebuffer, |pixel| {
+                    xyz_to_ycxcz(linear_rgb_to_xyz(clamp_color(pixel.color)))[channel]
+                });
+                contrast_sensitivity_filter(&plane, channel, pixels_per_degree)
+            })
+            .collect();
+        (0..opponent[0].values.len())
+            .map(|i| {
+                let ycxcz = Vec3::new(
+                    opponent[0].values[i],
+                    opponent[1].values[i],
+                    opponent[2].values[i],
+                );
+                hunt_adjusted_lab(clamp_color(xyz_to_linear_rgb(ycxcz_to_xyz(ycxcz))))
+            })
+            .collect()
+    };
+
+    // Compress the error, and spread the perceptible part of it over most of 0 - 1 range
+    let (cutoff, cutoff_error) = (0.4, 0.95);
+    let max_error = hyab(
+        hunt_adjusted_lab(Color::new(0., 1., 0.)),
+        hunt_adjusted_lab(Color::new(0., 0., 1.)),
+    )
+    .powf(0.7);
+    filtered_lab(reference)
+        .into_iter()
+        .zip(filtered_lab(test))
+        .map(|(reference, test)| {
+            let error = hyab(reference, test).powf(0.7);
+            let remapped = if error < cutoff * max_error {
+                error * cutoff_error / (cutoff * max_error)
+            } else {
+                cutoff_error
+                    + (error - cutoff * max_error) / (max_error - cutoff * max_error)
+                        * (1. - cutoff_error)
+            };
+            remapped.min(1.)
+        })
+        .collect()
+}
+
+// Blur one channel of the YCxCz opponent space as much as the eye does at the viewing distance
+fn contrast_sensitivity_filter(plane: &Plane, channel: u8, pixels_per_degree: f64) -> Plane {
+    // Each channel is filtered by one or two gaussians, given as (amplitude, variance in degrees²)
+    let gaussians: &[(f64, f64)] = match channel {
+        0 => &[(1., 0.0047)],
+        1 => &[(1., 0.0053)],
+        _ => &[(34.1, 0.04), (13.5, 0.025)],
+    };
+    let widest = gaussians.iter().map(|&(_, b)| b).fold(0., f64::max);
+    let radius = (3. * (widest / (2. * PI * PI)).sqrt() * pixels_per_degree).ceil() as isize;
+
+    // Sum the gaussians, each blurring separately with a kernel normalized to 1
+    let mut values = vec![0.; plane.values.len()];
+    let mut total_weight = 0.;
+    for &(amplitude, b) in gaussians {
+        let kernel: Vec<f64> = (-radius..=radius)
+            .map(|i| {
+                let degrees = i as f64 / pixels_per_degree;
+                (-PI * PI * degrees * degrees / b).exp()
+            })
+            .collect();
+        let sum: f64 = kernel.iter().sum();
+        let normalized: Vec<f64> = kernel.iter().map(|weight| weight / sum).collect();
+        let weight = amplitude * (PI / b).sqrt() * sum * sum;
+        let blurred = plane.convolve(&normalized, &normalized);
+        for (value, blurred) in values.iter_mut().zip(blurred.values) {
+            *value += weight * blurred;
+        }
+        total_weight += weight;
+    }
+    for value in values.iter_mut() {
+        *value /= total_weight;
+    }
+
+    Plane {
+        width: plane.width,
+        height: plane.height,
+        values,
+    }
+}
+
+// Difference in edges and points of the luminance, from 0 to 1
+fn feature_errors(reference: &Framebuffer, test: &Framebuffer, pixels_per_degree: f64) -> Vec<f64> {
+    let standard_deviation = 0.5 * 0.082 * pixels_per_degree;
+    let radius = (3. * standard_deviation).ceil() as isize;
+    let offsets: Vec<f64> = (-radius..=radius).map(|i| i as f64).collect();
+    let gaussian: Vec<f64> = offsets
+        .iter()
+        .map(|x| (-x * x / (2. * standard_deviation * standard_deviation)).exp())
+        .collect();
+
+    // Blur along one axis, and take the first or second derivative along the other
+    let gaussian_sum: f64 = gaussian.iter().sum();
+    let blur: Vec<f64> = gaussian.iter().map(|g| g / gaussian_sum).collect();
+    let edge = normalize_derivative(
+        offsets
+            .iter()
+            .zip(gaussian.iter())
+            .map(|(x, g)| -x * g)
+            .collect(),
+    );
+    let point = normalize_derivative(
+        offsets
+            .iter()
+            .zip(gaussian.iter())
+            .map(|(x, g)| (x * x / (standard_deviation * standard_deviation) - 1.) * g)
+            .collect(),
+    );
+
+    let features = |framebuffer: &Framebuffer| {
+        let luminance = Plane::from_pixels(framebuffer, |pixel| {
+            linear_rgb_to_xyz(clamp_color(pixel.color)).y
+        });
+        let magnitude = |kernel: &[f64]| -> Vec<f64> {
+            let horizontal = luminance.convolve(kernel, &blur);
+            let vertical = luminance.convolve(&blur, kernel);
+            horizontal
+                .values
+                .iter()
+                .zip(vertical.values)
+                .map(|(x, y)| x.hypot(y))
+                .collect()
+        };
+        (magnitude(&edge), magnitude(&point))
+    };
+    let (reference_edges, reference_points) = features(reference);
+    let (test_edges, test_points) = features(test);
+
+    (0..reference_edges.len())
+        .map(|i| {
+            let difference = (reference_edges[i] - test_edges[i])
+                .abs()
+                .max((reference_points[i] - test_points[i]).abs());
+            (difference / 2_f64.sqrt()).sqrt().min(1.)
+        })
+        .collect()
+}
+
+// Scale the positive weights of a derivative kernel to sum to 1, and the negative ones to -1
+fn normalize_derivative(kernel: Vec<f64>) -> Vec<f64> {
+    let positive: f64 = kernel.iter().filter(|&&weight| weight > 0.).sum();
+    let negative: f64 = -kernel.iter().filter(|&&weight| weight < 0.).sum::<f64>();
+    kernel
+        .into_iter()
+        .map(|weight| {
+            if weight > 0. {
+                weight / positive
+            } else if weight < 0. {
+                weight / negative
+            } else {
+                0.
+            }
+        })
+        .collect()
+}
+
+fn clamp_color(color: Color) -> Color {
+    Color::new(
+        color.x.clamp(0., 1.),
+        color.y.clamp(0., 1.),
+        color.z.clamp(0., 1.),
+    )
+}
+
+// The CIE XYZ coordinates of the D65 white point of sRGB
+const WHITE: Vec3 = Vec3 {
+    x: 0.950_428_545,
+    y: 1.,
+    z: 1.088_900_371,
+};
+
+fn linear_rgb_to_xyz(color: Color) -> Vec3 {
+    Vec3::new(
+        0.412_456_4 * color.x + 0.357_576_1 * color.y + 0.180_437_5 * color.z,
+        0.212_672_9 * color.x + 0.715_152_2 * color.y + 0.072_175 * color.z,
+        0.019_333_9 * color.x + 0.119_192 * color.y + 0.950_304_1 * color.z,
+    )
+}
+
+fn xyz_to_linear_rgb(xyz: Vec3) -> Color {
+    Color::new(
+        3.240_454_2 * xyz.x - 1.537_138_5 * xyz.y - 0.498_531_4 * xyz.z,
+        -0.969_266 * xyz.x + 1.876_010_8 * xyz.y + 0.041_556 * xyz.z,
+        0.055_643_4 * xyz.x - 0.204_025_9 * xyz.y + 1.057_225_2 * xyz.z,
+    )
+}
+
+// A linear opponent color space: luminance, red to green, and blue to yellow
+fn xyz_to_ycxcz(xyz: Vec3) -> Vec3 {
+    let (x, y, z) = (xyz.x / WHITE.x, xyz.y / WHITE.y, xyz.z / WHITE.z);
+    Vec3::new(116. * y - 16., 500. * (x - y), 200. * (y - z))
+}
+
+fn ycxcz_to_xyz(ycxcz: Vec3) -> Vec3 {
+    let y = (ycxcz.x + 16.) / 116.;
+    let x = y + ycxcz.y / 500.;
+    let z = y - ycxcz.z / 200.;
+    Vec3::new(x * WHITE.x, y * WHITE.y, z * WHITE.z)
+}
+
+// CIELAB, with the colorfulness scaled by lightness as the Hunt effect describes
+fn hunt_adjusted_lab(color: Color) -> Vec3 {
+    let f = |t: f64| {
+        let delta: f64 = 6. / 29.;
+        if t > delta.powi(3) {
+            t.cbrt()
+        } else {
+            t / (3. * delta * delta) + 4. / 29.
+        }
+    };
+    let xyz = linear_rgb_to_xyz(color);
+    let (x, y, z) = (f(xyz.x / WHITE.x), f(xyz.y / WHITE.y), f(xyz.z / WHITE.z));
+    let lightness = 116. * y - 16.;
+    Vec3::new(
+        lightness,
+        0.01 * lightness * 500. * (x - y),
+        0.01 * lightness * 200. * (y - z),
+    )
+}
+
+// Color distance that treats lightness and hue separately, which works better for large differences
+fn hyab(a: Vec3, b: Vec3) -> f64 {
+    (a.x - b.x).abs() + (a.y - b.y).hypot(a.z - b.z)
+}
+
+#[cfg(test)]
+fn gray_image(width: usize, height: usize, value: f64) -> Framebuffer {
+    Framebuffer::from_colors(vec![vec![Color::new(value, value, value); width]; height])
+}
+
+#[test]
+fn test_diff_identical_images() {
+    // Given an image
+    let image = gray_image(4, 3, 0.25);
+
+    // When I compare it with itself
+    let diff = diff_images(&image, &image, DEFAULT_PIXELS_PER_DEGREE).unwrap();
+
+    // Then there is no error at all
+    assert_eq!(diff.rmse, 0.);
+    assert_eq!(diff.psnr, f64::INFINITY);
+    assert_eq!(diff.flip, 0.);
+    assert_eq!(diff.difference, gray_image(4, 3, 0.));
+    assert_eq!(diff.error_map, gray_image(4, 3, 0.));
+}
+
+#[test]
+fn test_diff_rmse_and_psnr() {
+    // Given an image and a reference that differ in one of two pixels
+    let reference = Framebuffer::from_colors(vec![vec![
+        Color::new(0., 0., 0.),
+        Color::new(0.25, 0.25, 0.25),
+    ]]);
+    let test = Framebuffer::from_colors(vec![vec![
+        Color::new(1., 1., 1.),
+        Color::new(0.25, 0.25, 0.25),
+    ]]);
+
+    // When I compare them
+    let diff = diff_images(&reference, &test, DEFAULT_PIXELS_PER_DEGREE).unwrap();
+
+    // Then half of the channels are off by the full range
+    assert!((diff.rmse - 0.5_f64.sqrt()).abs() < 1e-12);
+    assert!((diff.psnr - 10. * 2_f64.log10()).abs() < 1e-12);
+    assert_eq!(diff.difference.pixel(0, 0).color, Color::new(1., 1., 1.));
+    assert_eq!(diff.difference.pixel(1, 0).color, Color::new(0., 0., 0.));
+}
+
+#[test]
+fn test_diff_flip_grows_with_the_difference() {
+    // Given a reference, and images with a faint and a strong dot in the middle
+    let reference = gray_image(9, 9, 0.2);
+    let mut faint = reference.clone();
+    faint.pixel_mut(4, 4).color = Color::new(0.25, 0.25, 0.25);
+    let mut strong = reference.clone();
+    strong.pixel_mut(4, 4).color = Color::new(1., 0., 0.);
+
+    // When I compare them with the reference
+    let faint = diff_images(&reference, &faint, DEFAULT_PIXELS_PER_DEGREE).unwrap();
+    let strong = diff_images(&reference, &strong, DEFAULT_PIXELS_PER_DEGREE).unwrap();
+
+    // Then the strong dot is a larger error, which is largest where the dot is
+    assert!(0. < faint.flip && faint.flip < strong.flip && strong.flip < 1.);
+    let center = strong.error_map.pixel(4, 4).color.x;
+    assert!(strong
+        .error_map
+        .pixels()
+        .iter()
+        .all(|pixel| pixel.color.x <= center));
+}
+
+#[test]
+fn test_diff_flip_of_opposite_images() {
+    // Given a black and a white image
+    let black = gray_image(3, 3, 0.);
+    let white = gray_image(3, 3, 1.);
+
+    // When I compare them
+    let diff = diff_images(&black, &white, DEFAULT_PIXELS_PER_DEGREE).unwrap();
+
+    // Then the error is close to the largest there is
+    assert!(diff.flip > 0.9 && diff.flip <= 1.);
+}
+
+#[test]
+fn test_diff_rejects_different_sizes() {
+    // Given images of different sizes
+    // When I compare them
+    let result = diff_images(&gray_image(2, 2, 0.), &gray_image(3, 2, 0.), 67.);
+
+    // Then I get an error
+    assert!(result.is_err());
+}
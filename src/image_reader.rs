@@ -0,0 +1,40 @@
+use std::error::Error;
+use std::fs::File;
+use std::io::BufReader;
+use std::path::Path;
+
+use crate::{
+    framebuffer::Framebuffer, hdr::read_hdr, openexr::read_exr, pfm::read_pfm, png_image::read_png,
+    ppm::read_ppm,
+};
+
+/// Read an image in any of the formats that can be written, picking the reader from the file extension
+///
+/// The pixels come back as linear radiance, like `render` makes them: the gamma correction of 8 and
+/// 16 bit formats is undone, so images of different formats can be compared with each other.
+pub fn read_image(path: impl AsRef<Path>) -> Result<Framebuffer, Box<dyn Error>> {
+    let path = path.as_ref();
+    let extension = path
+        .extension()
+        .and_then(|extension| extension.to_str())
+        .unwrap_or("")
+        .to_lowercase();
+    let reader = || -> Result<BufReader<File>, Box<dyn Error>> {
+        let file = File::open(path)
+            .map_err(|error| format!("can't open '{}': {}", path.display(), error))?;
+        Ok(BufReader::new(file))
+    };
+
+    match extension.as_str() {
+        "ppm" => Ok(read_ppm(reader()?)?.linearized()),
+        "png" => Ok(read_png(reader()?)?.linearized()),
+        "pfm" => read_pfm(reader()?),
+        "hdr" => read_hdr(reader()?),
+        "exr" => read_exr(reader()?),
+        _ => Err(format!(
+            "can't tell the image format from the extension of '{}'",
+            path.display()
+        )
+        .into()),
+    }
+}
@@ -0,0 +1,151 @@
+use std::error::Error;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    color::Color,
+    framebuffer::Framebuffer,
+    image_reader::read_image,
+    point3::Point3,
+    scene_file::{PatternDescription, TextureDescription},
+    texture::Texture,
+};
+
+/// What an `ImageTexture` shows outside the surface coordinates from 0 to 1
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WrapMode {
+    /// Tile the image
+    #[default]
+    Repeat,
+    /// Tile the image, flipping every other tile so that the edges line up
+    Mirror,
+    /// Stretch the pixels on the edges
+    Clamp,
+}
+
+/// An image stretched over the surface coordinates, with `u` going left to right and `v` bottom to top
+///
+/// Colors are blended bilinearly between the centers of the four nearest pixels.
+#[derive(Debug, Clone)]
+pub struct ImageTexture {
+    image: Framebuffer,
+    wrap: WrapMode,
+    // Where the image was loaded from, for scene files
+    path: Option<PathBuf>,
+}
+
+impl ImageTexture {
+    pub fn new(image: Framebuffer, wrap: WrapMode) -> Result<Self, Box<dyn Error>> {
+        if image.width() == 0 || image.height() == 0 {
+            return Err("a texture needs an image of at least one pixel".into());
+        }
+        Ok(Self {
+            image,
+            wrap,
+            path: None,
+        })
+    }
+
+    /// Load an image in any format `read_image` can read
+    pub fn load(path: impl AsRef<Path>, wrap: WrapMode) -> Result<Self, Box<dyn Error>> {
+        let path = path.as_ref();
+        let image = read_image(path)?;
+        let mut texture =
+            Self::new(image, wrap).map_err(|error| format!("{}: {}", path.display(), error))?;
+        texture.path = Some(path.canonicalize().unwrap_or_else(|_| path.to_path_buf()));
+        Ok(texture)
+    }
+
+    // The color of a pixel, with coordinates outside the image wrapped into it
+    fn texel(&self, x: i64, y: i64) -> Color {
+        let x = self.wrap.apply(x, self.image.width());
+        let y = self.wrap.apply(y, self.image.height());
+        self.image.pixel(x, y).color
+    }
+}
+
+impl WrapMode {
+    fn apply(self, i: i64, size: usize) -> usize {
+        let size = size as i64;
+        let i = match self {
+            WrapMode::Repeat => i.rem_euclid(size),
+            WrapMode::Mirror => {
+                let i = i.rem_euclid(2 * size);
+                if i < size {
+                    i
+                } else {
+                    2 * size - 1 - i
+                }
+            }
+            WrapMode::Clamp => i.clamp(0, size - 1),
+        };
+        i as usize
+    }
+}
+
+impl Texture for ImageTexture {
+    fn value(&self, u: f64, v: f64, _point: Point3) -> Color {
+        let finite = |value: f64| if value.is_finite() { value } else { 0. };
+        // Pixel centers sit at half pixels, and rows go from the top down
+        let x = finite(u) * self.image.width() as f64 - 0.5;
+        let y = (1. - finite(v)) * self.image.height() as f64 - 0.5;
+        let (left, top) = (x.floor(), y.floor());
+        let (dx, dy) = (x - left, y - top);
+        let (left, top) = (left as i64, top as i64);
+
+        let upper = (1. - dx) * self.texel(left, top) + dx * self.texel(left + 1, top);
+        let lower = (1. - dx) * self.texel(left, top + 1) + dx * self.texel(left + 1, top + 1);
+        (1. - dy) * upper + dy * lower
+    }
+
+    fn describe(&self) -> Option<TextureDescription> {
+        Some(TextureDescription::Pattern(PatternDescription::Image {
+            file: self.path.clone()?,
+            wrap: self.wrap,
+        }))
+    }
+}
+
+#[cfg(test)]
+fn test_texture(wrap: WrapMode) -> ImageTexture {
+    // A black pixel left of a white one
+    let image =
+        Framebuffer::from_colors(vec![vec![Color::new(0., 0., 0.), Color::new(1., 1., 1.)]]);
+    ImageTexture::new(image, wrap).unwrap()
+}
+
+#[test]
+fn test_image_texture_filters_bilinearly() {
+    // Given a texture of a black and a white pixel
+    let texture = test_texture(WrapMode::Clamp);
+
+    // When I look up pixel centers and the point between them
+    let value = |u| texture.value(u, 0.5, Point3::new(0., 0., 0.)).x;
+
+    // Then the centers have the pixel colors, and the point between them is blended
+    assert!(value(0.25).abs() < 1e-12);
+    assert!((value(0.75) - 1.).abs() < 1e-12);
+    assert!((value(0.5) - 0.5).abs() < 1e-12);
+    // And the edges are clamped
+    assert!(value(0.).abs() < 1e-12);
+    assert!((value(1.) - 1.).abs() < 1e-12);
+}
+
+#[test]
+fn test_image_texture_wrap_modes() {
+    // Given a texture of a black and a white pixel
+    let value = |wrap, u| test_texture(wrap).value(u, 0.5, Point3::new(0., 0., 0.)).x;
+
+    // When I look up the center of the pixel right of the image
+    let u = 1.25;
+
+    // Then repeating starts over with black, mirroring repeats the white and clamping stretches it
+    assert!(value(WrapMode::Repeat, u).abs() < 1e-12);
+    assert!((value(WrapMode::Mirror, u) - 1.).abs() < 1e-12);
+    assert!((value(WrapMode::Clamp, u) - 1.).abs() < 1e-12);
+    // And mirroring a tile to the left flips it
+    assert!((value(WrapMode::Mirror, -0.25) - 0.).abs() < 1e-12);
+    assert!((value(WrapMode::Mirror, -0.75) - 1.).abs() < 1e-12);
+}
@@ -1,12 +1,23 @@
-use crate::{color::Color, hittable::HitRecord, material::Material, point3::Vec3, ray::Ray};
+use std::sync::Arc;
 
-#[derive(Debug, Clone, Copy)]
+use rand::RngCore;
+
+use crate::{
+    color::Color, hittable::HitRecord, material::Material, point3::Vec3, ray::Ray,
+    scene_file::MaterialDescription, solid_color::SolidColor, texture::Texture,
+};
+
+#[derive(Clone)]
 pub struct Lambertian {
-    albedo: Color,
+    albedo: Arc<dyn Texture>,
 }
 
 impl Lambertian {
     pub fn new(albedo: Color) -> Self {
+        Self::from_texture(Arc::new(SolidColor::new(albedo)))
+    }
+
+    pub fn from_texture(albedo: Arc<dyn Texture>) -> Self {
         Self { albedo }
     }
 }
@@ -14,20 +25,27 @@ impl Lambertian {
 impl Material for Lambertian {
     fn scatter(
         &self,
-        _: Ray,
+        ray_in: Ray,
         record: &HitRecord,
         attenuation: &mut Color,
         scattered: &mut Ray,
+        rng: &mut dyn RngCore,
     ) -> bool {
-        let mut scatter_direction = record.normal + Vec3::random_unit_vector();
+        let mut scatter_direction = record.normal + Vec3::random_unit_vector(rng);
 
         // Catch degenerate scatter direction
         if scatter_direction.is_near_zero() {
             scatter_direction = record.normal;
         }
 
-        *scattered = Ray::new(record.point, scatter_direction);
-        *attenuation = self.albedo;
+        *scattered = Ray::at_time(record.point, scatter_direction, ray_in.time);
+        *attenuation = self.albedo.value(record.u, record.v, record.point);
         true
     }
+
+    fn describe(&self) -> Option<MaterialDescription> {
+        Some(MaterialDescription::Lambertian {
+            albedo: self.albedo.describe()?,
+        })
+    }
 }
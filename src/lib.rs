@@ -0,0 +1,125 @@
+//! A ray tracer implementing [_Ray Tracing in One Weekend_](https://raytracing.github.io/books/RayTracingInOneWeekend.html).
+//!
+//! The crate is split into geometry (`vec3`, `mat4` and `transform`, `ray`, `sphere`,
+//! `moving_sphere`, `plane`, `quad`, `disk`, `cuboid`, `cylinder`, `cone`, `paraboloid`, `torus`
+//! with the roots of `polynomial`s, `triangle`, `triangle_mesh`, `transformed` instances, `csg`
+//! combinations, `hittable`, with `aabb` and `bvh` to speed up finding what a ray hits), materials
+//! (`material`, `lambertian`, `metal`, `dielectric`, `diffuse_light`) and their textures
+//! (`texture`, `solid_color`, `checker`, `image_texture`, `noise_texture` on top of `noise`), the
+//! `camera`, the integrator (`render`) with its `tile_scheduler` and the `framebuffer` it renders
+//! into, image input and output (`ppm`, `png_image`, `hdr`, `pfm`, `openexr`, `image_reader`),
+//! model import (`obj`, `ply`, `stl`), comparing renders (`image_diff`), reading and writing JSON
+//! scene files (`scene_file`) and a couple of example `scenes`.
+
+// 3rd party
+extern crate rand;
+extern crate rayon;
+extern crate serde;
+extern crate serde_json;
+
+// Project
+pub mod aabb;
+pub mod bvh;
+pub mod camera;
+pub mod checker;
+pub mod color;
+pub mod cone;
+pub mod csg;
+pub mod cuboid;
+pub mod cylinder;
+pub mod dielectric;
+pub mod diffuse_light;
+pub mod disk;
+pub mod framebuffer;
+pub mod hdr;
+pub mod hittable;
+pub mod hittable_list;
+pub mod image_diff;
+pub mod image_reader;
+pub mod image_texture;
+pub mod lambertian;
+pub mod mat4;
+pub mod material;
+pub mod metal;
+pub mod moving_sphere;
+pub mod noise;
+pub mod noise_texture;
+pub mod obj;
+pub mod openexr;
+pub mod paraboloid;
+pub mod pfm;
+pub mod plane;
+pub mod ply;
+pub mod png_image;
+pub mod point3;
+pub mod polynomial;
+pub mod ppm;
+pub mod quad;
+pub mod ray;
+pub mod render;
+pub mod rng;
+pub mod scene_file;
+pub mod scenes;
+pub mod solid_color;
+pub mod sphere;
+pub mod stl;
+pub mod texture;
+pub mod tile_scheduler;
+pub mod torus;
+pub mod transform;
+pub mod transformed;
+pub mod triangle;
+pub mod triangle_mesh;
+pub mod vec3;
+
+pub use aabb::Aabb;
+pub use bvh::BvhNode;
+pub use camera::Camera;
+pub use checker::{Checker, CheckerMapping};
+pub use color::Color;
+pub use cone::Cone;
+pub use csg::{Csg, CsgOperation};
+pub use cuboid::Cuboid;
+pub use cylinder::Cylinder;
+pub use dielectric::Dielectric;
+pub use diffuse_light::DiffuseLight;
+pub use disk::Disk;
+pub use framebuffer::{Framebuffer, Pixel};
+pub use hdr::{read_hdr, write_hdr};
+pub use hittable::{HitRecord, Hittable};
+pub use hittable_list::HittableList;
+pub use image_diff::{diff_images, ImageDiff, DEFAULT_PIXELS_PER_DEGREE};
+pub use image_reader::read_image;
+pub use image_texture::{ImageTexture, WrapMode};
+pub use lambertian::Lambertian;
+pub use mat4::Mat4;
+pub use material::Material;
+pub use metal::Metal;
+pub use moving_sphere::MovingSphere;
+pub use noise::Noise;
+pub use noise_texture::{NoisePattern, NoiseTexture};
+pub use obj::{load_mtl, load_obj, MtlMaterial};
+pub use openexr::{read_exr, write_exr, ExrCompression, ExrSampleType, ExrSettings, Pass};
+pub use paraboloid::Paraboloid;
+pub use pfm::{read_pfm, write_pfm};
+pub use plane::Plane;
+pub use ply::{load_ply, read_ply};
+pub use png_image::{read_png, write_png, PngFormat};
+pub use point3::Point3;
+pub use ppm::{read_ppm, write_ppm, write_ppm_with_format, PpmFormat};
+pub use quad::Quad;
+pub use ray::Ray;
+pub use render::{render, Background, RenderSettings};
+pub use rng::SeededRng;
+pub use scene_file::{load_scene, parse_scene, SceneDescription};
+pub use solid_color::SolidColor;
+pub use sphere::Sphere;
+pub use stl::{load_stl, read_stl};
+pub use texture::Texture;
+pub use tile_scheduler::{Tile, TileOrder, TileScheduler};
+pub use torus::Torus;
+pub use transform::Transform;
+pub use transformed::Transformed;
+pub use triangle::Triangle;
+pub use triangle_mesh::{MeshData, TriangleMesh};
+pub use vec3::Vec3;
@@ -1,260 +1,128 @@
 // Sys
-use std::io::Write;
-use std::{error::Error, rc::Rc};
+use std::error::Error;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
 
 // Project
-mod camera;
-mod color;
-mod dielectric;
-mod hittable;
-mod hittable_list;
-mod lambertian;
-mod material;
-mod metal;
-mod point3;
-mod ppm;
-mod ray;
-mod sphere;
-mod vec3;
-use camera::Camera;
-use color::Color;
-use dielectric::Dielectric;
-use hittable::{HitRecord, Hittable};
-use hittable_list::HittableList;
-use lambertian::Lambertian;
-use material::Material;
-use metal::Metal;
-use point3::Point3;
-use ppm::write_ppm;
-use rand::{random, thread_rng, Rng};
-use ray::Ray;
-use sphere::Sphere;
-use vec3::Vec3;
+mod cli;
+use cli::{format_for_path, Cli, Command, DiffArgs, OutputFormat};
+use ray_tracing::{
+    diff_images, read_image, render,
+    scene_file::{CameraDescription, RenderDescription},
+    write_exr, write_hdr, write_pfm, write_png, write_ppm_with_format, BvhNode, ExrCompression,
+    ExrSampleType, ExrSettings, Framebuffer, Pass, PngFormat, PpmFormat, SceneDescription,
+};
 
-// 3rd party
-extern crate conv;
-extern crate rand;
-use conv::*;
-
-#[allow(dead_code)]
-fn ray_background_color_rainbow(ray: Ray) -> Color {
-    let unit_direction = Vec3::unit_vector(ray.direction);
-
-    // Generate color by scaling x and y of the unit direction from domain -1, 1 to range 0, 1.
-    Color::new(
-        unit_direction.y / 2. + 0.5,
-        0.5 - unit_direction.x / 2.,
-        0.25,
-    )
+fn main() -> Result<(), Box<dyn Error>> {
+    let cli = Cli::parse_and_validate();
+    match &cli.command {
+        Some(Command::Diff(args)) => diff(args),
+        None => render_image(&cli),
+    }
 }
 
-#[allow(dead_code)]
-fn ray_background_color(ray: Ray) -> Color {
-    let unit_direction = Vec3::unit_vector(ray.direction);
+fn render_image(cli: &Cli) -> Result<(), Box<dyn Error>> {
+    // Image
+    let settings = cli.render_settings();
 
-    // Scale the y factor of the unit direction from domain -1, 1 to range 0, 1
-    let t = 0.5 * (unit_direction.y + 1.);
+    // World
+    eprintln!("Seed: {}", cli.seed());
+    let world = cli.build_world()?;
 
-    // Scale from domain t: 0, 1 to color: white, blue
-    (1. - t) * Color::new(1., 1., 1.) + t * Color::new(0.5, 0.7, 1.)
-}
+    // Camera
+    let camera = cli.camera();
 
-// Return a surface normal visualization if the ray hits the sphere, and the background color if it does not
-fn ray_color(ray: Ray, world: &dyn Hittable, depth: i64) -> Color {
-    let mut record = HitRecord::new();
+    if let Some(path) = &cli.save_scene {
+        let scene = SceneDescription::from_world(
+            &world,
+            CameraDescription::from(&camera),
+            RenderDescription::from(&settings),
+        )?;
+        scene.save(path)?;
+        eprintln!("Saved the scene to {}", path.display());
+        return Ok(());
+    }
+    let world = BvhNode::from(world);
 
-    if depth <= 0 {
-        return Color::new(0., 0., 0.);
-    };
+    // Create pixel data
+    let framebuffer = render(&world, &camera, &settings, |tiles_remaining| {
+        eprintln!("Tiles remaining: {}", tiles_remaining);
+    })?;
 
-    // We set t_min to slightly above 0, so we don't get values below 0 from floating point rounding errors (this fixes shadow acne)
-    if world.hit(ray, 0.001, f64::INFINITY, &mut record) {
-        let mut scattered = Ray::nowhere();
-        let mut attenuation = Color::new(0., 0., 0.);
-        return if record
-            .material
-            .scatter(ray, &record, &mut attenuation, &mut scattered)
-        {
-            attenuation * ray_color(scattered, world, depth - 1)
-        } else {
-            Color::new(0., 0., 0.)
-        };
+    // Write image
+    let format = cli.output_format()?;
+    let framebuffer = if format.is_high_dynamic_range() {
+        framebuffer
+    } else {
+        framebuffer.gamma_corrected()
     };
-
-    ray_background_color(ray)
+    write_image(
+        &framebuffer,
+        format,
+        &cli.exr_settings(),
+        cli.output_path().map(|path| path.as_path()),
+    )
 }
 
-#[allow(dead_code)]
-fn controlled_scene() -> HittableList {
-    let mut world = HittableList::new();
+fn diff(args: &DiffArgs) -> Result<(), Box<dyn Error>> {
+    let reference = read_image(&args.reference)?;
+    let image = read_image(&args.image)?;
+    let diff = diff_images(&reference, &image, args.pixels_per_degree)?;
 
-    let ground_material = Lambertian::new(Color::new(0.8, 0.8, 0.));
-    let blue_ball_material = Lambertian::new(Color::new(0.1, 0.2, 0.5));
-    // let ball_material = Lambertian::new(Color::new(0.7, 0.3, 0.3));
-    // let metal_material = Metal::new(Color::new(0.8, 0.8, 0.8), 0.3);
-    let yellow_metal_material = Metal::new(Color::new(0.8, 0.6, 0.2), 0.1);
-    let refracting_material = Dielectric::new(1.5);
+    println!("RMSE: {:.6}", diff.rmse);
+    println!("PSNR: {:.2} dB", diff.psnr);
+    println!("FLIP: {:.6}", diff.flip);
 
-    let ground = Sphere::new(Point3::new(0., -100.5, -1.), 100., Rc::new(ground_material));
-    let left = Sphere::new(Point3::new(-1., 0., -1.), 0.5, Rc::new(refracting_material));
-    let left_internal = Sphere::new(
-        Point3::new(-1., 0., -1.),
-        -0.4,
-        Rc::new(refracting_material),
-    );
-    let center = Sphere::new(Point3::new(0., 0., -1.), 0.5, Rc::new(blue_ball_material));
-    let right = Sphere::new(
-        Point3::new(1., 0., -1.),
-        0.5,
-        Rc::new(yellow_metal_material),
+    // Write the difference images as they are, they hold errors rather than radiance
+    let exr_settings = ExrSettings::new(
+        ExrCompression::Zip,
+        ExrSampleType::Float,
+        vec![Pass::Beauty],
     );
-
-    world.add(Box::new(left));
-    world.add(Box::new(left_internal));
-    world.add(Box::new(center));
-    world.add(Box::new(right));
-    // world.add(Box::new(Sphere::new(
-    //     Point3::new(-1., 0., -2.),
-    //     0.5,
-    //     Rc::new(Lambertian::new(Color::new(0.3, 0.3, 0.7))),
-    // )));
-    // world.add(Box::new(Sphere::new(
-    //     Point3::new(-2., 0., -3.),
-    //     0.5,
-    //     Rc::new(Lambertian::new(Color::new(0.3, 0.7, 0.3))),
-    // )));
-    world.add(Box::new(ground));
-
-    world
-}
-
-#[allow(dead_code)]
-fn random_scene() -> HittableList {
-    let mut world = HittableList::new();
-
-    let ground_material = Rc::new(Lambertian::new(Color::new(0.5, 0.5, 0.5)));
-    let ground = Box::new(Sphere::new(
-        Point3::new(0., -1000., 0.),
-        1000.,
-        ground_material,
-    ));
-    world.add(ground);
-
-    for a in -11..11 {
-        for b in -11..11 {
-            let material_choice = random::<f64>();
-            let center = Point3::new(
-                f64::from(a) + 0.9 * random::<f64>(),
-                0.2,
-                f64::from(b) + 0.9 * random::<f64>(),
-            );
-
-            // Skip if the item is out of view
-            if (center - Point3::new(4., 0.2, 0.)).len() <= 0.9 {
-                continue;
-            };
-
-            let material: Rc<dyn Material> = if material_choice < 0.8 {
-                // Diffuse
-                let albedo = Color::random() * Color::random();
-                Rc::new(Lambertian::new(albedo))
-            } else if material_choice < 0.95 {
-                // Metal
-                let albedo = Color::random_in_range(0.5, 1.);
-                let mut rng = thread_rng();
-                let fuzz = rng.gen_range(0.0..0.5);
-                Rc::new(Metal::new(albedo, fuzz))
-            } else {
-                // Glass
-                Rc::new(Dielectric::new(1.5))
-            };
-
-            let object = Box::new(Sphere::new(center, 0.2, material));
-            world.add(object);
+    for (path, framebuffer) in [
+        (&args.difference, &diff.difference),
+        (&args.error_map, &diff.error_map),
+    ]
+    .iter()
+    {
+        if let Some(path) = path {
+            let format = format_for_path(path)?;
+            write_image(framebuffer, format, &exr_settings, Some(path))?;
         }
     }
 
-    let big_glass = Box::new(Sphere::new(
-        Point3::new(0., 1., 0.),
-        1.,
-        Rc::new(Dielectric::new(1.5)),
-    ));
-    world.add(big_glass);
-
-    let big_diffuse = Box::new(Sphere::new(
-        Point3::new(-4., 1., 0.),
-        1.,
-        Rc::new(Lambertian::new(Color::new(0.4, 0.2, 0.1))),
-    ));
-    world.add(big_diffuse);
-
-    let big_metal = Box::new(Sphere::new(
-        Point3::new(4., 1., 0.),
-        1.,
-        Rc::new(Metal::new(Color::new(0.7, 0.6, 0.5), 0.)),
-    ));
-    world.add(big_metal);
-
-    world
-}
-
-fn main() -> Result<(), Box<dyn Error>> {
-    // Image
-    let aspect_ratio: f64 = 3. / 2.;
-    let image_width: u16 = 400;
-    let image_height: u16 = (f64::from(image_width) / aspect_ratio).floor() as u16;
-    let samples_per_pixel = 100;
-    let max_depth = 50;
-
-    // World
-    let world = random_scene();
-
-    // Camera
-    let look_from = Point3::new(13., 2., 3.);
-    let look_at = Point3::new(0., 0., 0.);
-    let vup = Vec3::new(0., 1., 0.);
-    let vertical_field_of_view = 20.;
-    let focus_distance = 10.;
-    let aperture = 0.1;
-
-    let camera = Camera::new(
-        look_from,
-        look_at,
-        vup,
-        vertical_field_of_view,
-        aspect_ratio,
-        aperture,
-        focus_distance,
-    );
-
-    // Create pixel data
-    let mut image = vec![vec![Color::new(0., 0., 0.); image_width.into()]; image_height.into()];
-    for (y, row) in image.iter_mut().rev().enumerate() {
-        writeln!(
-            std::io::stderr(),
-            "Scanlines remaining: {}",
-            image_height - y as u16,
-        )?;
-        for (x, pixel) in row.iter_mut().enumerate() {
-            // Per pixel, compute a number of randomly sampled values and add them all to the pixel
-            for _ in 0..(samples_per_pixel - 1) {
-                let u = (f64::value_from(x)? + random::<f64>()) / f64::value_from(image_width - 1)?;
-                let v =
-                    (f64::value_from(y)? + random::<f64>()) / f64::value_from(image_height - 1)?;
-                let ray = camera.get_ray(u, v);
-                *pixel += ray_color(ray, &world, max_depth);
-            }
-
-            // Get average value of all samples and apply gamma correction
-            let scale = 1. / f64::from(samples_per_pixel);
-            pixel.x = f64::sqrt(scale * pixel.x);
-            pixel.y = f64::sqrt(scale * pixel.y);
-            pixel.z = f64::sqrt(scale * pixel.z);
+    match args.max_flip {
+        Some(max_flip) if diff.flip > max_flip => {
+            Err(format!("the images differ more than --max-flip {} allows", max_flip).into())
         }
+        _ => Ok(()),
     }
+}
 
-    // Write image
-    write_ppm(image_width, image_height, image, std::io::stdout())?;
+// Write the image to a file, or to stdout when there is no path
+fn write_image(
+    framebuffer: &Framebuffer,
+    format: OutputFormat,
+    exr_settings: &ExrSettings,
+    path: Option<&Path>,
+) -> Result<(), Box<dyn Error>> {
+    let writer: Box<dyn Write> = match path {
+        Some(path) => Box::new(BufWriter::new(File::create(path)?)),
+        None => Box::new(BufWriter::new(std::io::stdout())),
+    };
+    match format {
+        OutputFormat::Ppm => write_ppm_with_format(framebuffer, PpmFormat::Ascii, writer)?,
+        OutputFormat::PpmBinary => write_ppm_with_format(framebuffer, PpmFormat::Binary, writer)?,
+        OutputFormat::Ppm16 => write_ppm_with_format(framebuffer, PpmFormat::Binary16, writer)?,
+        OutputFormat::Png => write_png(framebuffer, PngFormat::Rgb8, writer)?,
+        OutputFormat::Png16 => write_png(framebuffer, PngFormat::Rgb16, writer)?,
+        OutputFormat::PngRgba => write_png(framebuffer, PngFormat::Rgba8, writer)?,
+        OutputFormat::Png16Rgba => write_png(framebuffer, PngFormat::Rgba16, writer)?,
+        OutputFormat::Hdr => write_hdr(framebuffer, writer)?,
+        OutputFormat::Pfm => write_pfm(framebuffer, writer)?,
+        OutputFormat::Exr => write_exr(framebuffer, exr_settings, writer)?,
+    }
 
     // Exit
     Ok(())
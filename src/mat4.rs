@@ -0,0 +1,175 @@
+// Sys
+use std::ops::Mul;
+
+// 3rd party
+use serde::{Deserialize, Serialize};
+
+// Project
+use crate::{point3::Point3, vec3::Vec3};
+
+/// A 4 by 4 matrix for affine transforms of points and vectors, which are multiplied as columns on its
+/// right. Stored as its rows in scene files.
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(from = "[[f64; 4]; 4]", into = "[[f64; 4]; 4]")]
+pub struct Mat4 {
+    pub rows: [[f64; 4]; 4],
+}
+
+impl Mat4 {
+    pub fn new(rows: [[f64; 4]; 4]) -> Self {
+        Self { rows }
+    }
+
+    pub fn identity() -> Self {
+        let mut rows = [[0.; 4]; 4];
+        for (i, row) in rows.iter_mut().enumerate() {
+            row[i] = 1.;
+        }
+        Self::new(rows)
+    }
+
+    pub fn transpose(&self) -> Self {
+        let mut rows = [[0.; 4]; 4];
+        for (i, row) in rows.iter_mut().enumerate() {
+            for (j, value) in row.iter_mut().enumerate() {
+                *value = self.rows[j][i];
+            }
+        }
+        Self::new(rows)
+    }
+
+    /// The matrix that undoes this one, or `None` when it flattens space so that it can't be undone
+    ///
+    /// Found by Gauss-Jordan elimination with partial pivoting.
+    pub fn inverse(&self) -> Option<Self> {
+        let mut left = self.rows;
+        let mut right = Self::identity().rows;
+        for column in 0..4 {
+            let pivot = (column..4)
+                .max_by(|&a, &b| left[a][column].abs().total_cmp(&left[b][column].abs()))
+                .unwrap();
+            if left[pivot][column].abs() < 1e-12 || !left[pivot][column].is_finite() {
+                return None;
+            }
+            left.swap(column, pivot);
+            right.swap(column, pivot);
+
+            let scale = 1. / left[column][column];
+            for j in 0..4 {
+                left[column][j] *= scale;
+                right[column][j] *= scale;
+            }
+            for row in 0..4 {
+                let factor = left[row][column];
+                if row == column || factor == 0. {
+                    continue;
+                }
+                for j in 0..4 {
+                    left[row][j] -= factor * left[column][j];
+                    right[row][j] -= factor * right[column][j];
+                }
+            }
+        }
+        Some(Self::new(right))
+    }
+
+    /// Transform a point, which is moved by the translation in the last column
+    pub fn transform_point(&self, point: Point3) -> Point3 {
+        let m = &self.rows;
+        Point3::new(
+            m[0][0] * point.x + m[0][1] * point.y + m[0][2] * point.z + m[0][3],
+            m[1][0] * point.x + m[1][1] * point.y + m[1][2] * point.z + m[1][3],
+            m[2][0] * point.x + m[2][1] * point.y + m[2][2] * point.z + m[2][3],
+        )
+    }
+
+    /// Transform a direction, which isn't moved by the translation
+    pub fn transform_vector(&self, vector: Vec3) -> Vec3 {
+        let m = &self.rows;
+        Vec3::new(
+            m[0][0] * vector.x + m[0][1] * vector.y + m[0][2] * vector.z,
+            m[1][0] * vector.x + m[1][1] * vector.y + m[1][2] * vector.z,
+            m[2][0] * vector.x + m[2][1] * vector.y + m[2][2] * vector.z,
+        )
+    }
+}
+
+impl From<[[f64; 4]; 4]> for Mat4 {
+    fn from(rows: [[f64; 4]; 4]) -> Self {
+        Self::new(rows)
+    }
+}
+
+impl From<Mat4> for [[f64; 4]; 4] {
+    fn from(matrix: Mat4) -> Self {
+        matrix.rows
+    }
+}
+
+impl Mul for Mat4 {
+    type Output = Self;
+
+    fn mul(self, other: Self) -> Self {
+        let mut rows = [[0.; 4]; 4];
+        for (i, row) in rows.iter_mut().enumerate() {
+            for (j, value) in row.iter_mut().enumerate() {
+                *value = (0..4).map(|k| self.rows[i][k] * other.rows[k][j]).sum();
+            }
+        }
+        Self::new(rows)
+    }
+}
+
+#[test]
+fn test_multiply_by_identity() {
+    // Given a matrix
+    let matrix = Mat4::new([
+        [1., 2., 3., 4.],
+        [5., 6., 7., 8.],
+        [9., 10., 11., 12.],
+        [0., 0., 0., 1.],
+    ]);
+
+    // When I multiply it by the identity on either side
+    let left = Mat4::identity() * matrix;
+    let right = matrix * Mat4::identity();
+
+    // Then it stays the same
+    assert_eq!(left, matrix);
+    assert_eq!(right, matrix);
+}
+
+#[test]
+fn test_inverse() {
+    // Given an invertible matrix, and one that flattens space onto a plane
+    let matrix = Mat4::new([
+        [0., 2., 0., 1.],
+        [1., 0., 0., 2.],
+        [0., 0., 4., 3.],
+        [0., 0., 0., 1.],
+    ]);
+    let flat = Mat4::new([
+        [1., 0., 0., 0.],
+        [0., 1., 0., 0.],
+        [0., 0., 0., 0.],
+        [0., 0., 0., 1.],
+    ]);
+
+    // When I invert them
+    let inverse = matrix.inverse().unwrap();
+
+    // Then the inverse undoes the matrix, and the flat one has none
+    let product = inverse * matrix;
+    for i in 0..4 {
+        for j in 0..4 {
+            let expected = if i == j { 1. } else { 0. };
+            assert!((product.rows[i][j] - expected).abs() < 1e-12);
+        }
+    }
+    let point = Point3::new(1., 2., 3.);
+    assert_eq!(
+        inverse.transform_point(matrix.transform_point(point)),
+        point
+    );
+    assert_eq!(flat.inverse(), None);
+}
@@ -1,13 +1,28 @@
+use rand::RngCore;
+
 use crate::color::Color;
 use crate::hittable::HitRecord;
 use crate::ray::Ray;
+use crate::scene_file::MaterialDescription;
+
+/// How light scatters off, or is emitted by, a surface. Materials are shared between render threads, so they have to be `Send + Sync`.
+pub trait Material: Send + Sync {
+    /// Light given off at the hit point, black for anything that isn't a light source
+    fn emitted(&self, _record: &HitRecord) -> Color {
+        Color::new(0., 0., 0.)
+    }
 
-pub trait Material {
     fn scatter(
         &self,
         ray_in: Ray,
         record: &HitRecord,
         attenuation: &mut Color,
         scattered: &mut Ray,
+        rng: &mut dyn RngCore,
     ) -> bool;
+
+    /// The material as it is written in scene files, or `None` for materials that can't be
+    fn describe(&self) -> Option<MaterialDescription> {
+        None
+    }
 }
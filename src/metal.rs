@@ -1,12 +1,23 @@
-use crate::{color::Color, hittable::HitRecord, material::Material, point3::Vec3, ray::Ray};
+use std::sync::Arc;
+
+use rand::RngCore;
+
+use crate::{
+    color::Color, hittable::HitRecord, material::Material, point3::Vec3, ray::Ray,
+    scene_file::MaterialDescription, solid_color::SolidColor, texture::Texture,
+};
 
 pub struct Metal {
-    albedo: Color,
+    albedo: Arc<dyn Texture>,
     fuzz: f64,
 }
 
 impl Metal {
     pub fn new(albedo: Color, fuzz: f64) -> Self {
+        Self::from_texture(Arc::new(SolidColor::new(albedo)), fuzz)
+    }
+
+    pub fn from_texture(albedo: Arc<dyn Texture>, fuzz: f64) -> Self {
         Self {
             albedo,
             fuzz: if fuzz < 1. { fuzz } else { 1. },
@@ -21,13 +32,22 @@ impl Material for Metal {
         record: &HitRecord,
         attenuation: &mut Color,
         scattered: &mut Ray,
+        rng: &mut dyn RngCore,
     ) -> bool {
         let reflected = ray_in.direction.unit_vector().reflect(record.normal);
-        *scattered = Ray::new(
+        *scattered = Ray::at_time(
             record.point,
-            reflected + self.fuzz * Vec3::random_in_unit_sphere(),
+            reflected + self.fuzz * Vec3::random_in_unit_sphere(rng),
+            ray_in.time,
         );
-        *attenuation = self.albedo;
+        *attenuation = self.albedo.value(record.u, record.v, record.point);
         scattered.direction.dot(record.normal) > 0.
     }
+
+    fn describe(&self) -> Option<MaterialDescription> {
+        Some(MaterialDescription::Metal {
+            albedo: self.albedo.describe()?,
+            fuzz: self.fuzz,
+        })
+    }
 }
@@ -0,0 +1,140 @@
+use std::error::Error;
+use std::sync::Arc;
+
+use crate::{
+    aabb::Aabb,
+    hittable::{HitRecord, Hittable},
+    material::Material,
+    point3::Point3,
+    ray::Ray,
+    scene_file::{ExportedMaterials, ObjectDescription},
+    sphere::hit_sphere,
+    vec3::Vec3,
+};
+
+/// A sphere moving in a straight line from `start_center` at `start_time` to `end_center` at
+/// `end_time`, which is blurred by a camera whose shutter is open while it moves
+///
+/// Before and after that it stays where it starts and ends, so that it never leaves its bounding box.
+#[derive(Clone)]
+pub struct MovingSphere {
+    start_center: Point3,
+    end_center: Point3,
+    start_time: f64,
+    end_time: f64,
+    radius: f64,
+    material: Arc<dyn Material>,
+}
+
+impl MovingSphere {
+    pub fn new(
+        start_center: Point3,
+        end_center: Point3,
+        start_time: f64,
+        end_time: f64,
+        radius: f64,
+        material: Arc<dyn Material>,
+    ) -> Self {
+        Self {
+            start_center,
+            end_center,
+            start_time,
+            end_time,
+            radius,
+            material,
+        }
+    }
+
+    /// Where the center is at a time
+    pub fn center(&self, time: f64) -> Point3 {
+        if self.end_time <= self.start_time {
+            return self.start_center;
+        }
+        let fraction = ((time - self.start_time) / (self.end_time - self.start_time)).clamp(0., 1.);
+        self.start_center + fraction * (self.end_center - self.start_center)
+    }
+}
+
+impl Hittable for MovingSphere {
+    fn hit(&self, ray: Ray, t_min: f64, t_max: f64, record: &mut HitRecord) -> bool {
+        hit_sphere(
+            self.center(ray.time),
+            self.radius,
+            &self.material,
+            ray,
+            t_min,
+            t_max,
+            record,
+        )
+    }
+
+    fn bounding_box(&self, output_box: &mut Aabb) -> bool {
+        let radius = self.radius.abs();
+        let radius = Vec3::new(radius, radius, radius);
+        let start = Aabb::new(self.start_center - radius, self.start_center + radius);
+        let end = Aabb::new(self.end_center - radius, self.end_center + radius);
+        *output_box = start.surrounding(end);
+        true
+    }
+
+    fn export(
+        &self,
+        materials: &mut ExportedMaterials,
+    ) -> Result<ObjectDescription, Box<dyn Error>> {
+        Ok(ObjectDescription::MovingSphere {
+            start_center: self.start_center,
+            end_center: self.end_center,
+            start_time: self.start_time,
+            end_time: self.end_time,
+            radius: self.radius,
+            material: materials.name(&self.material)?,
+        })
+    }
+}
+
+#[cfg(test)]
+fn test_moving_sphere() -> MovingSphere {
+    // A unit sphere moving from the origin to x = 4 between the times 0 and 1
+    MovingSphere::new(
+        Point3::new(0., 0., 0.),
+        Point3::new(4., 0., 0.),
+        0.,
+        1.,
+        1.,
+        Arc::new(crate::lambertian::Lambertian::new(Vec3::new(0.5, 0.5, 0.5))),
+    )
+}
+
+#[test]
+fn test_moving_sphere_is_hit_where_it_is_at_the_time_of_the_ray() {
+    // Given a moving sphere
+    let sphere = test_moving_sphere();
+
+    // When rays along the z axis at x = 2 come at different times
+    let hits = |time| {
+        let mut record = HitRecord::new();
+        let ray = Ray::at_time(Point3::new(2., 0., 5.), Vec3::new(0., 0., -1.), time);
+        sphere.hit(ray, 0.001, f64::INFINITY, &mut record)
+    };
+
+    // Then only the one halfway hits the sphere, which is at x = 2 by then
+    assert!(!hits(0.));
+    assert!(hits(0.5));
+    assert!(!hits(1.));
+    // And it stays where it ends
+    assert_eq!(sphere.center(2.), Point3::new(4., 0., 0.));
+}
+
+#[test]
+fn test_moving_sphere_bounding_box_covers_the_path() {
+    // Given a moving sphere
+    let sphere = test_moving_sphere();
+
+    // When I get its bounding box
+    let mut output_box = Aabb::new(Point3::new(0., 0., 0.), Point3::new(0., 0., 0.));
+    assert!(sphere.bounding_box(&mut output_box));
+
+    // Then it goes from the start to the end
+    assert_eq!(output_box.minimum, Point3::new(-1., -1., -1.));
+    assert_eq!(output_box.maximum, Point3::new(5., 1., 1.));
+}
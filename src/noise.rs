@@ -0,0 +1,156 @@
+use rand::seq::SliceRandom;
+
+use crate::{point3::Point3, rng::rng_from_seed, vec3::Vec3};
+
+// Lattice points repeat after this many cells along each axis
+const POINT_COUNT: usize = 256;
+
+/// Smooth random noise over space, the same for the same seed: Perlin gradient noise, turbulence built
+/// from it, and Worley cellular noise
+///
+/// Both kinds are built on a lattice of unit cells, with random values picked for each cell by hashing
+/// its coordinates through shuffled tables.
+#[derive(Debug, Clone)]
+pub struct Noise {
+    // Perlin gradients, one per lattice point
+    gradients: Vec<Vec3>,
+    // Worley feature points, as offsets in a cell
+    feature_points: Vec<Vec3>,
+    permutations: [Vec<usize>; 3],
+}
+
+impl Noise {
+    pub fn new(seed: u64) -> Self {
+        let mut rng = rng_from_seed(seed);
+        let gradients = (0..POINT_COUNT)
+            .map(|_| Vec3::random_unit_vector(&mut rng))
+            .collect();
+        let feature_points = (0..POINT_COUNT).map(|_| Vec3::random(&mut rng)).collect();
+        let mut permutation = || {
+            let mut permutation: Vec<usize> = (0..POINT_COUNT).collect();
+            permutation.shuffle(&mut rng);
+            permutation
+        };
+        let permutations = [permutation(), permutation(), permutation()];
+        Self {
+            gradients,
+            feature_points,
+            permutations,
+        }
+    }
+
+    // A random index for the lattice point or cell at the given coordinates
+    fn hash(&self, i: i64, j: i64, k: i64) -> usize {
+        let mask = POINT_COUNT as i64 - 1;
+        self.permutations[0][(i & mask) as usize]
+            ^ self.permutations[1][(j & mask) as usize]
+            ^ self.permutations[2][(k & mask) as usize]
+    }
+
+    /// Perlin noise, from about -1 to 1, changing smoothly over distances of about 1
+    pub fn perlin(&self, point: Point3) -> f64 {
+        let floor = Vec3::new(point.x.floor(), point.y.floor(), point.z.floor());
+        let fraction = point - floor;
+        // Hermite smoothing, so that the noise has no creases at the cell borders
+        let smooth = |t: f64| t * t * (3. - 2. * t);
+        let (u, v, w) = (smooth(fraction.x), smooth(fraction.y), smooth(fraction.z));
+
+        let mut total = 0.;
+        for di in 0..2 {
+            for dj in 0..2 {
+                for dk in 0..2 {
+                    let corner = Vec3::new(f64::from(di), f64::from(dj), f64::from(dk));
+                    let gradient = self.gradients[self.hash(
+                        floor.x as i64 + i64::from(di),
+                        floor.y as i64 + i64::from(dj),
+                        floor.z as i64 + i64::from(dk),
+                    )];
+                    let weight = |t: f64, d: f64| d * t + (1. - d) * (1. - t);
+                    total += weight(u, corner.x)
+                        * weight(v, corner.y)
+                        * weight(w, corner.z)
+                        * gradient.dot(fraction - corner);
+                }
+            }
+        }
+        total
+    }
+
+    /// Perlin noise summed over `depth` octaves, each at twice the frequency and half the weight of the
+    /// one before, from 0 to about 1
+    pub fn turbulence(&self, point: Point3, depth: u32) -> f64 {
+        let mut total = 0.;
+        let mut point = point;
+        let mut weight = 1.;
+        for _ in 0..depth {
+            total += weight * self.perlin(point);
+            weight *= 0.5;
+            point = 2. * point;
+        }
+        total.abs()
+    }
+
+    /// Worley noise: the distance to the nearest of the random points, one in every cell
+    pub fn worley(&self, point: Point3) -> f64 {
+        let (i, j, k) = (
+            point.x.floor() as i64,
+            point.y.floor() as i64,
+            point.z.floor() as i64,
+        );
+        let mut nearest = f64::INFINITY;
+        for di in -1..=1 {
+            for dj in -1..=1 {
+                for dk in -1..=1 {
+                    let cell = (i + di, j + dj, k + dk);
+                    let feature_point = Vec3::new(cell.0 as f64, cell.1 as f64, cell.2 as f64)
+                        + self.feature_points[self.hash(cell.0, cell.1, cell.2)];
+                    nearest = nearest.min((feature_point - point).len());
+                }
+            }
+        }
+        nearest
+    }
+}
+
+#[test]
+fn test_noise_is_seeded() {
+    // Given noise from two generators with the same seed, and one with another
+    let (a, b, c) = (Noise::new(1), Noise::new(1), Noise::new(2));
+
+    // When I look up the same point
+    let point = Point3::new(0.3, 1.7, -2.2);
+
+    // Then the same seed gives the same noise, and another seed other noise
+    assert_eq!(a.perlin(point), b.perlin(point));
+    assert_eq!(a.worley(point), b.worley(point));
+    assert_ne!(a.perlin(point), c.perlin(point));
+}
+
+#[test]
+fn test_perlin_noise_is_smooth_and_zero_on_the_lattice() {
+    // Given noise
+    let noise = Noise::new(7);
+
+    // When I look up lattice points, and points close to each other
+    let on_lattice = noise.perlin(Point3::new(3., -2., 5.));
+    let here = noise.perlin(Point3::new(0.5, 0.5, 0.5));
+    let near = noise.perlin(Point3::new(0.501, 0.5, 0.5));
+
+    // Then the gradients add nothing on the lattice, and the noise changes little between close points
+    assert_eq!(on_lattice, 0.);
+    assert!((here - near).abs() < 0.01);
+    assert!(here.abs() <= 1.);
+}
+
+#[test]
+fn test_worley_noise_is_zero_at_feature_points() {
+    // Given noise
+    let noise = Noise::new(7);
+
+    // When I look up the feature point of a cell
+    let feature_point = Point3::new(2., -1., 0.) + noise.feature_points[noise.hash(2, -1, 0)];
+
+    // Then it is the nearest one, and points elsewhere are farther away
+    assert_eq!(noise.worley(feature_point), 0.);
+    assert!(noise.worley(feature_point + Vec3::new(0.1, 0., 0.)) > 0.);
+}
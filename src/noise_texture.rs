@@ -0,0 +1,137 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    color::Color,
+    noise::Noise,
+    point3::Point3,
+    scene_file::{PatternDescription, TextureDescription},
+    texture::Texture,
+};
+
+// Octaves of turbulence, after which more detail would be smaller than a pixel in most scenes
+const TURBULENCE_DEPTH: u32 = 7;
+
+/// The shape of a `NoiseTexture`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NoisePattern {
+    /// Soft blobs
+    Perlin,
+    /// Layers of Perlin noise, like clouds or rust
+    Turbulence,
+    /// Veins along the z axis, bent by turbulence
+    Marble,
+    /// Rings around the y axis, bent by noise
+    Wood,
+    /// Cells around random points, like stones or scales
+    Worley,
+}
+
+/// A procedural texture that blends between two colors by the noise at the hit point
+///
+/// The same seed always gives the same texture, so renders of scenes with noise stay deterministic.
+#[derive(Debug, Clone)]
+pub struct NoiseTexture {
+    noise: Noise,
+    seed: u64,
+    pattern: NoisePattern,
+    scale: f64,
+    low: Color,
+    high: Color,
+}
+
+impl NoiseTexture {
+    /// Noise from black to white, with features about `1 / scale` apart
+    pub fn new(pattern: NoisePattern, scale: f64, seed: u64) -> Self {
+        Self::with_colors(
+            pattern,
+            scale,
+            seed,
+            Color::new(0., 0., 0.),
+            Color::new(1., 1., 1.),
+        )
+    }
+
+    /// Noise going from `low` where the pattern is lowest to `high` where it is highest
+    pub fn with_colors(
+        pattern: NoisePattern,
+        scale: f64,
+        seed: u64,
+        low: Color,
+        high: Color,
+    ) -> Self {
+        Self {
+            noise: Noise::new(seed),
+            seed,
+            pattern,
+            scale,
+            low,
+            high,
+        }
+    }
+
+    // Where the pattern is at a point, from 0 to 1
+    fn amount(&self, point: Point3) -> f64 {
+        let point = self.scale * point;
+        let amount = match self.pattern {
+            NoisePattern::Perlin => 0.5 * (1. + self.noise.perlin(point)),
+            NoisePattern::Turbulence => self.noise.turbulence(point, TURBULENCE_DEPTH),
+            NoisePattern::Marble => {
+                let turbulence = self.noise.turbulence(point, TURBULENCE_DEPTH);
+                0.5 * (1. + (point.z + 10. * turbulence).sin())
+            }
+            NoisePattern::Wood => {
+                let rings = (point.x * point.x + point.z * point.z).sqrt();
+                (rings + 0.5 * self.noise.perlin(point)).rem_euclid(1.)
+            }
+            NoisePattern::Worley => self.noise.worley(point),
+        };
+        amount.clamp(0., 1.)
+    }
+}
+
+impl Texture for NoiseTexture {
+    fn value(&self, _u: f64, _v: f64, point: Point3) -> Color {
+        let amount = self.amount(point);
+        (1. - amount) * self.low + amount * self.high
+    }
+
+    fn describe(&self) -> Option<TextureDescription> {
+        Some(TextureDescription::Pattern(PatternDescription::Noise {
+            pattern: self.pattern,
+            scale: self.scale,
+            seed: self.seed,
+            low: self.low,
+            high: self.high,
+        }))
+    }
+}
+
+#[test]
+fn test_noise_textures_stay_between_their_colors() {
+    // Given every pattern, between a dark and a light color
+    let patterns = [
+        NoisePattern::Perlin,
+        NoisePattern::Turbulence,
+        NoisePattern::Marble,
+        NoisePattern::Wood,
+        NoisePattern::Worley,
+    ];
+    let (low, high) = (Color::new(0.1, 0.2, 0.3), Color::new(0.5, 0.6, 0.7));
+    for pattern in patterns.iter() {
+        let texture = NoiseTexture::with_colors(*pattern, 4., 3, low, high);
+
+        for i in 0..100 {
+            // When I look up points all over
+            let t = f64::from(i);
+            let point = Point3::new(t * 0.37, t * -0.11, t * 0.23);
+            let color = texture.value(0., 0., point);
+
+            // Then their colors are between the two, and the same every time
+            for axis in 0..3 {
+                assert!(low[axis] - 1e-12 <= color[axis] && color[axis] <= high[axis] + 1e-12);
+            }
+            assert_eq!(color, texture.value(0., 0., point));
+        }
+    }
+}
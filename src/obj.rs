@@ -0,0 +1,619 @@
+use std::collections::HashMap;
+use std::error::Error;
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use crate::{
+    color::Color,
+    dielectric::Dielectric,
+    diffuse_light::DiffuseLight,
+    hittable_list::HittableList,
+    image_texture::{ImageTexture, WrapMode},
+    lambertian::Lambertian,
+    material::Material,
+    metal::Metal,
+    point3::Point3,
+    triangle_mesh::{MeshData, TriangleMesh},
+    vec3::Vec3,
+};
+
+/// A material from an MTL file, with the parameters that can be mapped onto the materials of this crate
+#[derive(Debug, Clone, PartialEq)]
+pub struct MtlMaterial {
+    pub name: String,
+    /// `Kd`
+    pub diffuse: Color,
+    /// `Ks`
+    pub specular: Color,
+    /// `Ke`
+    pub emission: Color,
+    /// `Ns`, the specular exponent from 0 for rough to 1000 for polished
+    pub shininess: f64,
+    /// `Ni`
+    pub refraction_index: f64,
+    /// `d`, or 1 - `Tr`, from 0 for transparent to 1 for opaque
+    pub dissolve: f64,
+    /// `illum`, the illumination model
+    pub illumination: u32,
+    /// `map_Kd`, relative to the MTL file
+    pub diffuse_map: Option<PathBuf>,
+}
+
+impl MtlMaterial {
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            diffuse: Color::new(0.8, 0.8, 0.8),
+            specular: Color::new(0., 0., 0.),
+            emission: Color::new(0., 0., 0.),
+            shininess: 0.,
+            refraction_index: 1.,
+            dissolve: 1.,
+            illumination: 2,
+            diffuse_map: None,
+        }
+    }
+
+    /// The closest material this crate has: a light when it emits anything, glass when it is transparent
+    /// or refracts, metal when it reflects, and diffuse otherwise
+    ///
+    /// A diffuse material takes its color from `map_Kd` instead of `Kd` when it has one, loading it from
+    /// `directory`, where the MTL file is.
+    pub fn to_material(&self, directory: &Path) -> Result<Arc<dyn Material>, Box<dyn Error>> {
+        let is_black = |color: Color| color.x <= 0. && color.y <= 0. && color.z <= 0.;
+        if !is_black(self.emission) {
+            return Ok(Arc::new(DiffuseLight::new(self.emission)));
+        }
+        match self.illumination {
+            4 | 6 | 7 | 9 => return Ok(Arc::new(Dielectric::new(self.glass_refraction_index()))),
+            _ if self.dissolve < 1. => {
+                return Ok(Arc::new(Dielectric::new(self.glass_refraction_index())))
+            }
+            3 | 5 | 8 => {
+                let albedo = if is_black(self.specular) {
+                    self.diffuse
+                } else {
+                    self.specular
+                };
+                // Spread reflections like a Phong lobe with the specular exponent would
+                let fuzz = (2. / (self.shininess.max(0.) + 2.)).sqrt();
+                return Ok(Arc::new(Metal::new(albedo, fuzz)));
+            }
+            _ => {}
+        }
+        match &self.diffuse_map {
+            Some(file) => {
+                let texture = ImageTexture::load(directory.join(file), WrapMode::Repeat)?;
+                Ok(Arc::new(Lambertian::from_texture(Arc::new(texture))))
+            }
+            None => Ok(Arc::new(Lambertian::new(self.diffuse))),
+        }
+    }
+
+    // Exporters often leave Ni at 1 for glass, which would make it invisible
+    fn glass_refraction_index(&self) -> f64 {
+        if self.refraction_index > 1. {
+            self.refraction_index
+        } else {
+            1.5
+        }
+    }
+}
+
+/// Load a Wavefront OBJ file and the MTL files it uses, as one `TriangleMesh` per group and material
+///
+/// Polygons are split into triangle fans, so they have to be convex. Faces without normals are shaded
+/// flat, and a mesh only gets texture coordinates when all of its vertices have them.
+pub fn load_obj(path: impl AsRef<Path>) -> Result<HittableList, Box<dyn Error>> {
+    let path = path.as_ref();
+    let file =
+        File::open(path).map_err(|error| format!("can't open '{}': {}", path.display(), error))?;
+    parse_obj(
+        BufReader::new(file),
+        &path.display().to_string(),
+        path.parent(),
+    )
+}
+
+/// Load the materials of an MTL file
+pub fn load_mtl(path: impl AsRef<Path>) -> Result<Vec<MtlMaterial>, Box<dyn Error>> {
+    let path = path.as_ref();
+    let file =
+        File::open(path).map_err(|error| format!("can't open '{}': {}", path.display(), error))?;
+    parse_mtl(BufReader::new(file), &path.display().to_string())
+}
+
+// Where in the file a statement is, for error messages
+struct Location<'a> {
+    file_name: &'a str,
+    line: usize,
+}
+
+impl Location<'_> {
+    fn error(&self, message: impl std::fmt::Display) -> Box<dyn Error> {
+        format!("{}:{}: {}", self.file_name, self.line, message).into()
+    }
+}
+
+// Read the statements of an OBJ or MTL file, joining lines that end in a backslash and skipping comments
+fn statements(
+    reader: impl BufRead,
+    file_name: &str,
+    mut handle: impl FnMut(&Location, &str, &[&str]) -> Result<(), Box<dyn Error>>,
+) -> Result<(), Box<dyn Error>> {
+    let mut statement = String::new();
+    let mut first_line = 0;
+    for (i, line) in reader.lines().enumerate() {
+        let line = line.map_err(|error| format!("{}:{}: {}", file_name, i + 1, error))?;
+        if statement.is_empty() {
+            first_line = i + 1;
+        }
+        let line = line.split('#').next().unwrap_or("");
+        if let Some(continued) = line.trim_end().strip_suffix('\\') {
+            statement.push_str(continued);
+            statement.push(' ');
+            continue;
+        }
+        statement.push_str(line);
+
+        let words: Vec<&str> = statement.split_whitespace().collect();
+        if let Some((&keyword, arguments)) = words.split_first() {
+            let location = Location {
+                file_name,
+                line: first_line,
+            };
+            handle(&location, keyword, arguments)?;
+        }
+        statement.clear();
+    }
+    Ok(())
+}
+
+fn parse_numbers(
+    location: &Location,
+    keyword: &str,
+    arguments: &[&str],
+    count: std::ops::RangeInclusive<usize>,
+) -> Result<Vec<f64>, Box<dyn Error>> {
+    if !count.contains(&arguments.len()) {
+        return Err(location.error(format!(
+            "expected {} to {} numbers after '{}', got {}",
+            count.start(),
+            count.end(),
+            keyword,
+            arguments.len()
+        )));
+    }
+    arguments
+        .iter()
+        .map(|argument| {
+            argument
+                .parse::<f64>()
+                .ok()
+                .filter(|number| number.is_finite())
+                .ok_or_else(|| location.error(format!("expected a number, got '{}'", argument)))
+        })
+        .collect()
+}
+
+fn parse_color(
+    location: &Location,
+    keyword: &str,
+    arguments: &[&str],
+) -> Result<Color, Box<dyn Error>> {
+    let numbers = parse_numbers(location, keyword, arguments, 1..=3)?;
+    Ok(match numbers.as_slice() {
+        [gray] => Color::new(*gray, *gray, *gray),
+        [r, g, b] => Color::new(*r, *g, *b),
+        _ => return Err(location.error(format!("expected 1 or 3 numbers after '{}'", keyword))),
+    })
+}
+
+/// Parse the materials of an MTL file, `file_name` is only used in error messages
+pub fn parse_mtl(
+    reader: impl BufRead,
+    file_name: &str,
+) -> Result<Vec<MtlMaterial>, Box<dyn Error>> {
+    let mut materials: Vec<MtlMaterial> = Vec::new();
+    statements(reader, file_name, |location, keyword, arguments| {
+        if keyword == "newmtl" {
+            materials.push(MtlMaterial::new(&arguments.join(" ")));
+            return Ok(());
+        }
+        let material = match materials.last_mut() {
+            Some(material) => material,
+            None => {
+                return Err(location.error(format!("'{}' comes before any 'newmtl'", keyword)));
+            }
+        };
+        let number = |arguments: &[&str]| -> Result<f64, Box<dyn Error>> {
+            Ok(parse_numbers(location, keyword, arguments, 1..=1)?[0])
+        };
+        match keyword {
+            "Kd" => material.diffuse = parse_color(location, keyword, arguments)?,
+            "Ks" => material.specular = parse_color(location, keyword, arguments)?,
+            "Ke" => material.emission = parse_color(location, keyword, arguments)?,
+            "Ns" => material.shininess = number(arguments)?,
+            "Ni" => material.refraction_index = number(arguments)?,
+            "d" => material.dissolve = number(arguments)?,
+            "Tr" => material.dissolve = 1. - number(arguments)?,
+            "illum" => {
+                material.illumination = arguments
+                    .first()
+                    .and_then(|argument| argument.parse().ok())
+                    .ok_or_else(|| location.error("expected an illumination model after 'illum'"))?
+            }
+            // Options like -s come before the file name, which is always last
+            "map_Kd" => {
+                let file = arguments
+                    .last()
+                    .ok_or_else(|| location.error("expected a file name after 'map_Kd'"))?;
+                material.diffuse_map = Some(PathBuf::from(file));
+            }
+            // Ambient color, other texture maps and other parameters don't matter for a path tracer
+            _ => {}
+        }
+        Ok(())
+    })?;
+    Ok(materials)
+}
+
+// Indices of the position, texture coordinates and normal of a face vertex
+type FaceVertex = (usize, Option<usize>, Option<usize>);
+
+// A mesh that is being read, with its vertices made unique by the attributes they are built from
+#[derive(Default)]
+struct MeshBuilder {
+    data: MeshData,
+    vertices: HashMap<FaceVertex, usize>,
+    missing_normals: bool,
+    missing_uvs: bool,
+}
+
+/// Parse an OBJ file, loading the MTL files it uses from `directory`
+///
+/// `file_name` is only used in error messages.
+pub fn parse_obj(
+    reader: impl BufRead,
+    file_name: &str,
+    directory: Option<&Path>,
+) -> Result<HittableList, Box<dyn Error>> {
+    let mut positions: Vec<Point3> = Vec::new();
+    let mut normals: Vec<Vec3> = Vec::new();
+    let mut uvs: Vec<(f64, f64)> = Vec::new();
+    let mut materials: HashMap<String, Arc<dyn Material>> = HashMap::new();
+    let default_material: Arc<dyn Material> = Arc::new(Lambertian::new(Color::new(0.5, 0.5, 0.5)));
+
+    // Meshes in the order they were started, by group and material
+    let mut meshes: Vec<((String, String), MeshBuilder)> = Vec::new();
+    let mut group = String::new();
+    let mut material_name = String::new();
+
+    statements(reader, file_name, |location, keyword, arguments| {
+        match keyword {
+            "v" => {
+                let numbers = parse_numbers(location, keyword, arguments, 3..=4)?;
+                positions.push(Point3::new(numbers[0], numbers[1], numbers[2]));
+            }
+            "vn" => {
+                let numbers = parse_numbers(location, keyword, arguments, 3..=3)?;
+                normals.push(Vec3::new(numbers[0], numbers[1], numbers[2]));
+            }
+            "vt" => {
+                let numbers = parse_numbers(location, keyword, arguments, 1..=3)?;
+                uvs.push((numbers[0], numbers.get(1).copied().unwrap_or(0.)));
+            }
+            "g" | "o" => group = arguments.join(" "),
+            "usemtl" => {
+                material_name = arguments.join(" ");
+                if !materials.contains_key(&material_name) {
+                    return Err(location.error(format!("unknown material '{}'", material_name)));
+                }
+            }
+            "mtllib" => {
+                for library in arguments {
+                    let path = directory.unwrap_or_else(|| Path::new("")).join(library);
+                    let library = load_mtl(&path).map_err(|error| location.error(error))?;
+                    let library_directory = path.parent().unwrap_or_else(|| Path::new(""));
+                    for material in library {
+                        let built = material
+                            .to_material(library_directory)
+                            .map_err(|error| location.error(error))?;
+                        materials.insert(material.name.clone(), built);
+                    }
+                }
+            }
+            "f" => {
+                if arguments.len() < 3 {
+                    return Err(location.error(format!(
+                        "a face needs at least 3 vertices, got {}",
+                        arguments.len()
+                    )));
+                }
+                let key = (group.clone(), material_name.clone());
+                let mesh = match meshes.iter().position(|(mesh_key, _)| *mesh_key == key) {
+                    Some(i) => &mut meshes[i].1,
+                    None => {
+                        meshes.push((key, MeshBuilder::default()));
+                        &mut meshes.last_mut().unwrap().1
+                    }
+                };
+                let mut face = Vec::with_capacity(arguments.len());
+                for argument in arguments {
+                    let indices = parse_face_vertex(
+                        location,
+                        argument,
+                        (positions.len(), uvs.len(), normals.len()),
+                    )?;
+                    face.push(add_vertex(mesh, indices, &positions, &uvs, &normals));
+                }
+
+                // Split the polygon into a fan of triangles around its first vertex
+                for i in 1..face.len() - 1 {
+                    mesh.data.triangles.push([face[0], face[i], face[i + 1]]);
+                }
+            }
+            // Smoothing groups, lines, points, curves and other statements aren't rendered
+            _ => {}
+        }
+        Ok(())
+    })?;
+
+    let mut list = HittableList::new();
+    for ((_, material_name), mut mesh) in meshes {
+        if mesh.missing_normals {
+            mesh.data.normals.clear();
+        }
+        if mesh.missing_uvs {
+            mesh.data.uvs.clear();
+        }
+        let material = materials
+            .get(&material_name)
+            .cloned()
+            .unwrap_or_else(|| default_material.clone());
+        list.add(Box::new(TriangleMesh::new(mesh.data, material)?));
+    }
+    Ok(list)
+}
+
+// Resolve a face vertex like `1`, `1/2`, `1//3` or `1/2/3` to indices into the position, texture
+// coordinate and normal lists, where negative indices count back from the end of each list
+fn parse_face_vertex(
+    location: &Location,
+    vertex: &str,
+    counts: (usize, usize, usize),
+) -> Result<FaceVertex, Box<dyn Error>> {
+    let mut parts = vertex.split('/');
+    let index =
+        |part: Option<&str>, count: usize, what: &str| -> Result<Option<usize>, Box<dyn Error>> {
+            let part = match part {
+                Some(part) if !part.is_empty() => part,
+                _ => return Ok(None),
+            };
+            let number: i64 = part.parse().map_err(|_| {
+                location.error(format!("expected a {} index, got '{}'", what, part))
+            })?;
+            let resolved = if number < 0 {
+                count as i64 + number
+            } else {
+                number - 1
+            };
+            if resolved < 0 || resolved >= count as i64 {
+                return Err(location.error(format!(
+                    "{} index {} is out of range, there are {} so far",
+                    what, number, count
+                )));
+            }
+            Ok(Some(resolved as usize))
+        };
+    let position = index(parts.next(), counts.0, "position")?
+        .ok_or_else(|| location.error(format!("face vertex '{}' has no position", vertex)))?;
+    let uv = index(parts.next(), counts.1, "texture coordinate")?;
+    let normal = index(parts.next(), counts.2, "normal")?;
+    Ok((position, uv, normal))
+}
+
+// Add a vertex to a mesh unless it has the same attributes as one that is already there
+fn add_vertex(
+    mesh: &mut MeshBuilder,
+    indices: FaceVertex,
+    positions: &[Point3],
+    uvs: &[(f64, f64)],
+    normals: &[Vec3],
+) -> usize {
+    if let Some(&vertex) = mesh.vertices.get(&indices) {
+        return vertex;
+    }
+    let (position, uv, normal) = indices;
+    let vertex = mesh.data.positions.len();
+    mesh.data.positions.push(positions[position]);
+    mesh.data.uvs.push(uv.map_or((0., 0.), |uv| uvs[uv]));
+    mesh.data
+        .normals
+        .push(normal.map_or(Vec3::new(0., 0., 0.), |normal| normals[normal]));
+    mesh.missing_uvs |= uv.is_none();
+    mesh.missing_normals |= normal.is_none();
+    mesh.vertices.insert(indices, vertex);
+    vertex
+}
+
+#[cfg(test)]
+use crate::{hittable::HitRecord, hittable::Hittable, ray::Ray};
+
+#[test]
+fn test_parse_obj() {
+    // Given a square made of a quad with texture coordinates and normals, and a triangle in another group
+    let obj = b"# a square
+v 0 0 0
+v 1 0 0
+v 1 1 0
+v 0 1 0
+vt 0 0
+vt 1 0
+vt 1 1
+vt 0 1
+vn 0 0 1
+g square
+f 1/1/1 2/2/1 3/3/1 -1/-1/-1
+g triangle
+f 1 2 \\
+  4
+";
+
+    // When I parse it
+    let list = parse_obj(&obj[..], "square.obj", None).unwrap();
+
+    // Then there is a mesh for every group, which are hit like the faces describe
+    assert_eq!(list.len(), 2);
+    let mut record = HitRecord::new();
+    let ray = Ray::new(Point3::new(0.75, 0.5, 1.), Vec3::new(0., 0., -1.));
+    assert!(list.hit(ray, 0.001, f64::INFINITY, &mut record));
+    assert_eq!(record.object_id, 1);
+    assert_eq!(record.normal, Vec3::new(0., 0., 1.));
+    assert!((record.u - 0.75).abs() < 1e-12 && (record.v - 0.5).abs() < 1e-12);
+}
+
+#[test]
+fn test_parse_obj_reports_file_and_line() {
+    // Given OBJ files with mistakes
+    let cases: [(&[u8], &str); 4] = [
+        (
+            b"v 0 0 0\nv 1 zero 0\n",
+            "broken.obj:2: expected a number, got 'zero'",
+        ),
+        (
+            b"v 0 0 0\n\nf 1 2\n",
+            "broken.obj:3: a face needs at least 3 vertices",
+        ),
+        (
+            b"v 0 0 0\nf 1 2 3\n",
+            "broken.obj:2: position index 2 is out of range",
+        ),
+        (
+            b"usemtl missing\n",
+            "broken.obj:1: unknown material 'missing'",
+        ),
+    ];
+
+    for (obj, expected) in cases.iter() {
+        // When I parse them
+        let error = parse_obj(*obj, "broken.obj", None).err().unwrap();
+
+        // Then the error says where the mistake is
+        assert!(
+            error.to_string().starts_with(expected),
+            "'{}' doesn't start with '{}'",
+            error,
+            expected
+        );
+    }
+}
+
+#[test]
+fn test_parse_mtl() {
+    // Given an MTL file with several kinds of materials
+    let mtl = b"newmtl red
+Kd 0.8 0.1 0.1
+newmtl mirror
+illum 3
+Ks 0.9 0.9 0.9
+Ns 1000
+newmtl glass
+illum 4
+Ni 1.33
+d 0.1
+newmtl lamp
+Ke 4 4 4
+newmtl textured
+map_Kd -s 2 2 1 textures/wood.png
+";
+
+    // When I parse it
+    let materials = parse_mtl(&mtl[..], "materials.mtl").unwrap();
+
+    // Then every material has its parameters
+    let names: Vec<&str> = materials.iter().map(|m| m.name.as_str()).collect();
+    assert_eq!(names, vec!["red", "mirror", "glass", "lamp", "textured"]);
+    assert_eq!(materials[0].diffuse, Color::new(0.8, 0.1, 0.1));
+    assert_eq!(materials[1].illumination, 3);
+    assert_eq!(materials[1].shininess, 1000.);
+    assert_eq!(materials[2].refraction_index, 1.33);
+    assert_eq!(materials[2].dissolve, 0.1);
+    assert_eq!(materials[3].emission, Color::new(4., 4., 4.));
+    assert_eq!(
+        materials[4].diffuse_map,
+        Some(PathBuf::from("textures/wood.png"))
+    );
+}
+
+#[test]
+fn test_load_obj_with_mtllib() {
+    // Given an OBJ file that uses a light from an MTL file next to it
+    let directory = std::env::temp_dir().join("ray-tracing-test-load-obj-with-mtllib");
+    std::fs::create_dir_all(&directory).unwrap();
+    std::fs::write(directory.join("lamp.mtl"), "newmtl lamp\nKe 2 3 4\n").unwrap();
+    std::fs::write(
+        directory.join("lamp.obj"),
+        "mtllib lamp.mtl\nv 0 0 0\nv 1 0 0\nv 0 1 0\nusemtl lamp\nf 1 2 3\n",
+    )
+    .unwrap();
+
+    // When I load it
+    let list = load_obj(directory.join("lamp.obj")).unwrap();
+    std::fs::remove_dir_all(&directory).unwrap();
+
+    // Then the triangle glows with the light of the material
+    let mut record = HitRecord::new();
+    let ray = Ray::new(Point3::new(0.25, 0.25, 1.), Vec3::new(0., 0., -1.));
+    assert!(list.hit(ray, 0.001, f64::INFINITY, &mut record));
+    assert_eq!(record.material.emitted(&record), Color::new(2., 3., 4.));
+}
+
+#[test]
+fn test_load_obj_with_diffuse_map() {
+    // Given an OBJ file with a textured material, whose image is half black and half white
+    let directory = std::env::temp_dir().join("ray-tracing-test-load-obj-with-diffuse-map");
+    std::fs::create_dir_all(directory.join("textures")).unwrap();
+    std::fs::write(
+        directory.join("textures/halves.ppm"),
+        "P3 2 1 255\n0 0 0 255 255 255\n",
+    )
+    .unwrap();
+    std::fs::write(
+        directory.join("halves.mtl"),
+        "newmtl halves\nmap_Kd textures/halves.ppm\n",
+    )
+    .unwrap();
+    std::fs::write(
+        directory.join("halves.obj"),
+        "mtllib halves.mtl
+v 0 0 0\nv 1 0 0\nv 0 1 0
+vt 0 0\nvt 1 0\nvt 0 1
+usemtl halves
+f 1/1 2/2 3/3
+",
+    )
+    .unwrap();
+
+    // When I load it
+    let list = load_obj(directory.join("halves.obj")).unwrap();
+    std::fs::remove_dir_all(&directory).unwrap();
+
+    // Then the triangle takes its color from the image, with the pixel centers where u is 1/4 and 3/4
+    let color_at = |x| {
+        let mut record = HitRecord::new();
+        let ray = Ray::new(Point3::new(x, 0.1, 1.), Vec3::new(0., 0., -1.));
+        assert!(list.hit(ray, 0.001, f64::INFINITY, &mut record));
+        let mut attenuation = Color::new(0., 0., 0.);
+        let mut scattered = Ray::new(Point3::new(0., 0., 0.), Vec3::new(0., 0., 1.));
+        let mut rng = crate::rng::rng_from_seed(1);
+        record
+            .material
+            .scatter(ray, &record, &mut attenuation, &mut scattered, &mut rng);
+        attenuation
+    };
+    assert_eq!(color_at(0.25), Color::new(0., 0., 0.));
+    assert_eq!(color_at(0.75), Color::new(1., 1., 1.));
+}
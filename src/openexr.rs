@@ -0,0 +1,349 @@
+use std::error::Error;
+use std::io::Cursor;
+
+use exr::prelude::{
+    f16, AnyChannel, AnyChannels, Compression, Encoding, FlatSamples, Image, Layer,
+    LayerAttributes, LineOrder, SmallVec, WritableImage,
+};
+
+#[cfg(test)]
+use crate::color::Color;
+use crate::framebuffer::{Framebuffer, Pixel};
+
+/// How the scanlines of an OpenEXR file are compressed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExrCompression {
+    Uncompressed,
+    /// Lossless zlib compression of blocks of 16 scanlines
+    Zip,
+}
+
+/// The precision floating point channels are stored with. Object IDs are always stored as integers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExrSampleType {
+    /// 16 bit floats
+    Half,
+    /// 32 bit floats
+    Float,
+}
+
+/// The render passes that can be stored as layers in an OpenEXR file
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Pass {
+    /// Linear radiance and coverage, as the `R`, `G`, `B` and `A` channels
+    Beauty,
+    /// Distance from the camera, as `depth.Z`
+    Depth,
+    /// World space surface normal, as `normal.X`, `normal.Y` and `normal.Z`
+    Normal,
+    /// Surface color, as `albedo.R`, `albedo.G` and `albedo.B`
+    Albedo,
+    /// Object ID, as the unsigned integer channel `id.ID`
+    ObjectId,
+}
+
+impl Pass {
+    pub const ALL: [Pass; 5] = [
+        Pass::Beauty,
+        Pass::Depth,
+        Pass::Normal,
+        Pass::Albedo,
+        Pass::ObjectId,
+    ];
+
+    // The name of every channel of the pass, with a function that reads it from a pixel
+    #[allow(clippy::type_complexity)]
+    fn channels(self) -> Vec<(&'static str, fn(&Pixel) -> f64)> {
+        match self {
+            Pass::Beauty => vec![
+                ("R", |pixel| pixel.color.x),
+                ("G", |pixel| pixel.color.y),
+                ("B", |pixel| pixel.color.z),
+                ("A", |pixel| pixel.alpha),
+            ],
+            Pass::Depth => vec![("depth.Z", |pixel| pixel.depth)],
+            Pass::Normal => vec![
+                ("normal.X", |pixel| pixel.normal.x),
+                ("normal.Y", |pixel| pixel.normal.y),
+                ("normal.Z", |pixel| pixel.normal.z),
+            ],
+            Pass::Albedo => vec![
+                ("albedo.R", |pixel| pixel.albedo.x),
+                ("albedo.G", |pixel| pixel.albedo.y),
+                ("albedo.B", |pixel| pixel.albedo.z),
+            ],
+            Pass::ObjectId => vec![("id.ID", |pixel| f64::from(pixel.object_id))],
+        }
+    }
+}
+
+/// Which passes to write to an OpenEXR file, and how
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExrSettings {
+    pub compression: ExrCompression,
+    pub sample_type: ExrSampleType,
+    pub passes: Vec<Pass>,
+}
+
+impl ExrSettings {
+    pub fn new(compression: ExrCompression, sample_type: ExrSampleType, passes: Vec<Pass>) -> Self {
+        Self {
+            compression,
+            sample_type,
+            passes,
+        }
+    }
+}
+
+impl Default for ExrSettings {
+    fn default() -> Self {
+        Self::new(ExrCompression::Zip, ExrSampleType::Half, Pass::ALL.to_vec())
+    }
+}
+
+/// Write the passes of the framebuffer as layers of a single part, scanline OpenEXR file
+///
+/// Apart from the beauty pass, every pass is a layer whose channels are prefixed by its name, like
+/// `normal.X`, which is how compositing packages expect render passes.
+pub fn write_exr(
+    framebuffer: &Framebuffer,
+    settings: &ExrSettings,
+    mut writer: impl std::io::Write,
+) -> Result<(), Box<dyn Error>> {
+    let mut channels = SmallVec::new();
+    for pass in settings.passes.iter() {
+        for (name, read) in pass.channels() {
+            let values = framebuffer.pixels().iter().map(read);
+            let samples = match (pass, settings.sample_type) {
+                (Pass::ObjectId, _) => FlatSamples::U32(values.map(|value| value as u32).collect()),
+                (_, ExrSampleType::Half) => FlatSamples::F16(values.map(f16::from_f64).collect()),
+                (_, ExrSampleType::Float) => {
+                    FlatSamples::F32(values.map(|value| value as f32).collect())
+                }
+            };
+            channels.push(AnyChannel::new(name, samples));
+        }
+    }
+
+    let encoding = Encoding {
+        compression: match settings.compression {
+            ExrCompression::Uncompressed => Compression::Uncompressed,
+            ExrCompression::Zip => Compression::ZIP16,
+        },
+        line_order: LineOrder::Increasing,
+        ..Encoding::default()
+    };
+    let layer = Layer::new(
+        (framebuffer.width(), framebuffer.height()),
+        LayerAttributes::default(),
+        encoding,
+        AnyChannels::sort(channels),
+    );
+
+    // The encoder needs to seek, so encode to memory first
+    let mut buffer = Cursor::new(Vec::new());
+    Image::from_layer(layer).write().to_buffered(&mut buffer)?;
+    writer.write_all(&buffer.into_inner())?;
+
+    // Exit
+    Ok(())
+}
+
+/// Read the first layer of an OpenEXR file, with the passes `write_exr` writes when they are present
+///
+/// The `R`, `G` and `B` channels are required, every other channel is optional.
+pub fn read_exr(mut reader: impl std::io::Read) -> Result<Framebuffer, Box<dyn Error>> {
+    use exr::prelude::{read, ReadChannels, ReadLayers};
+
+    // The decoder needs to seek, so read into memory first
+    let mut data = Vec::new();
+    reader.read_to_end(&mut data)?;
+    let image = read()
+        .no_deep_data()
+        .largest_resolution_level()
+        .all_channels()
+        .first_valid_layer()
+        .all_attributes()
+        .from_buffered(Cursor::new(data))?;
+    let layer = image.layer_data;
+    let (width, height) = (layer.size.width(), layer.size.height());
+
+    let mut framebuffer = Framebuffer::new(width, height);
+    for pass in Pass::ALL.iter() {
+        for (name, _) in pass.channels() {
+            let channel = layer
+                .channel_data
+                .list
+                .iter()
+                .find(|channel| channel.name.to_string() == name);
+            let values: Vec<f64> = match channel.map(|channel| &channel.sample_data) {
+                Some(FlatSamples::U32(values)) => {
+                    values.iter().map(|&value| value.into()).collect()
+                }
+                Some(samples) => samples.values_as_f32().map(f64::from).collect(),
+                None if ["R", "G", "B"].contains(&name) => {
+                    return Err(format!("the OpenEXR file has no {} channel", name).into())
+                }
+                None => continue,
+            };
+            for (pixel, value) in framebuffer.pixels_mut().iter_mut().zip(values) {
+                write_channel(pixel, name, value);
+            }
+        }
+    }
+
+    Ok(framebuffer)
+}
+
+// Set the value of a channel of a pass, the inverse of `Pass::channels`
+fn write_channel(pixel: &mut Pixel, name: &str, value: f64) {
+    match name {
+        "R" => pixel.color.x = value,
+        "G" => pixel.color.y = value,
+        "B" => pixel.color.z = value,
+        "A" => pixel.alpha = value,
+        "depth.Z" => pixel.depth = value,
+        "normal.X" => pixel.normal.x = value,
+        "normal.Y" => pixel.normal.y = value,
+        "normal.Z" => pixel.normal.z = value,
+        "albedo.R" => pixel.albedo.x = value,
+        "albedo.G" => pixel.albedo.y = value,
+        "albedo.B" => pixel.albedo.z = value,
+        "id.ID" => pixel.object_id = value as u32,
+        _ => unreachable!("{} is not a channel of any pass", name),
+    }
+}
+
+#[cfg(test)]
+fn read_exr_channels(data: Vec<u8>) -> Vec<(String, Vec<f32>)> {
+    use exr::prelude::{read, ReadChannels, ReadLayers};
+
+    let image = read()
+        .no_deep_data()
+        .largest_resolution_level()
+        .all_channels()
+        .first_valid_layer()
+        .all_attributes()
+        .from_buffered(Cursor::new(data))
+        .unwrap();
+    image
+        .layer_data
+        .channel_data
+        .list
+        .into_iter()
+        .map(|channel| {
+            (
+                channel.name.to_string(),
+                channel.sample_data.values_as_f32().collect(),
+            )
+        })
+        .collect()
+}
+
+#[cfg(test)]
+fn test_framebuffer() -> Framebuffer {
+    use crate::vec3::Vec3;
+
+    let mut framebuffer = Framebuffer::from_colors(vec![vec![
+        Color::new(0.5, 2., 100.),
+        Color::new(0.25, 0., 1.),
+    ]]);
+    let hit = framebuffer.pixel_mut(0, 0);
+    hit.depth = 3.5;
+    hit.normal = Vec3::new(0., 1., 0.);
+    hit.albedo = Color::new(0.8, 0.6, 0.2);
+    hit.object_id = 7;
+    framebuffer.pixel_mut(1, 0).alpha = 0.;
+    framebuffer
+}
+
+#[test]
+fn test_write_exr_all_passes() {
+    // Given a framebuffer with passes
+    let mut result = Vec::new();
+
+    // When I write all of its passes as a zip compressed half float EXR
+    write_exr(&test_framebuffer(), &ExrSettings::default(), &mut result).unwrap();
+
+    // Then reading it back gives every channel of every pass
+    let channels = read_exr_channels(result);
+    let names: Vec<&str> = channels.iter().map(|(name, _)| name.as_str()).collect();
+    assert_eq!(
+        names,
+        vec![
+            "A", "B", "G", "R", "albedo.B", "albedo.G", "albedo.R", "depth.Z", "id.ID", "normal.X",
+            "normal.Y", "normal.Z"
+        ]
+    );
+    let channel = |name: &str| channels.iter().find(|(n, _)| n == name).unwrap().1.clone();
+    assert_eq!(channel("R"), vec![0.5, 0.25]);
+    assert_eq!(channel("B"), vec![100., 1.]);
+    assert_eq!(channel("A"), vec![1., 0.]);
+    assert_eq!(channel("depth.Z"), vec![3.5, f32::INFINITY]);
+    assert_eq!(channel("normal.Y"), vec![1., 0.]);
+    assert_eq!(channel("id.ID"), vec![7., 0.]);
+    assert!((channel("albedo.R")[0] - 0.8).abs() < 1e-3);
+}
+
+#[test]
+fn test_write_exr_uncompressed_float() {
+    // Given a framebuffer with passes
+    let mut result = Vec::new();
+
+    // When I write only its beauty and depth as an uncompressed float EXR
+    let settings = ExrSettings::new(
+        ExrCompression::Uncompressed,
+        ExrSampleType::Float,
+        vec![Pass::Beauty, Pass::Depth],
+    );
+    write_exr(&test_framebuffer(), &settings, &mut result).unwrap();
+
+    // Then reading it back gives the exact values of only those passes
+    let channels = read_exr_channels(result);
+    let names: Vec<&str> = channels.iter().map(|(name, _)| name.as_str()).collect();
+    assert_eq!(names, vec!["A", "B", "G", "R", "depth.Z"]);
+    assert_eq!(channels[2].1, vec![2., 0.]);
+}
+
+#[test]
+fn test_read_exr_round_trip() {
+    // Given a framebuffer with passes
+    let framebuffer = test_framebuffer();
+    let mut data = Vec::new();
+
+    // When I write all of its passes as floats and read them back
+    let settings = ExrSettings::new(
+        ExrCompression::Zip,
+        ExrSampleType::Float,
+        Pass::ALL.to_vec(),
+    );
+    write_exr(&framebuffer, &settings, &mut data).unwrap();
+    let read = read_exr(data.as_slice()).unwrap();
+
+    // Then I get the same framebuffer, within the precision of 32 bit floats
+    assert_eq!((read.width(), read.height()), (2, 1));
+    for (pixel, expected) in read.pixels().iter().zip(framebuffer.pixels()) {
+        assert!((pixel.color - expected.color).len() < 1e-6);
+        assert!((pixel.albedo - expected.albedo).len() < 1e-6);
+        assert_eq!(pixel.normal, expected.normal);
+        assert_eq!(pixel.depth, expected.depth);
+        assert_eq!(pixel.alpha, expected.alpha);
+        assert_eq!(pixel.object_id, expected.object_id);
+    }
+}
+
+#[test]
+fn test_read_exr_without_passes() {
+    // Given an EXR with only a beauty pass
+    let mut data = Vec::new();
+    let settings = ExrSettings::new(ExrCompression::Zip, ExrSampleType::Half, vec![Pass::Beauty]);
+    write_exr(&test_framebuffer(), &settings, &mut data).unwrap();
+
+    // When I read it
+    let read = read_exr(data.as_slice()).unwrap();
+
+    // Then the passes it doesn't have are left at their defaults
+    assert_eq!(read.pixel(0, 0).color, Color::new(0.5, 2., 100.));
+    assert_eq!(read.pixel(0, 0).depth, f64::INFINITY);
+    assert_eq!(read.pixel(0, 0).object_id, 0);
+}
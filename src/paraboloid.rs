@@ -0,0 +1,123 @@
+use std::error::Error;
+use std::sync::Arc;
+
+use crate::{
+    aabb::Aabb,
+    cylinder::{around_y_axis, hit_cap, record_closest},
+    hittable::{HitRecord, Hittable},
+    material::Material,
+    point3::Point3,
+    polynomial::solve_quadratic,
+    ray::Ray,
+    scene_file::{ExportedMaterials, ObjectDescription},
+    vec3::Vec3,
+};
+
+/// A solid paraboloid, like a bowl or a reflector, opening upwards from its `vertex` and closed by a
+/// round cap of `radius` at `height` above it
+///
+/// The texture coordinates go around its side from the -x side, with v going up from the vertex, and
+/// across the cap like a decal.
+#[derive(Clone)]
+pub struct Paraboloid {
+    vertex: Point3,
+    radius: f64,
+    height: f64,
+    material: Arc<dyn Material>,
+}
+
+impl Paraboloid {
+    pub fn new(vertex: Point3, radius: f64, height: f64, material: Arc<dyn Material>) -> Self {
+        Self {
+            vertex,
+            radius,
+            height,
+            material,
+        }
+    }
+}
+
+impl Hittable for Paraboloid {
+    // The side is where height·(x² + z²) = radius²·y
+    fn hit(&self, ray: Ray, t_min: f64, t_max: f64, record: &mut HitRecord) -> bool {
+        let local = Ray::at_time(ray.origin - self.vertex, ray.direction, ray.time);
+        let (o, d) = (local.origin, local.direction);
+        let (h, r2) = (self.height, self.radius * self.radius);
+        let roots = solve_quadratic(
+            h * (d.x * d.x + d.z * d.z),
+            2. * h * (o.x * d.x + o.z * d.z) - r2 * d.y,
+            h * (o.x * o.x + o.z * o.z) - r2 * o.y,
+        );
+        let side = roots
+            .into_iter()
+            .flat_map(|(first, second)| [first, second])
+            .filter_map(|t| {
+                let point = local.at(t);
+                if point.y < 0. || point.y > self.height {
+                    return None;
+                }
+                let normal = Vec3::new(2. * h * point.x, -r2, 2. * h * point.z).unit_vector();
+                Some((t, normal, around_y_axis(point), point.y / self.height))
+            });
+        let cap = hit_cap(local, self.height, self.radius, Vec3::new(0., 1., 0.));
+
+        record_closest(side.chain(cap), ray, t_min, t_max, &self.material, record)
+    }
+
+    fn bounding_box(&self, output_box: &mut Aabb) -> bool {
+        *output_box = Aabb::new(
+            self.vertex - Vec3::new(self.radius, 0., self.radius),
+            self.vertex + Vec3::new(self.radius, self.height, self.radius),
+        );
+        true
+    }
+
+    fn export(
+        &self,
+        materials: &mut ExportedMaterials,
+    ) -> Result<ObjectDescription, Box<dyn Error>> {
+        Ok(ObjectDescription::Paraboloid {
+            vertex: self.vertex,
+            radius: self.radius,
+            height: self.height,
+            material: materials.name(&self.material)?,
+        })
+    }
+}
+
+#[test]
+fn test_paraboloid_side_and_cap() {
+    // Given a paraboloid where y = x² + z², up to a height of 4
+    let paraboloid = Paraboloid::new(
+        Point3::new(0., 0., 0.),
+        2.,
+        4.,
+        Arc::new(crate::lambertian::Lambertian::new(Vec3::new(0.5, 0.5, 0.5))),
+    );
+    let hit = |origin: Point3, direction: Vec3| {
+        let mut record = HitRecord::new();
+        let hit = paraboloid.hit(
+            Ray::new(origin, direction),
+            0.001,
+            f64::INFINITY,
+            &mut record,
+        );
+        (hit, record)
+    };
+
+    // When rays come at it from below, straight up, from the side and from above
+    let (below_hit, below) = hit(Point3::new(1., -3., 0.), Vec3::new(0., 1., 0.));
+    let (side_hit, side) = hit(Point3::new(-5., 1., 0.), Vec3::new(1., 0., 0.));
+    let (above_hit, above) = hit(Point3::new(1., 10., 0.), Vec3::new(0., -1., 0.));
+
+    // Then a ray straight up hits the curved side, which faces down and out
+    assert!(below_hit && side_hit && above_hit);
+    assert!((below.t - 4.).abs() < 1e-12);
+    assert!(below.normal.y < 0. && below.normal.x > 0. && below.is_front_face);
+    assert!((below.v - 0.25).abs() < 1e-12);
+    assert!((side.t - 4.).abs() < 1e-12);
+
+    // And one from above hits the cap first
+    assert_eq!(above.t, 6.);
+    assert_eq!(above.normal, Vec3::new(0., 1., 0.));
+}
@@ -0,0 +1,173 @@
+use std::error::Error;
+
+use crate::{
+    color::Color,
+    framebuffer::{value_count, Framebuffer, Pixel},
+    ppm::{next_token, parse_token},
+};
+
+/// Write the image as a Portable Float Map (`.pfm`), keeping the linear radiance of every pixel
+///
+/// See http://www.pauldebevec.com/Research/HDR/PFM/ for the format. Channels are written as little
+/// endian 32 bit floats, and rows are stored from the bottom of the image to the top.
+pub fn write_pfm(
+    framebuffer: &Framebuffer,
+    mut writer: impl std::io::Write,
+) -> Result<(), Box<dyn Error>> {
+    // Write file header, a negative scale means little endian
+    writeln!(
+        writer,
+        "PF\n{} {}\n-1.0",
+        framebuffer.width(),
+        framebuffer.height()
+    )?;
+
+    // Write pixels
+    for row in framebuffer.rows().rev() {
+        for pixel in row {
+            for channel in [pixel.color.x, pixel.color.y, pixel.color.z].iter() {
+                writer.write_all(&(*channel as f32).to_le_bytes())?;
+            }
+        }
+    }
+
+    // Exit
+    Ok(())
+}
+
+/// Read a color (`PF`) or grayscale (`Pf`) Portable Float Map of either endianness
+pub fn read_pfm(mut reader: impl std::io::Read) -> Result<Framebuffer, Box<dyn Error>> {
+    let mut data = Vec::new();
+    reader.read_to_end(&mut data)?;
+
+    // Read file header
+    let mut position = 0;
+    let channel_count = match next_token(&data, &mut position)? {
+        "PF" => 3,
+        "Pf" => 1,
+        other => {
+            return Err(format!("expected a PFM starting with PF or Pf, got '{}'", other).into())
+        }
+    };
+    let width: usize = parse_token(&data, &mut position)?;
+    let height: usize = parse_token(&data, &mut position)?;
+    let scale: f64 = parse_token(&data, &mut position)?;
+    let little_endian = scale < 0.;
+
+    // Read pixels, a single whitespace character separates them from the header
+    let floats = data.get(position + 1..).unwrap_or(&[]);
+    if floats.len() < value_count(width, height, channel_count * 4)? {
+        return Err("the PFM ends before all of its pixels".into());
+    }
+    let channels: Vec<f64> = floats
+        .chunks(4)
+        .take(value_count(width, height, channel_count)?)
+        .map(|bytes| {
+            let bytes = [bytes[0], bytes[1], bytes[2], bytes[3]];
+            f64::from(if little_endian {
+                f32::from_le_bytes(bytes)
+            } else {
+                f32::from_be_bytes(bytes)
+            })
+        })
+        .collect();
+    let mut pixels: Vec<Pixel> = channels
+        .chunks(channel_count)
+        .map(|channel| {
+            let last = channel_count - 1;
+            Pixel::new(Color::new(
+                channel[0],
+                channel[1.min(last)],
+                channel[2.min(last)],
+            ))
+        })
+        .collect();
+
+    // Rows are stored from the bottom of the image to the top
+    if width > 0 {
+        pixels = pixels
+            .chunks(width)
+            .rev()
+            .flat_map(|row| row.iter().copied())
+            .collect();
+    }
+
+    Framebuffer::try_from_pixels(width, height, pixels)
+}
+
+#[test]
+fn test_write_pfm() {
+    // Given an image with radiance above 1
+    let mut result = Vec::new();
+    let image = vec![
+        vec![Color::new(0., 0.25, 0.5)],
+        vec![Color::new(1., 16., 1024.)],
+    ];
+
+    // When I write it as a PFM
+    write_pfm(&Framebuffer::from_colors(image), &mut result).unwrap();
+
+    // Then it has a header, followed by the bottom row and then the top row as floats
+    let header = b"PF\n1 2\n-1.0\n";
+    assert!(result.starts_with(header));
+    let channels: Vec<f32> = result[header.len()..]
+        .chunks(4)
+        .map(|bytes| f32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+        .collect();
+    assert_eq!(channels, vec![1., 16., 1024., 0., 0.25, 0.5]);
+}
+
+#[test]
+fn test_read_pfm_round_trip() {
+    // Given an image with radiance above 1
+    let mut data = Vec::new();
+    let image = vec![
+        vec![Color::new(0., 0.25, 0.5), Color::new(3., 2., 1.)],
+        vec![Color::new(1., 16., 1024.), Color::new(0.125, 0., 7.)],
+    ];
+
+    // When I write it as a PFM and read it back
+    write_pfm(&Framebuffer::from_colors(image.clone()), &mut data).unwrap();
+    let framebuffer = read_pfm(data.as_slice()).unwrap();
+
+    // Then I get the same image
+    assert_eq!(framebuffer.colors(), image);
+}
+
+#[test]
+fn test_read_big_endian_grayscale_pfm() {
+    // Given a big endian grayscale PFM, bottom row first
+    let mut data = b"Pf\n1 2\n1.0\n".to_vec();
+    data.extend_from_slice(&2_f32.to_be_bytes());
+    data.extend_from_slice(&0.5_f32.to_be_bytes());
+
+    // When I read it
+    let framebuffer = read_pfm(data.as_slice()).unwrap();
+
+    // Then every pixel is gray, with the top row first
+    assert_eq!(
+        framebuffer.colors(),
+        vec![
+            vec![Color::new(0.5, 0.5, 0.5)],
+            vec![Color::new(2., 2., 2.)]
+        ]
+    );
+}
+
+#[test]
+fn test_read_pfm_with_corrupt_size() {
+    // Given PFMs with sizes that are too big to count, or bigger than their pixels
+    let sizes: [&[u8]; 3] = [
+        b"PF\n18446744073709551615 2\n-1.0\n\x00\x00\x00\x00",
+        b"PF\n1537228672809129302 1\n-1.0\n\x00\x00\x00\x00",
+        b"Pf\n100000 100000\n-1.0\n\x00\x00\x00\x00",
+    ];
+
+    for data in sizes.iter() {
+        // When I read them
+        let result = read_pfm(*data);
+
+        // Then I get an error instead of a panic
+        assert!(result.is_err());
+    }
+}
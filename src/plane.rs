@@ -0,0 +1,159 @@
+use std::error::Error;
+use std::sync::Arc;
+
+use crate::{
+    aabb::Aabb,
+    hittable::{HitRecord, Hittable},
+    material::Material,
+    point3::Point3,
+    ray::Ray,
+    scene_file::{ExportedMaterials, ObjectDescription},
+    vec3::Vec3,
+};
+
+/// An infinite plane through `point`, facing where `normal` points to, like a floor or a distant wall
+///
+/// The texture coordinates of a hit are its distances from `point` along two directions in the plane,
+/// so textures repeat across it. It has no bounding box, so a `BvhNode` tests it for every ray.
+#[derive(Clone)]
+pub struct Plane {
+    point: Point3,
+    normal: Vec3,
+    material: Arc<dyn Material>,
+}
+
+impl Plane {
+    pub fn new(point: Point3, normal: Vec3, material: Arc<dyn Material>) -> Self {
+        Self {
+            point,
+            normal: normal.unit_vector(),
+            material,
+        }
+    }
+}
+
+// Where a ray crosses the plane through `point` with `normal`, if it does so between t_min and t_max
+pub(crate) fn intersect_plane(
+    point: Point3,
+    normal: Vec3,
+    ray: Ray,
+    t_min: f64,
+    t_max: f64,
+) -> Option<f64> {
+    let denominator = normal.dot(ray.direction);
+    // The ray runs along the plane
+    if denominator.abs() < 1e-12 * ray.direction.len() {
+        return None;
+    }
+    let t = normal.dot(point - ray.origin) / denominator;
+    if t < t_min || t_max < t {
+        return None;
+    }
+    Some(t)
+}
+
+// Two unit directions that are perpendicular to each other and to the unit `normal`, turning
+// counterclockwise around it, for surface coordinates on flat shapes
+pub(crate) fn perpendicular_axes(normal: Vec3) -> (Vec3, Vec3) {
+    // Start from whichever axis is furthest from the normal, so the cross product is well defined
+    let helper = if normal.x.abs() < 0.9 {
+        Vec3::new(1., 0., 0.)
+    } else {
+        Vec3::new(0., 1., 0.)
+    };
+    let first = helper.cross(normal).unit_vector();
+    let second = normal.cross(first);
+    (first, second)
+}
+
+impl Hittable for Plane {
+    fn hit(&self, ray: Ray, t_min: f64, t_max: f64, record: &mut HitRecord) -> bool {
+        let t = match intersect_plane(self.point, self.normal, ray, t_min, t_max) {
+            Some(t) => t,
+            None => return false,
+        };
+
+        record.t = t;
+        record.point = ray.at(t);
+        let (first, second) = perpendicular_axes(self.normal);
+        let offset = record.point - self.point;
+        record.u = offset.dot(first);
+        record.v = offset.dot(second);
+        record.set_face_normal(ray, self.normal);
+        record.material = Arc::clone(&self.material);
+
+        true
+    }
+
+    fn bounding_box(&self, _output_box: &mut Aabb) -> bool {
+        false
+    }
+
+    fn export(
+        &self,
+        materials: &mut ExportedMaterials,
+    ) -> Result<ObjectDescription, Box<dyn Error>> {
+        Ok(ObjectDescription::Plane {
+            point: self.point,
+            normal: self.normal,
+            material: materials.name(&self.material)?,
+        })
+    }
+}
+
+#[test]
+fn test_plane_is_hit_from_both_sides() {
+    // Given a floor at y = -1
+    let floor = Plane::new(
+        Point3::new(0., -1., 0.),
+        Vec3::new(0., 2., 0.),
+        Arc::new(crate::lambertian::Lambertian::new(Vec3::new(0.5, 0.5, 0.5))),
+    );
+
+    // When rays come at it from above, from below and along it
+    let hit = |origin: Point3, direction: Vec3| {
+        let mut record = HitRecord::new();
+        let hit = floor.hit(
+            Ray::new(origin, direction),
+            0.001,
+            f64::INFINITY,
+            &mut record,
+        );
+        (hit, record)
+    };
+    let (from_above, above) = hit(Point3::new(3., 1., 4.), Vec3::new(0., -1., 0.));
+    let (from_below, below) = hit(Point3::new(0., -3., 0.), Vec3::new(1., 1., 0.));
+    let (along, _) = hit(Point3::new(0., -1., 0.), Vec3::new(1., 0., 0.));
+
+    // Then the ones crossing it hit it, facing back at them, and the one along it doesn't
+    assert!(from_above && from_below && !along);
+    assert_eq!(above.t, 2.);
+    assert_eq!(above.normal, Vec3::new(0., 1., 0.));
+    assert!(above.is_front_face);
+    assert_eq!(below.normal, Vec3::new(0., -1., 0.));
+    assert!(!below.is_front_face);
+
+    // And the texture coordinates are distances in the plane
+    assert!((above.u * above.u + above.v * above.v - 25.).abs() < 1e-9);
+}
+
+#[test]
+fn test_perpendicular_axes() {
+    // Given normals along every axis and in between
+    let normals = [
+        Vec3::new(1., 0., 0.),
+        Vec3::new(0., 1., 0.),
+        Vec3::new(0., 0., -1.),
+        Vec3::new(1., 1., 1.).unit_vector(),
+    ];
+
+    for normal in normals.iter() {
+        // When I get the axes in the plane they define
+        let (first, second) = perpendicular_axes(*normal);
+
+        // Then they are unit directions perpendicular to each other, going counterclockwise
+        assert!((first.len() - 1.).abs() < 1e-12 && (second.len() - 1.).abs() < 1e-12);
+        assert!(first.dot(second).abs() < 1e-12 && first.dot(*normal).abs() < 1e-12);
+        assert!((first.cross(second) - *normal).len() < 1e-12);
+    }
+}
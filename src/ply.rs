@@ -0,0 +1,422 @@
+use std::error::Error;
+use std::fs::File;
+use std::io::BufReader;
+use std::path::Path;
+use std::sync::Arc;
+
+use crate::{
+    material::Material,
+    point3::Point3,
+    triangle_mesh::{MeshData, TriangleMesh},
+    vec3::Vec3,
+};
+
+/// Load a PLY file as a mesh of one material, see `read_ply`
+pub fn load_ply(
+    path: impl AsRef<Path>,
+    material: Arc<dyn Material>,
+) -> Result<TriangleMesh, Box<dyn Error>> {
+    let path = path.as_ref();
+    let in_file = |error: Box<dyn Error>| format!("{}: {}", path.display(), error);
+    let file = File::open(path).map_err(|error| in_file(error.into()))?;
+    let mesh = read_ply(BufReader::new(file)).map_err(in_file)?;
+    Ok(TriangleMesh::new(mesh, material).map_err(in_file)?)
+}
+
+// The ways PLY files store their values
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Encoding {
+    Ascii,
+    LittleEndian,
+    BigEndian,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum ScalarType {
+    I8,
+    U8,
+    I16,
+    U16,
+    I32,
+    U32,
+    F32,
+    F64,
+}
+
+impl ScalarType {
+    fn parse(name: &str) -> Result<Self, Box<dyn Error>> {
+        Ok(match name {
+            "char" | "int8" => ScalarType::I8,
+            "uchar" | "uint8" => ScalarType::U8,
+            "short" | "int16" => ScalarType::I16,
+            "ushort" | "uint16" => ScalarType::U16,
+            "int" | "int32" => ScalarType::I32,
+            "uint" | "uint32" => ScalarType::U32,
+            "float" | "float32" => ScalarType::F32,
+            "double" | "float64" => ScalarType::F64,
+            _ => return Err(format!("unknown property type '{}'", name).into()),
+        })
+    }
+
+    fn size(self) -> usize {
+        match self {
+            ScalarType::I8 | ScalarType::U8 => 1,
+            ScalarType::I16 | ScalarType::U16 => 2,
+            ScalarType::I32 | ScalarType::U32 | ScalarType::F32 => 4,
+            ScalarType::F64 => 8,
+        }
+    }
+}
+
+#[derive(Debug)]
+enum PropertyType {
+    Scalar(ScalarType),
+    /// A count followed by that many items
+    List(ScalarType, ScalarType),
+}
+
+#[derive(Debug)]
+struct Property {
+    name: String,
+    property_type: PropertyType,
+}
+
+#[derive(Debug)]
+struct Element {
+    name: String,
+    count: usize,
+    properties: Vec<Property>,
+}
+
+impl Element {
+    fn property(&self, names: &[&str]) -> Option<usize> {
+        self.properties
+            .iter()
+            .position(|property| names.contains(&property.name.as_str()))
+    }
+}
+
+// The values after the header, read one at a time
+struct Body<'a> {
+    encoding: Encoding,
+    data: &'a [u8],
+    position: usize,
+}
+
+impl Body<'_> {
+    fn read(&mut self, scalar_type: ScalarType) -> Result<f64, Box<dyn Error>> {
+        if self.encoding == Encoding::Ascii {
+            let token = crate::ppm::next_token(self.data, &mut self.position).map_err(|error| {
+                if self.position < self.data.len() {
+                    error
+                } else {
+                    "the file ends before all elements are read".into()
+                }
+            })?;
+            return token
+                .parse::<f64>()
+                .map_err(|_| format!("expected a number, got '{}'", token).into());
+        }
+
+        let size = scalar_type.size();
+        let bytes = self
+            .data
+            .get(self.position..self.position + size)
+            .ok_or("the file ends before all elements are read")?;
+        self.position += size;
+        let mut buffer = [0; 8];
+        if self.encoding == Encoding::LittleEndian {
+            buffer[..size].copy_from_slice(bytes);
+        } else {
+            for (i, byte) in bytes.iter().rev().enumerate() {
+                buffer[i] = *byte;
+            }
+        }
+        let [b0, b1, b2, b3, ..] = buffer;
+        Ok(match scalar_type {
+            ScalarType::I8 => f64::from(b0 as i8),
+            ScalarType::U8 => f64::from(b0),
+            ScalarType::I16 => f64::from(i16::from_le_bytes([b0, b1])),
+            ScalarType::U16 => f64::from(u16::from_le_bytes([b0, b1])),
+            ScalarType::I32 => f64::from(i32::from_le_bytes([b0, b1, b2, b3])),
+            ScalarType::U32 => f64::from(u32::from_le_bytes([b0, b1, b2, b3])),
+            ScalarType::F32 => f64::from(f32::from_le_bytes([b0, b1, b2, b3])),
+            ScalarType::F64 => f64::from_le_bytes(buffer),
+        })
+    }
+
+    // Read the values of all properties of one element, with lists flattened into `lists`
+    fn read_row(
+        &mut self,
+        element: &Element,
+        scalars: &mut Vec<f64>,
+        lists: &mut Vec<Vec<f64>>,
+    ) -> Result<(), Box<dyn Error>> {
+        scalars.clear();
+        lists.clear();
+        for property in element.properties.iter() {
+            match property.property_type {
+                PropertyType::Scalar(scalar_type) => scalars.push(self.read(scalar_type)?),
+                PropertyType::List(count_type, item_type) => {
+                    scalars.push(0.);
+                    let count = self.read(count_type)?;
+                    if count < 0. || count.fract() != 0. {
+                        return Err(format!("'{}' has {} items", property.name, count).into());
+                    }
+                    let items = (0..count as usize)
+                        .map(|_| self.read(item_type))
+                        .collect::<Result<Vec<f64>, _>>()?;
+                    lists.push(items);
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Read an ASCII or binary PLY file
+///
+/// The `vertex` element needs `x`, `y` and `z`, and can have normals (`nx`, `ny`, `nz`) and texture
+/// coordinates (`u` and `v`, or `s` and `t`). Polygons from the `vertex_indices` of the `face` element
+/// are split into triangle fans. Other elements and properties, like colors, are skipped.
+pub fn read_ply(mut reader: impl std::io::Read) -> Result<MeshData, Box<dyn Error>> {
+    let mut data = Vec::new();
+    reader.read_to_end(&mut data)?;
+
+    let (encoding, elements, body_start) = read_header(&data)?;
+    let mut body = Body {
+        encoding,
+        data: &data,
+        position: body_start,
+    };
+
+    let mut mesh = MeshData::default();
+    let mut scalars = Vec::new();
+    let mut lists = Vec::new();
+    for element in elements.iter() {
+        match element.name.as_str() {
+            "vertex" => {
+                let required = |name| {
+                    element
+                        .property(&[name])
+                        .ok_or_else(|| format!("the vertices have no '{}' property", name))
+                };
+                let position = [required("x")?, required("y")?, required("z")?];
+                let normal = [
+                    element.property(&["nx"]),
+                    element.property(&["ny"]),
+                    element.property(&["nz"]),
+                ];
+                let uv = [
+                    element.property(&["u", "s", "texture_u", "texture_s"]),
+                    element.property(&["v", "t", "texture_v", "texture_t"]),
+                ];
+                for _ in 0..element.count {
+                    body.read_row(element, &mut scalars, &mut lists)?;
+                    mesh.positions.push(Point3::new(
+                        scalars[position[0]],
+                        scalars[position[1]],
+                        scalars[position[2]],
+                    ));
+                    if let [Some(x), Some(y), Some(z)] = normal {
+                        mesh.normals
+                            .push(Vec3::new(scalars[x], scalars[y], scalars[z]));
+                    }
+                    if let [Some(u), Some(v)] = uv {
+                        mesh.uvs.push((scalars[u], scalars[v]));
+                    }
+                }
+            }
+            "face" => {
+                let indices = element
+                    .property(&["vertex_indices", "vertex_index"])
+                    .ok_or("the faces have no 'vertex_indices' property")?;
+                // Lists come in the same order as the properties, so count the ones before the indices
+                let list = element.properties[..indices]
+                    .iter()
+                    .filter(|property| matches!(property.property_type, PropertyType::List(..)))
+                    .count();
+                if let PropertyType::Scalar(_) = element.properties[indices].property_type {
+                    return Err("the 'vertex_indices' of the faces aren't a list".into());
+                }
+                for face in 0..element.count {
+                    body.read_row(element, &mut scalars, &mut lists)?;
+                    let polygon = lists[list]
+                        .iter()
+                        .map(|&index| {
+                            if index < 0. || index.fract() != 0. {
+                                return Err(format!("face {} has vertex index {}", face, index));
+                            }
+                            Ok(index as usize)
+                        })
+                        .collect::<Result<Vec<usize>, _>>()?;
+                    if polygon.len() < 3 {
+                        return Err(format!(
+                            "face {} needs at least 3 vertices, got {}",
+                            face,
+                            polygon.len()
+                        )
+                        .into());
+                    }
+                    for i in 1..polygon.len() - 1 {
+                        mesh.triangles
+                            .push([polygon[0], polygon[i], polygon[i + 1]]);
+                    }
+                }
+            }
+            _ => {
+                for _ in 0..element.count {
+                    body.read_row(element, &mut scalars, &mut lists)?;
+                }
+            }
+        }
+    }
+
+    Ok(mesh)
+}
+
+// Read the header, returning how the body is stored, the elements in it and where it starts
+fn read_header(data: &[u8]) -> Result<(Encoding, Vec<Element>, usize), Box<dyn Error>> {
+    if !data.starts_with(b"ply") {
+        return Err("not a PLY file".into());
+    }
+
+    let mut encoding = None;
+    let mut elements: Vec<Element> = Vec::new();
+    let mut position = 0;
+    loop {
+        let end = data[position..]
+            .iter()
+            .position(|&byte| byte == b'\n')
+            .ok_or("the header has no 'end_header'")?;
+        let line = std::str::from_utf8(&data[position..position + end])
+            .map_err(|_| "the header isn't text")?;
+        position += end + 1;
+
+        let words: Vec<&str> = line.split_whitespace().collect();
+        match words.as_slice() {
+            ["end_header"] => break,
+            ["ply"] | ["comment", ..] | ["obj_info", ..] | [] => {}
+            ["format", format, "1.0"] => {
+                encoding = Some(match *format {
+                    "ascii" => Encoding::Ascii,
+                    "binary_little_endian" => Encoding::LittleEndian,
+                    "binary_big_endian" => Encoding::BigEndian,
+                    _ => return Err(format!("unknown format '{}'", format).into()),
+                })
+            }
+            ["element", name, count] => elements.push(Element {
+                name: name.to_string(),
+                count: count
+                    .parse()
+                    .map_err(|_| format!("element '{}' has count '{}'", name, count))?,
+                properties: Vec::new(),
+            }),
+            ["property", rest @ ..] => {
+                let element = elements
+                    .last_mut()
+                    .ok_or("a property comes before any element")?;
+                let (property_type, name) = match rest {
+                    ["list", count_type, item_type, name] => (
+                        PropertyType::List(
+                            ScalarType::parse(count_type)?,
+                            ScalarType::parse(item_type)?,
+                        ),
+                        name,
+                    ),
+                    [scalar_type, name] => {
+                        (PropertyType::Scalar(ScalarType::parse(scalar_type)?), name)
+                    }
+                    _ => return Err(format!("can't read the header line '{}'", line).into()),
+                };
+                element.properties.push(Property {
+                    name: name.to_string(),
+                    property_type,
+                });
+            }
+            _ => return Err(format!("can't read the header line '{}'", line.trim()).into()),
+        }
+    }
+
+    let encoding = encoding.ok_or("the header has no 'format'")?;
+    Ok((encoding, elements, position))
+}
+
+#[cfg(test)]
+const TEST_SQUARE: &str = "ply
+format ascii 1.0
+comment a unit square
+element vertex 4
+property float x
+property float y
+property float z
+property float s
+property float t
+element face 1
+property uchar red
+property list uchar int vertex_indices
+end_header
+0 0 0 0 0
+1 0 0 1 0
+1 1 0 1 1
+0 1 0 0 1
+255 4 0 1 2 3
+";
+
+#[test]
+fn test_read_ply() {
+    // Given an ASCII PLY of a square with texture coordinates
+    let ply = TEST_SQUARE;
+
+    // When I read it
+    let mesh = read_ply(ply.as_bytes()).unwrap();
+
+    // Then the square is split into two triangles
+    assert_eq!(mesh.positions.len(), 4);
+    assert_eq!(mesh.positions[2], Point3::new(1., 1., 0.));
+    assert!(mesh.normals.is_empty());
+    assert_eq!(mesh.uvs[1], (1., 0.));
+    assert_eq!(mesh.triangles, vec![[0, 1, 2], [0, 2, 3]]);
+}
+
+#[test]
+fn test_read_big_endian_ply() {
+    // Given a binary big endian PLY of a triangle with double positions and ushort indices
+    let mut ply = b"ply
+format binary_big_endian 1.0
+element vertex 3
+property double x
+property double y
+property double z
+element face 1
+property list uchar ushort vertex_indices
+end_header
+"
+    .to_vec();
+    for coordinate in [0., 0., 0., 2., 0., 0., 0., 2., 0.].iter() {
+        ply.extend_from_slice(&f64::to_be_bytes(*coordinate));
+    }
+    ply.push(3);
+    for index in [0_u16, 1, 2].iter() {
+        ply.extend_from_slice(&index.to_be_bytes());
+    }
+
+    // When I read it
+    let mesh = read_ply(&ply[..]).unwrap();
+
+    // Then it has the triangle
+    assert_eq!(mesh.positions[1], Point3::new(2., 0., 0.));
+    assert_eq!(mesh.triangles, vec![[0, 1, 2]]);
+}
+
+#[test]
+fn test_read_truncated_ply() {
+    // Given a PLY that ends in the middle of a face
+    let ply = &TEST_SQUARE[..TEST_SQUARE.len() - 4];
+
+    // When I read it
+    let result = read_ply(ply.as_bytes());
+
+    // Then I get an error
+    assert!(result.is_err());
+}
@@ -0,0 +1,251 @@
+use std::convert::TryFrom;
+use std::error::Error;
+
+use crate::{
+    color::Color,
+    framebuffer::{Framebuffer, Pixel},
+};
+
+/// The channels and bit depths of PNG that can be written
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PngFormat {
+    Rgb8,
+    Rgb16,
+    Rgba8,
+    Rgba16,
+}
+
+impl PngFormat {
+    fn color_type(self) -> ::png::ColorType {
+        match self {
+            PngFormat::Rgb8 | PngFormat::Rgb16 => ::png::ColorType::Rgb,
+            PngFormat::Rgba8 | PngFormat::Rgba16 => ::png::ColorType::Rgba,
+        }
+    }
+
+    fn bit_depth(self) -> ::png::BitDepth {
+        match self {
+            PngFormat::Rgb8 | PngFormat::Rgba8 => ::png::BitDepth::Eight,
+            PngFormat::Rgb16 | PngFormat::Rgba16 => ::png::BitDepth::Sixteen,
+        }
+    }
+
+    fn has_alpha(self) -> bool {
+        self.color_type() == ::png::ColorType::Rgba
+    }
+}
+
+/// Write the framebuffer as a PNG, the RGBA formats taking the alpha channel from its coverage
+pub fn write_png(
+    framebuffer: &Framebuffer,
+    format: PngFormat,
+    writer: impl std::io::Write,
+) -> Result<(), Box<dyn Error>> {
+    let mut encoder = ::png::Encoder::new(
+        writer,
+        u32::try_from(framebuffer.width())?,
+        u32::try_from(framebuffer.height())?,
+    );
+    encoder.set_color(format.color_type());
+    encoder.set_depth(format.bit_depth());
+    let mut png_writer = encoder.write_header()?;
+
+    // Collect all channels of all pixels, row by row
+    let mut channels = Vec::new();
+    for pixel in framebuffer.pixels() {
+        channels.extend_from_slice(&[pixel.color.x, pixel.color.y, pixel.color.z]);
+        if format.has_alpha() {
+            channels.push(pixel.alpha);
+        }
+    }
+
+    // Convert channels to integers, 16 bit values with the most significant byte first
+    let data: Vec<u8> = match format.bit_depth() {
+        ::png::BitDepth::Sixteen => channels
+            .into_iter()
+            .flat_map(|channel| ((65535.999_f64 * channel).floor() as u16).to_be_bytes())
+            .collect(),
+        _ => channels
+            .into_iter()
+            .map(|channel| (255.999_f64 * channel).floor() as u8)
+            .collect(),
+    };
+    png_writer.write_image_data(&data)?;
+    png_writer.finish()?;
+
+    // Exit
+    Ok(())
+}
+
+/// Read a PNG of any color type and bit depth, taking the alpha channel as coverage
+///
+/// Channels are scaled to 0 - 1 range and kept as they are stored, so they are usually gamma corrected.
+pub fn read_png(reader: impl std::io::Read) -> Result<Framebuffer, Box<dyn Error>> {
+    // Expand palettes and low bit depths to 8 bits per channel
+    let mut decoder = ::png::Decoder::new(reader);
+    decoder.set_transformations(::png::Transformations::EXPAND);
+    let mut png_reader = decoder.read_info()?;
+    let mut buffer = vec![0; png_reader.output_buffer_size()];
+    let info = png_reader.next_frame(&mut buffer)?;
+    buffer.truncate(info.buffer_size());
+
+    // Convert channels to 0 - 1 range, 16 bit values have the most significant byte first
+    let (color_type, bit_depth) = png_reader.output_color_type();
+    let channels: Vec<f64> = match bit_depth {
+        ::png::BitDepth::Sixteen => buffer
+            .chunks(2)
+            .map(|bytes| f64::from(u16::from_be_bytes([bytes[0], bytes[1]])) / 65535.)
+            .collect(),
+        _ => buffer.iter().map(|&byte| f64::from(byte) / 255.).collect(),
+    };
+    let pixels = channels
+        .chunks(color_type.samples())
+        .map(|channel| {
+            let (color, alpha) = match color_type {
+                ::png::ColorType::Grayscale => (Color::new(channel[0], channel[0], channel[0]), 1.),
+                ::png::ColorType::GrayscaleAlpha => {
+                    (Color::new(channel[0], channel[0], channel[0]), channel[1])
+                }
+                ::png::ColorType::Rgba => {
+                    (Color::new(channel[0], channel[1], channel[2]), channel[3])
+                }
+                _ => (Color::new(channel[0], channel[1], channel[2]), 1.),
+            };
+            Pixel {
+                alpha,
+                ..Pixel::new(color)
+            }
+        })
+        .collect();
+
+    Framebuffer::try_from_pixels(
+        usize::try_from(info.width)?,
+        usize::try_from(info.height)?,
+        pixels,
+    )
+}
+
+#[cfg(test)]
+fn decode_png(data: &[u8]) -> (::png::OutputInfo, Vec<u8>) {
+    let decoder = ::png::Decoder::new(data);
+    let mut reader = decoder.read_info().unwrap();
+    let mut buffer = vec![0; reader.output_buffer_size()];
+    let info = reader.next_frame(&mut buffer).unwrap();
+    buffer.truncate(info.buffer_size());
+    (info, buffer)
+}
+
+#[test]
+fn test_write_png() {
+    // Given an image
+    let mut result = Vec::new();
+    let image = vec![vec![Color::new(0., 0.3, 0.7), Color::new(0.6, 0.2, 1.5)]];
+
+    // When I write it as an 8 bit RGB PNG
+    write_png(
+        &Framebuffer::from_colors(image),
+        PngFormat::Rgb8,
+        &mut result,
+    )
+    .unwrap();
+
+    // Then decoding it gives the same pixels as the PPM writer would
+    let (info, pixels) = decode_png(&result);
+    assert_eq!((info.width, info.height), (2, 1));
+    assert_eq!(info.color_type, ::png::ColorType::Rgb);
+    assert_eq!(pixels, vec![0, 76, 179, 153, 51, 255]);
+}
+
+#[test]
+fn test_write_png_16_bit_with_alpha() {
+    // Given an image with an alpha channel
+    let mut result = Vec::new();
+    let image = vec![vec![Color::new(0., 0.3, 1.)], vec![Color::new(1., 1., 1.)]];
+    let mut framebuffer = Framebuffer::from_colors(image);
+    framebuffer.pixel_mut(0, 0).alpha = 0.5;
+
+    // When I write it as a 16 bit RGBA PNG
+    write_png(&framebuffer, PngFormat::Rgba16, &mut result).unwrap();
+
+    // Then decoding it gives 16 bit channels with the alpha after every pixel
+    let (info, pixels) = decode_png(&result);
+    assert_eq!(info.color_type, ::png::ColorType::Rgba);
+    assert_eq!(info.bit_depth, ::png::BitDepth::Sixteen);
+    let channels: Vec<u16> = pixels
+        .chunks(2)
+        .map(|bytes| u16::from_be_bytes([bytes[0], bytes[1]]))
+        .collect();
+    assert_eq!(
+        channels,
+        vec![0, 19660, 65535, 32767, 65535, 65535, 65535, 65535]
+    );
+}
+
+#[test]
+fn test_write_png_rgba_of_colors_is_opaque() {
+    // Given a framebuffer made of nothing but colors
+    let mut result = Vec::new();
+    let framebuffer = Framebuffer::from_colors(vec![vec![Color::new(0.2, 0.4, 0.6)]]);
+
+    // When I write it as an 8 bit RGBA PNG
+    write_png(&framebuffer, PngFormat::Rgba8, &mut result).unwrap();
+
+    // Then every pixel is opaque
+    let (_, pixels) = decode_png(&result);
+    assert_eq!(pixels, vec![51, 102, 153, 255]);
+}
+
+#[test]
+fn test_read_png_round_trip() {
+    // Given an image with an alpha channel
+    let image = vec![
+        vec![Color::new(0., 0.3, 0.7), Color::new(0.6, 0.2, 1.)],
+        vec![Color::new(1., 1., 1.), Color::new(0.5, 0.5, 0.5)],
+    ];
+    let mut framebuffer = Framebuffer::from_colors(image.clone());
+    framebuffer.pixel_mut(1, 1).alpha = 0.;
+
+    for &(format, step) in [
+        (PngFormat::Rgb8, 1. / 255.),
+        (PngFormat::Rgba8, 1. / 255.),
+        (PngFormat::Rgb16, 1. / 65535.),
+        (PngFormat::Rgba16, 1. / 65535.),
+    ]
+    .iter()
+    {
+        // When I write it in every format and read it back
+        let mut data = Vec::new();
+        write_png(&framebuffer, format, &mut data).unwrap();
+        let read = read_png(data.as_slice()).unwrap();
+
+        // Then every channel is within one step of what was written, and only RGBA keeps the alpha
+        assert_eq!((read.width(), read.height()), (2, 2));
+        for (pixel, expected) in read.pixels().iter().zip(image.iter().flatten()) {
+            assert!((pixel.color - *expected).len() <= 2. * step);
+        }
+        let expected_alpha = if format.has_alpha() { 0. } else { 1. };
+        assert_eq!(read.pixel(1, 1).alpha, expected_alpha);
+    }
+}
+
+#[test]
+fn test_read_grayscale_png() {
+    // Given an 8 bit grayscale PNG
+    let mut data = Vec::new();
+    let mut encoder = ::png::Encoder::new(&mut data, 2, 1);
+    encoder.set_color(::png::ColorType::Grayscale);
+    encoder.set_depth(::png::BitDepth::Eight);
+    let mut writer = encoder.write_header().unwrap();
+    writer.write_image_data(&[0, 255]).unwrap();
+    writer.finish().unwrap();
+
+    // When I read it
+    let framebuffer = read_png(data.as_slice()).unwrap();
+
+    // Then every pixel is an opaque gray
+    assert_eq!(
+        framebuffer.colors(),
+        vec![vec![Color::new(0., 0., 0.), Color::new(1., 1., 1.)]]
+    );
+    assert_eq!(framebuffer.pixel(0, 0).alpha, 1.);
+}
@@ -0,0 +1,240 @@
+//! Real roots of polynomials up to the fourth degree, for intersecting rays with curved surfaces
+//!
+//! Roots come back from smallest to largest. Repeated roots may come back once or twice, and cubics
+//! and quartics leave out roots that aren't finite.
+
+/// The real roots of a·x² + b·x + c, also when a is 0 and it is a line
+pub fn solve_quadratic(a: f64, b: f64, c: f64) -> Option<(f64, f64)> {
+    if a == 0. {
+        if b == 0. {
+            return None;
+        }
+        let root = -c / b;
+        return Some((root, root));
+    }
+    let discriminant = b * b - 4. * a * c;
+    if discriminant < 0. {
+        return None;
+    }
+    // Adding numbers of the same sign, so the larger root doesn't lose its digits, and getting the
+    // other one from their product c / a
+    let q = -0.5 * (b + b.signum() * discriminant.sqrt());
+    let (first, second) = if q == 0. { (0., 0.) } else { (q / a, c / q) };
+    Some((first.min(second), first.max(second)))
+}
+
+/// The real roots of a·x³ + b·x² + c·x + d
+pub fn solve_cubic(a: f64, b: f64, c: f64, d: f64) -> Vec<f64> {
+    if a == 0. {
+        return solve_quadratic(b, c, d)
+            .map(|(first, second)| vec![first, second])
+            .unwrap_or_default();
+    }
+    let (b, c, d) = (b / a, c / a, d / a);
+    let q = (b * b - 3. * c) / 9.;
+    let r = (2. * b * b * b - 9. * b * c + 27. * d) / 54.;
+    let shift = b / 3.;
+
+    let mut roots = if r * r < q * q * q {
+        // Three real roots, spread evenly around a circle
+        let theta = (r / (q * q * q).sqrt()).clamp(-1., 1.).acos();
+        let scale = -2. * q.sqrt();
+        (0..3)
+            .map(|k| scale * ((theta + 2. * std::f64::consts::PI * k as f64) / 3.).cos() - shift)
+            .collect()
+    } else {
+        let big = -r.signum() * (r.abs() + (r * r - q * q * q).sqrt()).cbrt();
+        let small = if big == 0. { 0. } else { q / big };
+        vec![big + small - shift]
+    };
+    roots.retain(|root| root.is_finite());
+    roots.sort_by(f64::total_cmp);
+    roots
+}
+
+/// The real roots of a·x⁴ + b·x³ + c·x² + d·x + e, by Ferrari's method, polished with Newton's
+pub fn solve_quartic(a: f64, b: f64, c: f64, d: f64, e: f64) -> Vec<f64> {
+    if a == 0. {
+        return solve_cubic(b, c, d, e);
+    }
+    let (b, c, d, e) = (b / a, c / a, d / a, e / a);
+
+    // Substituting x = y - b / 4 leaves y⁴ + p·y² + q·y + r
+    let shift = b / 4.;
+    let p = c - 6. * shift * shift;
+    let q = d - 2. * c * shift + 8. * shift * shift * shift;
+    let r = e - d * shift + c * shift * shift - 3. * shift * shift * shift * shift;
+
+    // How far from 0 the roots are, for telling whether q is small next to the other coefficients, which
+    // grow with its square and its fourth power
+    let size = p.abs().sqrt().max(r.abs().sqrt().sqrt());
+    let mut depressed = Vec::with_capacity(4);
+    if q.abs() <= 1e-12 * size * size * size {
+        // Without the odd term it is a quadratic in y²
+        if let Some((first, second)) = solve_quadratic(1., p, r) {
+            for z in [first, second].iter().filter(|&&z| z >= 0.) {
+                depressed.push(z.sqrt());
+                depressed.push(-z.sqrt());
+            }
+        }
+    } else {
+        // Adding m to y² + p/2 on both sides turns the right side into a square for any m that is a
+        // root of the resolvent cubic, and the largest one is positive
+        let m = solve_cubic(1., p, p * p / 4. - r, -q * q / 8.)
+            .into_iter()
+            .fold(f64::NEG_INFINITY, f64::max);
+        if m > 0. {
+            let s = (2. * m).sqrt();
+            for &(sign, constant) in [(-1., q / (2. * s)), (1., -q / (2. * s))].iter() {
+                if let Some((first, second)) = solve_quadratic(1., sign * s, p / 2. + m + constant)
+                {
+                    depressed.push(first);
+                    depressed.push(second);
+                }
+            }
+        }
+    }
+
+    let value = |x: f64| (((x + b) * x + c) * x + d) * x + e;
+    let slope = |x: f64| ((4. * x + 3. * b) * x + 2. * c) * x + d;
+    let mut roots: Vec<f64> = depressed
+        .into_iter()
+        .map(|y| {
+            let mut x = y - shift;
+            for _ in 0..2 {
+                let derivative = slope(x);
+                if derivative != 0. {
+                    x -= value(x) / derivative;
+                }
+            }
+            x
+        })
+        .collect();
+    roots.retain(|root| root.is_finite());
+    roots.sort_by(f64::total_cmp);
+    roots
+}
+
+#[cfg(test)]
+fn assert_roots(actual: &[f64], expected: &[f64]) {
+    assert_eq!(
+        actual.len(),
+        expected.len(),
+        "{:?} isn't {:?}",
+        actual,
+        expected
+    );
+    for (actual_root, expected_root) in actual.iter().zip(expected.iter()) {
+        assert!(
+            (actual_root - expected_root).abs() < 1e-9,
+            "{:?} isn't {:?}",
+            actual,
+            expected
+        );
+    }
+}
+
+#[test]
+fn test_solve_quadratic_and_cubic() {
+    // Given polynomials with known roots, one of them nearly cancelling out
+    // When I solve them
+    // Then I get the roots from smallest to largest
+    assert_eq!(solve_quadratic(1., -3., 2.), Some((1., 2.)));
+    assert_eq!(solve_quadratic(1., 0., 1.), None);
+    assert_eq!(solve_quadratic(0., 2., -4.), Some((2., 2.)));
+    let (small, large) = solve_quadratic(1., -1e8, 1.).unwrap();
+    assert!((small - 1e-8).abs() < 1e-20 && (large - 1e8).abs() < 1e-6);
+
+    // (x + 2)(x - 1)(x - 3)
+    assert_roots(&solve_cubic(2., -4., -10., 12.), &[-2., 1., 3.]);
+    // (x - 1)(x² + 1)
+    assert_roots(&solve_cubic(1., -1., 1., -1.), &[1.]);
+}
+
+#[test]
+fn test_solve_quartic() {
+    // Given quartics with four, two and no real roots, and one without an odd term
+    let cases: [([f64; 5], &[f64]); 4] = [
+        // (x + 3)(x + 1)(x - 2)(x - 5)
+        ([1., -3., -15., 19., 30.], &[-3., -1., 2., 5.]),
+        // 2(x - 1)(x - 4)(x² + 1)
+        ([2., -10., 10., -10., 8.], &[1., 4.]),
+        // (x² + 1)(x² + 4)
+        ([1., 0., 5., 0., 4.], &[]),
+        // (x² - 1)(x² - 9)
+        ([1., 0., -10., 0., 9.], &[-3., -1., 1., 3.]),
+    ];
+
+    for (coefficients, expected) in cases.iter() {
+        // When I solve them
+        let [a, b, c, d, e] = *coefficients;
+        let roots = solve_quartic(a, b, c, d, e);
+
+        // Then I get the real roots
+        assert_roots(&roots, expected);
+    }
+}
+
+#[test]
+fn test_solve_quartic_at_any_scale() {
+    for &s in [1e-6, 1e-3, 1e3, 1e6].iter() {
+        // Given quartics with their roots scaled by s, so that their odd term gets tiny or huge
+        let cases: [([f64; 5], [f64; 4]); 3] = [
+            // (x + 3s)(x + s)(x - 2s)(x - 5s)
+            (
+                [
+                    1.,
+                    -3. * s,
+                    -15. * s.powi(2),
+                    19. * s.powi(3),
+                    30. * s.powi(4),
+                ],
+                [-3., -1., 2., 5.],
+            ),
+            // (x² - s²)(x² - 9s²)
+            (
+                [1., 0., -10. * s.powi(2), 0., 9. * s.powi(4)],
+                [-3., -1., 1., 3.],
+            ),
+            // (x - s)(x - 3s)(x - 5s)(x - 7s), which only loses its odd term once it is moved by 4s
+            (
+                [
+                    1.,
+                    -16. * s,
+                    86. * s.powi(2),
+                    -176. * s.powi(3),
+                    105. * s.powi(4),
+                ],
+                [1., 3., 5., 7.],
+            ),
+        ];
+
+        for (coefficients, expected) in cases.iter() {
+            // When I solve them
+            let [a, b, c, d, e] = *coefficients;
+            let roots: Vec<f64> = solve_quartic(a, b, c, d, e)
+                .into_iter()
+                .map(|root| root / s)
+                .collect();
+
+            // Then I get the roots at the same scale
+            assert_roots(&roots, expected);
+        }
+    }
+}
+
+#[test]
+fn test_solve_coefficients_that_arent_finite() {
+    // Given polynomials with coefficients that aren't numbers or are infinite
+    // When I solve them
+    // Then I get no roots that aren't finite, instead of a panic
+    assert!(solve_cubic(1., f64::NAN, 0., 0.)
+        .iter()
+        .all(|root| root.is_finite()));
+    assert!(solve_quartic(1., f64::NAN, 0., 0., 1.)
+        .iter()
+        .all(|root| root.is_finite()));
+    assert!(solve_quartic(1., 0., f64::INFINITY, 1., -1.)
+        .iter()
+        .all(|root| root.is_finite()));
+}
@@ -1,21 +1,71 @@
 use std::error::Error;
+use std::str::FromStr;
 
-use crate::Color;
+use crate::{
+    color::Color,
+    framebuffer::{value_count, Framebuffer, Pixel},
+};
+
+/// The flavours of PPM that can be written
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PpmFormat {
+    /// Plain text (P3), one pixel per line with 8 bits per channel
+    Ascii,
+    /// Binary (P6) with 8 bits per channel
+    Binary,
+    /// Binary (P6) with 16 bits per channel, most significant byte first
+    Binary16,
+}
+
+impl PpmFormat {
+    fn magic_number(self) -> &'static str {
+        match self {
+            PpmFormat::Ascii => "P3",
+            PpmFormat::Binary | PpmFormat::Binary16 => "P6",
+        }
+    }
+
+    fn max_value(self) -> u16 {
+        match self {
+            PpmFormat::Ascii | PpmFormat::Binary => 255,
+            PpmFormat::Binary16 => 65535,
+        }
+    }
+}
 
 // View at http://cs.rhodes.edu/welshc/COMP141_F16/ppmReader.html
 pub fn write_ppm(
-    image_width: u16,
-    image_height: u16,
-    image: Vec<Vec<Color>>,
+    framebuffer: &Framebuffer,
+    writer: impl std::io::Write,
+) -> Result<(), Box<dyn Error>> {
+    write_ppm_with_format(framebuffer, PpmFormat::Ascii, writer)
+}
+
+pub fn write_ppm_with_format(
+    framebuffer: &Framebuffer,
+    format: PpmFormat,
     mut writer: impl std::io::Write,
 ) -> Result<(), Box<dyn Error>> {
     // Write file header
-    writeln!(writer, "P3\n{} {}\n255", image_width, image_height)?;
+    writeln!(
+        writer,
+        "{}\n{} {}\n{}",
+        format.magic_number(),
+        framebuffer.width(),
+        framebuffer.height(),
+        format.max_value()
+    )?;
 
     // Write pixels
-    for row in image {
-        for pixel in row {
-            write_pixel(pixel, &mut writer)?;
+    for pixel in framebuffer.pixels() {
+        match format {
+            PpmFormat::Ascii => write_pixel(pixel.color, &mut writer)?,
+            PpmFormat::Binary => writer.write_all(&to_u8(pixel.color))?,
+            PpmFormat::Binary16 => {
+                for channel in to_u16(pixel.color).iter() {
+                    writer.write_all(&channel.to_be_bytes())?;
+                }
+            }
         }
     }
 
@@ -23,18 +73,134 @@ pub fn write_ppm(
     Ok(())
 }
 
-fn write_pixel(pixel: Color, writer: &mut impl std::io::Write) -> Result<(), Box<dyn Error>> {
-    // Convert pixel values to integers in 0 - 255 range
-    let resized_pixel = [
+/// Read a plain text (P3) or binary (P6) PPM with 8 or 16 bits per channel
+///
+/// Channels are scaled to 0 - 1 range and kept as they are stored, so they are usually gamma corrected.
+pub fn read_ppm(mut reader: impl std::io::Read) -> Result<Framebuffer, Box<dyn Error>> {
+    let mut data = Vec::new();
+    reader.read_to_end(&mut data)?;
+
+    // Read file header
+    let mut position = 0;
+    let magic_number = next_token(&data, &mut position)?;
+    if magic_number != "P3" && magic_number != "P6" {
+        return Err(format!(
+            "expected a PPM starting with P3 or P6, got '{}'",
+            magic_number
+        )
+        .into());
+    }
+    let width: usize = parse_token(&data, &mut position)?;
+    let height: usize = parse_token(&data, &mut position)?;
+    let max_value: u16 = parse_token(&data, &mut position)?;
+    if max_value == 0 {
+        return Err("the maximum value of a PPM can't be 0".into());
+    }
+
+    // Read pixels
+    let channel_count = value_count(width, height, 3)?;
+    let channels: Vec<u16> = if magic_number == "P3" {
+        // Every value takes at least a digit and the whitespace before it
+        if data.len().saturating_sub(position) / 2 < channel_count {
+            return Err("the PPM ends before all of its pixels".into());
+        }
+        (0..channel_count)
+            .map(|_| parse_token(&data, &mut position))
+            .collect::<Result<_, _>>()?
+    } else {
+        // A single whitespace character separates the header from the pixels
+        let pixels = data.get(position + 1..).unwrap_or(&[]);
+        let bytes_per_channel = if max_value > 255 { 2 } else { 1 };
+        if pixels.len() < value_count(width, height, 3 * bytes_per_channel)? {
+            return Err("the PPM ends before all of its pixels".into());
+        }
+        pixels
+            .chunks(bytes_per_channel)
+            .take(channel_count)
+            .map(|bytes| {
+                bytes
+                    .iter()
+                    .fold(0, |value, &byte| value << 8 | u16::from(byte))
+            })
+            .collect()
+    };
+    let pixels = channels
+        .chunks(3)
+        .map(|channel| {
+            let scale = 1. / f64::from(max_value);
+            Pixel::new(Color::new(
+                f64::from(channel[0]) * scale,
+                f64::from(channel[1]) * scale,
+                f64::from(channel[2]) * scale,
+            ))
+        })
+        .collect();
+
+    Framebuffer::try_from_pixels(width, height, pixels)
+}
+
+// Find the next whitespace separated word of a Netpbm style header, skipping comments
+pub(crate) fn next_token<'a>(
+    data: &'a [u8],
+    position: &mut usize,
+) -> Result<&'a str, Box<dyn Error>> {
+    loop {
+        match data.get(*position) {
+            Some(byte) if byte.is_ascii_whitespace() => *position += 1,
+            Some(b'#') => {
+                while data.get(*position).is_some_and(|&byte| byte != b'\n') {
+                    *position += 1;
+                }
+            }
+            Some(_) => break,
+            None => return Err("the image ends in the middle of its header".into()),
+        }
+    }
+    let start = *position;
+    while data
+        .get(*position)
+        .is_some_and(|byte| !byte.is_ascii_whitespace())
+    {
+        *position += 1;
+    }
+    Ok(std::str::from_utf8(&data[start..*position])?)
+}
+
+pub(crate) fn parse_token<T: FromStr>(
+    data: &[u8],
+    position: &mut usize,
+) -> Result<T, Box<dyn Error>> {
+    let token = next_token(data, position)?;
+    token
+        .parse()
+        .map_err(|_| format!("expected a number in the image header, got '{}'", token).into())
+}
+
+// Convert pixel values to integers in 0 - 255 range
+fn to_u8(pixel: Color) -> [u8; 3] {
+    [
         (255.999_f64 * pixel[0]).floor() as u8,
         (255.999_f64 * pixel[1]).floor() as u8,
         (255.999_f64 * pixel[2]).floor() as u8,
-    ];
+    ]
+}
+
+// Convert pixel values to integers in 0 - 65535 range
+fn to_u16(pixel: Color) -> [u16; 3] {
+    [
+        (65535.999_f64 * pixel[0]).floor() as u16,
+        (65535.999_f64 * pixel[1]).floor() as u16,
+        (65535.999_f64 * pixel[2]).floor() as u16,
+    ]
+}
+
+fn write_pixel(pixel: Color, writer: &mut impl std::io::Write) -> Result<(), Box<dyn Error>> {
+    let resized_pixel = to_u8(pixel);
 
     // Write pixel
-    write!(
+    writeln!(
         writer,
-        "{} {} {}\n",
+        "{} {} {}",
         resized_pixel[0], resized_pixel[1], resized_pixel[2]
     )?;
 
@@ -42,13 +208,156 @@ fn write_pixel(pixel: Color, writer: &mut impl std::io::Write) -> Result<(), Box
     Ok(())
 }
 
+// Split a binary PPM into its header fields and pixel bytes, for checking what was written
+#[cfg(test)]
+fn split_binary_ppm(data: &[u8]) -> (Vec<String>, &[u8]) {
+    let mut fields = Vec::new();
+    let mut start = 0;
+    while fields.len() < 4 {
+        let end = start
+            + data[start..]
+                .iter()
+                .position(|b| b.is_ascii_whitespace())
+                .unwrap();
+        fields.push(String::from_utf8(data[start..end].to_vec()).unwrap());
+        start = end + 1;
+    }
+    (fields, &data[start..])
+}
+
 #[test]
 fn test_write_ppm() {
     let mut result = Vec::new();
 
     let image = vec![vec![Color::new(0., 0.3, 0.7), Color::new(0.6, 0.2, 0.8)]];
 
-    write_ppm(2, 1, image, &mut result).unwrap();
+    write_ppm(&Framebuffer::from_colors(image), &mut result).unwrap();
 
     assert_eq!(result, b"P3\n2 1\n255\n0 76 179\n153 51 204\n");
 }
+
+#[test]
+fn test_write_binary_ppm() {
+    // Given an image
+    let mut result = Vec::new();
+    let image = vec![
+        vec![Color::new(0., 0.3, 0.7), Color::new(0.6, 0.2, 0.8)],
+        vec![Color::new(1., 1.5, -0.5), Color::new(0.5, 0.5, 0.5)],
+    ];
+
+    // When I write it as a binary PPM
+    let framebuffer = Framebuffer::from_colors(image.clone());
+    write_ppm_with_format(&framebuffer, PpmFormat::Binary, &mut result).unwrap();
+
+    // Then reading it back gives the same header and pixels as the plain text version
+    let (header, pixels) = split_binary_ppm(&result);
+    assert_eq!(header, vec!["P6", "2", "2", "255"]);
+    let expected: Vec<u8> = image.into_iter().flatten().flat_map(to_u8).collect();
+    assert_eq!(pixels, expected.as_slice());
+    assert_eq!(&pixels[..6], &[0, 76, 179, 153, 51, 204]);
+    assert_eq!(&pixels[6..9], &[255, 255, 0]);
+}
+
+#[test]
+fn test_write_16_bit_ppm() {
+    // Given an image
+    let mut result = Vec::new();
+    let image = vec![vec![Color::new(0., 0.3, 0.7), Color::new(1., 0.2, 0.8)]];
+
+    // When I write it as a 16 bit PPM
+    let framebuffer = Framebuffer::from_colors(image.clone());
+    write_ppm_with_format(&framebuffer, PpmFormat::Binary16, &mut result).unwrap();
+
+    // Then reading it back gives every channel within one step of 65535
+    let (header, pixels) = split_binary_ppm(&result);
+    assert_eq!(header, vec!["P6", "2", "1", "65535"]);
+    let channels: Vec<f64> = pixels
+        .chunks(2)
+        .map(|bytes| f64::from(u16::from_be_bytes([bytes[0], bytes[1]])) / 65535.)
+        .collect();
+    let expected: Vec<f64> = image
+        .into_iter()
+        .flatten()
+        .flat_map(|pixel| vec![pixel.x, pixel.y, pixel.z])
+        .collect();
+    assert_eq!(channels.len(), expected.len());
+    for (channel, expected) in channels.iter().zip(expected) {
+        assert!((channel - expected).abs() <= 1. / 65535.);
+    }
+}
+
+#[test]
+fn test_read_ppm() {
+    // Given a plain text PPM with comments
+    let data = b"P3\n# made by hand\n2 1 # two pixels\n255\n0 51 255\n\n255 0 102\n";
+
+    // When I read it
+    let framebuffer = read_ppm(&data[..]).unwrap();
+
+    // Then I get its pixels in 0 - 1 range
+    assert_eq!(
+        framebuffer.colors(),
+        vec![vec![Color::new(0., 0.2, 1.), Color::new(1., 0., 0.4)]]
+    );
+}
+
+#[test]
+fn test_read_ppm_round_trip() {
+    // Given an image
+    let image = vec![
+        vec![Color::new(0., 0.3, 0.7), Color::new(0.6, 0.2, 0.8)],
+        vec![Color::new(1., 0.9, 0.5), Color::new(0.5, 0.5, 0.5)],
+    ];
+    let framebuffer = Framebuffer::from_colors(image.clone());
+
+    for &(format, step) in [
+        (PpmFormat::Ascii, 1. / 255.),
+        (PpmFormat::Binary, 1. / 255.),
+        (PpmFormat::Binary16, 1. / 65535.),
+    ]
+    .iter()
+    {
+        // When I write it in every format and read it back
+        let mut data = Vec::new();
+        write_ppm_with_format(&framebuffer, format, &mut data).unwrap();
+        let read = read_ppm(data.as_slice()).unwrap();
+
+        // Then every channel is within one step of what was written
+        assert_eq!((read.width(), read.height()), (2, 2));
+        for (pixel, expected) in read.pixels().iter().zip(image.iter().flatten()) {
+            for i in 0..3 {
+                assert!((pixel.color[i] - expected[i]).abs() <= step);
+            }
+        }
+    }
+}
+
+#[test]
+fn test_read_truncated_ppm() {
+    // Given a binary PPM that misses its last pixel
+    let data = b"P6\n2 1\n255\n\x00\x01\x02";
+
+    // When I read it
+    let result = read_ppm(&data[..]);
+
+    // Then I get an error
+    assert!(result.is_err());
+}
+
+#[test]
+fn test_read_ppm_with_corrupt_size() {
+    // Given PPMs with sizes that are too big to count, or much bigger than their pixels
+    let sizes: [&[u8]; 3] = [
+        b"P6\n18446744073709551615 2\n255\n\x00\x01\x02",
+        b"P6\n6148914691236517206 1\n255\n\x00\x01\x02",
+        b"P3\n100000 100000\n255\n0 1 2",
+    ];
+
+    for data in sizes.iter() {
+        // When I read them
+        let result = read_ppm(*data);
+
+        // Then I get an error instead of a panic
+        assert!(result.is_err());
+    }
+}
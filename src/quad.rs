@@ -0,0 +1,136 @@
+use std::error::Error;
+use std::sync::Arc;
+
+use crate::{
+    aabb::Aabb,
+    hittable::{HitRecord, Hittable},
+    material::Material,
+    plane::intersect_plane,
+    point3::Point3,
+    ray::Ray,
+    scene_file::{ExportedMaterials, ObjectDescription},
+    triangle::BOUNDING_BOX_PADDING,
+    vec3::Vec3,
+};
+
+/// A parallelogram with a corner and the two edges from it, like a wall, a panel light or the side of a
+/// box. Edges along two axes make an axis-aligned rectangle.
+///
+/// It faces the side from which the second edge is counterclockwise from the first. The texture
+/// coordinates of a hit are how far it is along each edge, from 0 to 1.
+#[derive(Clone)]
+pub struct Quad {
+    corner: Point3,
+    edges: [Vec3; 2],
+    material: Arc<dyn Material>,
+    normal: Vec3,
+    // Turns a point in the plane into how far it is along the edges, see `hit`
+    w: Vec3,
+}
+
+impl Quad {
+    pub fn new(
+        corner: Point3,
+        first_edge: Vec3,
+        second_edge: Vec3,
+        material: Arc<dyn Material>,
+    ) -> Self {
+        let n = first_edge.cross(second_edge);
+        Self {
+            corner,
+            edges: [first_edge, second_edge],
+            material,
+            normal: n.unit_vector(),
+            w: n / n.len_squared(),
+        }
+    }
+}
+
+impl Hittable for Quad {
+    // A ray hits the plane of the quad at corner + alpha * first edge + beta * second edge, and the
+    // quad where alpha and beta are both between 0 and 1. Crossing both sides of that equation with an
+    // edge and dotting with the normal isolates the other factor.
+    fn hit(&self, ray: Ray, t_min: f64, t_max: f64, record: &mut HitRecord) -> bool {
+        let t = match intersect_plane(self.corner, self.normal, ray, t_min, t_max) {
+            Some(t) => t,
+            None => return false,
+        };
+        let point = ray.at(t);
+        let offset = point - self.corner;
+        let alpha = self.w.dot(offset.cross(self.edges[1]));
+        let beta = self.w.dot(self.edges[0].cross(offset));
+        if !(0. ..=1.).contains(&alpha) || !(0. ..=1.).contains(&beta) {
+            return false;
+        }
+
+        record.t = t;
+        record.point = point;
+        record.u = alpha;
+        record.v = beta;
+        record.set_face_normal(ray, self.normal);
+        record.material = Arc::clone(&self.material);
+
+        true
+    }
+
+    fn bounding_box(&self, output_box: &mut Aabb) -> bool {
+        let [first, second] = self.edges;
+        let corners = [
+            self.corner + first,
+            self.corner + second,
+            self.corner + first + second,
+        ];
+        *output_box = corners
+            .iter()
+            .fold(Aabb::new(self.corner, self.corner), |aabb, &corner| {
+                aabb.surrounding(Aabb::new(corner, corner))
+            })
+            .padded(BOUNDING_BOX_PADDING);
+        true
+    }
+
+    fn export(
+        &self,
+        materials: &mut ExportedMaterials,
+    ) -> Result<ObjectDescription, Box<dyn Error>> {
+        Ok(ObjectDescription::Quad {
+            corner: self.corner,
+            edges: self.edges,
+            material: materials.name(&self.material)?,
+        })
+    }
+}
+
+#[test]
+fn test_quad_hits_and_misses() {
+    // Given a slanted quad from the origin, 2 along x and 1 up and back
+    let quad = Quad::new(
+        Point3::new(0., 0., 0.),
+        Vec3::new(2., 0., 0.),
+        Vec3::new(0., 1., -1.),
+        Arc::new(crate::lambertian::Lambertian::new(Vec3::new(0.5, 0.5, 0.5))),
+    );
+
+    // When rays come down at it inside and outside its edges
+    let hit = |x: f64, z: f64| {
+        let mut record = HitRecord::new();
+        let ray = Ray::new(Point3::new(x, 5., z), Vec3::new(0., -1., 0.));
+        let hit = quad.hit(ray, 0.001, f64::INFINITY, &mut record);
+        (hit, record)
+    };
+    let (inside, record) = hit(1.5, -0.25);
+
+    // Then the one inside hits it at how far it is along the edges, facing up
+    assert!(inside);
+    assert!((record.u - 0.75).abs() < 1e-12 && (record.v - 0.25).abs() < 1e-12);
+    assert!((record.point - Point3::new(1.5, 0.25, -0.25)).len() < 1e-12);
+    assert!(record.is_front_face);
+    assert!(!hit(2.5, -0.25).0);
+    assert!(!hit(1., 0.5).0);
+
+    // And its bounding box covers it
+    let mut output_box = Aabb::new(Point3::new(0., 0., 0.), Point3::new(0., 0., 0.));
+    assert!(quad.bounding_box(&mut output_box));
+    assert_eq!(output_box.minimum, Point3::new(0., 0., -1.));
+    assert_eq!(output_box.maximum, Point3::new(2., 1., 0.));
+}
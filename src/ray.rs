@@ -5,13 +5,20 @@ use crate::vec3::Vec3;
 pub struct Ray {
     pub origin: Point3,
     pub direction: Vec3,
+    /// When the ray was sent, for objects that move while the shutter is open
+    pub time: f64,
 }
 
 impl Ray {
     pub fn new(origin: Point3, direction: Vec3) -> Self {
+        Self::at_time(origin, direction, 0.)
+    }
+
+    pub fn at_time(origin: Point3, direction: Vec3, time: f64) -> Self {
         Ray {
-            origin: origin,
-            direction: direction,
+            origin,
+            direction,
+            time,
         }
     }
 
@@ -19,7 +26,6 @@ impl Ray {
         Self::new(Point3::new(0., 0., 0.), Vec3::new(0., 0., 0.))
     }
 
-    #[allow(dead_code)]
     pub fn at(self, t: f64) -> Point3 {
         self.origin + t * self.direction
     }
@@ -0,0 +1,285 @@
+// Sys
+use std::convert::TryFrom;
+use std::error::Error;
+
+// 3rd party
+use rand::{Rng, RngCore};
+use rayon::ThreadPoolBuilder;
+use serde::{Deserialize, Serialize};
+
+// Project
+use crate::{
+    camera::Camera,
+    color::Color,
+    framebuffer::{Framebuffer, Pixel},
+    hittable::{HitRecord, Hittable},
+    ray::Ray,
+    rng::pixel_rng,
+    tile_scheduler::{TileOrder, TileScheduler},
+    vec3::Vec3,
+};
+
+/// Everything that determines how an image is rendered, apart from the scene and the camera
+#[derive(Debug, Clone, Copy)]
+pub struct RenderSettings {
+    pub image_width: u16,
+    pub image_height: u16,
+    pub samples_per_pixel: u32,
+    pub max_depth: i64,
+    /// Number of render threads, 0 uses one thread per core
+    pub threads: usize,
+    /// Width and height in pixels of the tiles the image is split into
+    pub tile_size: usize,
+    pub tile_order: TileOrder,
+    /// Seed for all random sampling, the same seed always gives the same image
+    pub seed: u64,
+    /// What rays that don't hit anything see
+    pub background: Background,
+}
+
+impl RenderSettings {
+    pub fn new(
+        image_width: u16,
+        image_height: u16,
+        samples_per_pixel: u32,
+        max_depth: i64,
+    ) -> Self {
+        Self {
+            image_width,
+            image_height,
+            samples_per_pixel,
+            max_depth,
+            threads: 0,
+            tile_size: 32,
+            tile_order: TileOrder::Scanline,
+            seed: 0,
+            background: Background::Sky,
+        }
+    }
+}
+
+/// The light that comes from everywhere the world doesn't cover
+///
+/// Stored as `"sky"`, `"rainbow"` or an `[r, g, b]` color in scene files.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(try_from = "BackgroundValue", into = "BackgroundValue")]
+pub enum Background {
+    /// A gradient from white at the horizon to blue overhead, which lights a scene on its own
+    Sky,
+    /// Colors that depend on the direction, to see which way rays go
+    Rainbow,
+    /// The same color in every direction, black for scenes that are only lit by their own light sources
+    Solid(Color),
+}
+
+impl Background {
+    pub fn color(self, ray: Ray) -> Color {
+        match self {
+            Background::Sky => ray_background_color(ray),
+            Background::Rainbow => ray_background_color_rainbow(ray),
+            Background::Solid(color) => color,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(untagged)]
+enum BackgroundValue {
+    Name(String),
+    Color(Color),
+}
+
+impl TryFrom<BackgroundValue> for Background {
+    type Error = String;
+
+    fn try_from(value: BackgroundValue) -> Result<Self, Self::Error> {
+        match value {
+            BackgroundValue::Name(name) if name == "sky" => Ok(Background::Sky),
+            BackgroundValue::Name(name) if name == "rainbow" => Ok(Background::Rainbow),
+            BackgroundValue::Name(name) => Err(format!(
+                "expected sky, rainbow or a color like [0, 0, 0], got '{}'",
+                name
+            )),
+            BackgroundValue::Color(color) => Ok(Background::Solid(color)),
+        }
+    }
+}
+
+impl From<Background> for BackgroundValue {
+    fn from(background: Background) -> Self {
+        match background {
+            Background::Sky => BackgroundValue::Name("sky".to_string()),
+            Background::Rainbow => BackgroundValue::Name("rainbow".to_string()),
+            Background::Solid(color) => BackgroundValue::Color(color),
+        }
+    }
+}
+
+pub fn ray_background_color_rainbow(ray: Ray) -> Color {
+    let unit_direction = ray.direction.unit_vector();
+
+    // Generate color by scaling x and y of the unit direction from domain -1, 1 to range 0, 1.
+    Color::new(
+        unit_direction.y / 2. + 0.5,
+        0.5 - unit_direction.x / 2.,
+        0.25,
+    )
+}
+
+pub fn ray_background_color(ray: Ray) -> Color {
+    let unit_direction = ray.direction.unit_vector();
+
+    // Scale the y factor of the unit direction from domain -1, 1 to range 0, 1
+    let t = 0.5 * (unit_direction.y + 1.);
+
+    // Scale from domain t: 0, 1 to color: white, blue
+    (1. - t) * Color::new(1., 1., 1.) + t * Color::new(0.5, 0.7, 1.)
+}
+
+// Return the color seen along the ray, following scattered rays until they hit the background or `depth` runs out
+pub fn ray_color(
+    ray: Ray,
+    world: &dyn Hittable,
+    background: Background,
+    depth: i64,
+    rng: &mut dyn RngCore,
+) -> Color {
+    trace(ray, world, background, depth, rng).0
+}
+
+// What a ray from the camera hits first, for the passes besides color
+struct FirstHit {
+    distance: f64,
+    normal: Vec3,
+    albedo: Color,
+    object_id: u32,
+}
+
+// Return the color seen along the ray, and what the ray itself hit if it hit anything
+fn trace(
+    ray: Ray,
+    world: &dyn Hittable,
+    background: Background,
+    depth: i64,
+    rng: &mut dyn RngCore,
+) -> (Color, Option<FirstHit>) {
+    let mut record = HitRecord::new();
+
+    if depth <= 0 {
+        return (Color::new(0., 0., 0.), None);
+    };
+
+    // We set t_min to slightly above 0, so we don't get values below 0 from floating point rounding errors (this fixes shadow acne)
+    if world.hit(ray, 0.001, f64::INFINITY, &mut record) {
+        let mut scattered = Ray::nowhere();
+        let mut attenuation = Color::new(0., 0., 0.);
+        let emitted = record.material.emitted(&record);
+        let color = if record
+            .material
+            .scatter(ray, &record, &mut attenuation, &mut scattered, rng)
+        {
+            emitted + attenuation * ray_color(scattered, world, background, depth - 1, rng)
+        } else {
+            emitted
+        };
+        let first_hit = FirstHit {
+            distance: record.t * ray.direction.len(),
+            normal: record.normal,
+            albedo: attenuation,
+            object_id: record.object_id,
+        };
+        return (color, Some(first_hit));
+    };
+
+    (background.color(ray), None)
+}
+
+// Average a number of randomly sampled rays through the pixel at column x and row y (counted from the bottom)
+fn render_pixel(
+    world: &dyn Hittable,
+    camera: &Camera,
+    settings: &RenderSettings,
+    x: usize,
+    y: usize,
+) -> Pixel {
+    let image_width = f64::from(settings.image_width);
+    let image_height = f64::from(settings.image_height);
+
+    let mut rng = pixel_rng(settings.seed, x, y);
+    let mut pixel = Pixel::default();
+    let mut hits = 0;
+    let mut depth = 0.;
+    for _ in 0..settings.samples_per_pixel {
+        let u = (x as f64 + rng.gen::<f64>()) / (image_width - 1.);
+        let v = (y as f64 + rng.gen::<f64>()) / (image_height - 1.);
+        let ray = camera.get_ray(u, v, &mut rng);
+        let (color, first_hit) = trace(
+            ray,
+            world,
+            settings.background,
+            settings.max_depth,
+            &mut rng,
+        );
+
+        pixel.color += color;
+        if let Some(first_hit) = first_hit {
+            hits += 1;
+            depth += first_hit.distance;
+            pixel.normal += first_hit.normal;
+            pixel.albedo += first_hit.albedo;
+            if pixel.object_id == 0 {
+                pixel.object_id = first_hit.object_id;
+            }
+        }
+    }
+
+    pixel.color /= f64::from(settings.samples_per_pixel);
+    pixel.alpha = f64::from(hits) / f64::from(settings.samples_per_pixel);
+    if hits > 0 {
+        pixel.depth = depth / f64::from(hits);
+        pixel.albedo /= f64::from(hits);
+        if !pixel.normal.is_near_zero() {
+            pixel.normal = pixel.normal.unit_vector();
+        }
+    }
+    pixel
+}
+
+/// Render the world as seen from the camera into a framebuffer holding linear radiance and the other passes
+///
+/// Use `Framebuffer::gamma_corrected` before writing the result to an 8 or 16 bit image format.
+///
+/// The image is split into tiles that are spread over a pool of `settings.threads` threads (all cores
+/// when 0). `on_tile` is called from the render threads with the number of tiles that are left whenever
+/// a tile is done.
+pub fn render(
+    world: &dyn Hittable,
+    camera: &Camera,
+    settings: &RenderSettings,
+    on_tile: impl Fn(usize) + Sync,
+) -> Result<Framebuffer, Box<dyn Error>> {
+    let image_height = usize::from(settings.image_height);
+    let pool = ThreadPoolBuilder::new()
+        .num_threads(settings.threads)
+        .build()?;
+    let scheduler = TileScheduler::new(
+        settings.image_width.into(),
+        settings.image_height.into(),
+        settings.tile_size,
+        settings.tile_order,
+    );
+
+    let framebuffer = scheduler.run(
+        &pool,
+        |tile, pixels| {
+            for (i, pixel) in pixels.iter_mut().enumerate() {
+                let x = tile.x + i % tile.width;
+                let y = image_height - 1 - (tile.y + i / tile.width);
+                *pixel = render_pixel(world, camera, settings, x, y);
+            }
+        },
+        on_tile,
+    );
+
+    Ok(framebuffer)
+}
@@ -0,0 +1,59 @@
+// 3rd party
+use rand::SeedableRng;
+use rand_pcg::Pcg64Mcg;
+
+/// The random number generator used for everything that is sampled, so a seed always gives the same image
+pub type SeededRng = Pcg64Mcg;
+
+pub fn rng_from_seed(seed: u64) -> SeededRng {
+    SeededRng::seed_from_u64(splitmix64(seed))
+}
+
+/// An independent stream of random numbers for one pixel
+///
+/// Every pixel gets its own stream derived from the seed and its position, so an image is the same no
+/// matter how many threads render it or in which order the pixels are visited.
+pub fn pixel_rng(seed: u64, x: usize, y: usize) -> SeededRng {
+    let position = ((y as u64) << 32) | (x as u64 & 0xffff_ffff);
+    rng_from_seed(splitmix64(seed) ^ position)
+}
+
+// Scramble the bits of a number, so seeds that are close together give unrelated streams
+// See https://prng.di.unimi.it/splitmix64.c
+fn splitmix64(value: u64) -> u64 {
+    let mut z = value.wrapping_add(0x9e37_79b9_7f4a_7c15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
+
+#[test]
+fn test_pixel_rng_is_deterministic() {
+    use rand::Rng;
+
+    // Given two streams for the same seed and pixel
+    let mut first = pixel_rng(42, 3, 7);
+    let mut second = pixel_rng(42, 3, 7);
+
+    // When I draw numbers from them
+    // Then they are the same
+    for _ in 0..10 {
+        assert_eq!(first.gen::<u64>(), second.gen::<u64>());
+    }
+}
+
+#[test]
+fn test_pixel_rng_differs_per_pixel_and_seed() {
+    use rand::Rng;
+
+    // Given streams for neighbouring pixels and different seeds
+    let first = pixel_rng(42, 3, 7).gen::<u64>();
+    let neighbour = pixel_rng(42, 4, 7).gen::<u64>();
+    let transposed = pixel_rng(42, 7, 3).gen::<u64>();
+    let other_seed = pixel_rng(43, 3, 7).gen::<u64>();
+
+    // Then they all start differently
+    assert_ne!(first, neighbour);
+    assert_ne!(first, transposed);
+    assert_ne!(first, other_seed);
+}
@@ -0,0 +1,1477 @@
+use std::collections::{BTreeMap, HashMap};
+use std::convert::TryFrom;
+use std::error::Error;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    camera::Camera,
+    checker::{Checker, CheckerMapping},
+    color::Color,
+    cone::Cone,
+    csg::{Csg, CsgOperation},
+    cuboid::Cuboid,
+    cylinder::Cylinder,
+    dielectric::Dielectric,
+    diffuse_light::DiffuseLight,
+    disk::Disk,
+    hittable::Hittable,
+    hittable_list::HittableList,
+    image_texture::{ImageTexture, WrapMode},
+    lambertian::Lambertian,
+    mat4::Mat4,
+    material::Material,
+    metal::Metal,
+    moving_sphere::MovingSphere,
+    noise_texture::{NoisePattern, NoiseTexture},
+    obj::load_obj,
+    paraboloid::Paraboloid,
+    plane::Plane,
+    ply::load_ply,
+    point3::Point3,
+    quad::Quad,
+    render::{Background, RenderSettings},
+    solid_color::SolidColor,
+    sphere::Sphere,
+    stl::load_stl,
+    texture::Texture,
+    torus::Torus,
+    transform::Transform,
+    transformed::Transformed,
+    triangle::Triangle,
+    triangle_mesh::{MeshData, TriangleMesh},
+    vec3::Vec3,
+};
+
+/// A scene as it is written in a JSON scene file: the camera, how to render it, and the materials and
+/// objects in it
+///
+/// Objects refer to materials by name, so every object with the same material name shares one material.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SceneDescription {
+    pub camera: CameraDescription,
+    #[serde(default)]
+    pub render: RenderDescription,
+    #[serde(default)]
+    pub materials: BTreeMap<String, MaterialDescription>,
+    #[serde(default)]
+    pub objects: Vec<ObjectDescription>,
+    /// Objects that give off light, which need a material that does
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub lights: Vec<ObjectDescription>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct CameraDescription {
+    pub look_from: Point3,
+    pub look_at: Point3,
+    #[serde(default = "default_vup")]
+    pub vup: Vec3,
+    #[serde(default = "default_vertical_field_of_view")]
+    pub vertical_field_of_view: f64,
+    #[serde(default)]
+    pub aperture: f64,
+    /// The distance between `look_from` and `look_at` when left out
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub focus_distance: Option<f64>,
+    /// When the shutter opens and closes, for motion blur
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub shutter_open: f64,
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub shutter_close: f64,
+}
+
+/// Render settings of a scene, where the ones that are left out are up to whoever renders it
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RenderDescription {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub width: Option<u16>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub height: Option<u16>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub samples_per_pixel: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_depth: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub background: Option<Background>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub seed: Option<u64>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case", deny_unknown_fields)]
+pub enum MaterialDescription {
+    Lambertian {
+        albedo: TextureDescription,
+    },
+    Metal {
+        albedo: TextureDescription,
+        #[serde(default)]
+        fuzz: f64,
+    },
+    Dielectric {
+        refraction_index: f64,
+    },
+    DiffuseLight {
+        emit: Color,
+    },
+}
+
+/// A step of a transform, like `{ "rotate": { "axis": [0, 1, 0], "degrees": 90 } }`, applied in the
+/// order they are listed
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case", deny_unknown_fields)]
+pub enum TransformStep {
+    Translate(Vec3),
+    /// Counterclockwise around the axis, seen from where it points to
+    Rotate {
+        axis: Vec3,
+        degrees: f64,
+    },
+    Scale(Vec3),
+    /// An affine matrix, given as its rows
+    Matrix(Mat4),
+}
+
+/// A texture: either a color like `[0.5, 0.5, 0.5]`, or a pattern
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged, try_from = "serde_json::Value")]
+pub enum TextureDescription {
+    Solid(Color),
+    Pattern(PatternDescription),
+}
+
+/// A texture that isn't a plain color. Image files are relative to the scene file.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case", deny_unknown_fields)]
+pub enum PatternDescription {
+    /// A `Checker` of cubes of `size`
+    Checker {
+        even: Box<TextureDescription>,
+        odd: Box<TextureDescription>,
+        size: f64,
+    },
+    /// A `Checker` of `columns` by `rows` squares over the surface coordinates
+    UvChecker {
+        even: Box<TextureDescription>,
+        odd: Box<TextureDescription>,
+        columns: f64,
+        rows: f64,
+    },
+    Image {
+        file: PathBuf,
+        #[serde(default)]
+        wrap: WrapMode,
+    },
+    /// A `NoiseTexture`, from black to white unless it has colors
+    Noise {
+        pattern: NoisePattern,
+        #[serde(default = "default_noise_scale")]
+        scale: f64,
+        #[serde(default)]
+        seed: u64,
+        #[serde(default = "default_noise_low")]
+        low: Color,
+        #[serde(default = "default_noise_high")]
+        high: Color,
+    },
+}
+
+/// An object, with the name of its material. Model files are relative to the scene file.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case", deny_unknown_fields)]
+pub enum ObjectDescription {
+    Sphere {
+        center: Point3,
+        radius: f64,
+        material: String,
+    },
+    /// A `MovingSphere`, blurred by a camera with its shutter open between `start_time` and `end_time`
+    MovingSphere {
+        start_center: Point3,
+        end_center: Point3,
+        start_time: f64,
+        end_time: f64,
+        radius: f64,
+        material: String,
+    },
+    Triangle {
+        vertices: [Point3; 3],
+        material: String,
+    },
+    /// An infinite `Plane`, like a floor
+    Plane {
+        point: Point3,
+        normal: Vec3,
+        material: String,
+    },
+    /// A parallelogram `Quad` with a corner and the two edges from it
+    Quad {
+        corner: Point3,
+        edges: [Vec3; 2],
+        material: String,
+    },
+    Disk {
+        center: Point3,
+        normal: Vec3,
+        radius: f64,
+        material: String,
+    },
+    /// An upright `Cylinder` standing on the center of its bottom cap
+    Cylinder {
+        base: Point3,
+        radius: f64,
+        height: f64,
+        material: String,
+    },
+    /// An upright `Cone` standing on the center of its bottom cap
+    Cone {
+        base: Point3,
+        radius: f64,
+        height: f64,
+        material: String,
+    },
+    /// A `Paraboloid` opening upwards from its vertex
+    Paraboloid {
+        vertex: Point3,
+        radius: f64,
+        height: f64,
+        material: String,
+    },
+    /// A `Torus` around the y axis through its center
+    Torus {
+        center: Point3,
+        major_radius: f64,
+        minor_radius: f64,
+        material: String,
+    },
+    /// An axis-aligned `Cuboid` between two opposite corners
+    #[serde(rename = "box")]
+    Cuboid {
+        minimum: Point3,
+        maximum: Point3,
+        material: String,
+    },
+    /// A `TriangleMesh` written out in the scene file, see `MeshData`
+    Mesh {
+        positions: Vec<Point3>,
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        normals: Vec<Vec3>,
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        uvs: Vec<(f64, f64)>,
+        triangles: Vec<[usize; 3]>,
+        material: String,
+    },
+    /// Objects that are kept together, like the meshes of a model, as a `HittableList` of their own
+    Group {
+        objects: Vec<ObjectDescription>,
+    },
+    /// An object moved, turned or resized by the steps of `transform`, see `Transformed`
+    Transformed {
+        transform: Vec<TransformStep>,
+        object: Box<ObjectDescription>,
+    },
+    /// Two solids combined by a `Csg`, like a hollow ball carved out of a bigger one
+    Csg {
+        operation: CsgOperation,
+        left: Box<ObjectDescription>,
+        right: Box<ObjectDescription>,
+    },
+    /// A Wavefront OBJ file, with the materials of its MTL files
+    Obj {
+        file: PathBuf,
+    },
+    Ply {
+        file: PathBuf,
+        material: String,
+    },
+    Stl {
+        file: PathBuf,
+        material: String,
+    },
+}
+
+fn default_vup() -> Vec3 {
+    Vec3::new(0., 1., 0.)
+}
+
+fn default_vertical_field_of_view() -> f64 {
+    20.
+}
+
+fn is_zero(value: &f64) -> bool {
+    *value == 0.
+}
+
+fn default_noise_scale() -> f64 {
+    1.
+}
+
+fn default_noise_low() -> Color {
+    Color::new(0., 0., 0.)
+}
+
+fn default_noise_high() -> Color {
+    Color::new(1., 1., 1.)
+}
+
+/// Read a JSON scene file, see `parse_scene`
+pub fn load_scene(path: impl AsRef<Path>) -> Result<SceneDescription, Box<dyn Error>> {
+    let path = path.as_ref();
+    let text = std::fs::read_to_string(path)
+        .map_err(|error| format!("can't read '{}': {}", path.display(), error))?;
+    parse_scene(&text).map_err(|error| format!("{}: {}", path.display(), error).into())
+}
+
+/// Parse a JSON scene, checking that its values make sense and that every material it uses exists
+///
+/// Errors say where in the scene the problem is, like `objects[2].radius: ...`.
+pub fn parse_scene(text: &str) -> Result<SceneDescription, Box<dyn Error>> {
+    let mut deserializer = serde_json::Deserializer::from_str(text);
+    let scene: SceneDescription = serde_path_to_error::deserialize(&mut deserializer)?;
+    deserializer.end()?;
+    scene.check()?;
+    Ok(scene)
+}
+
+impl SceneDescription {
+    fn check(&self) -> Result<(), String> {
+        self.camera
+            .check()
+            .map_err(|error| format!("camera.{}", error))?;
+        self.render
+            .check()
+            .map_err(|error| format!("render.{}", error))?;
+        for (name, material) in self.materials.iter() {
+            material
+                .check()
+                .map_err(|error| format!("materials.{}.{}", name, error))?;
+        }
+        for (list, objects) in [("objects", &self.objects), ("lights", &self.lights)].iter() {
+            for (i, object) in objects.iter().enumerate() {
+                self.check_object(object, *list == "lights")
+                    .map_err(|error| format!("{}[{}].{}", list, i, error))?;
+            }
+        }
+        Ok(())
+    }
+
+    fn check_object(&self, object: &ObjectDescription, is_light: bool) -> Result<(), String> {
+        match object {
+            ObjectDescription::Sphere { radius, .. }
+            | ObjectDescription::MovingSphere { radius, .. }
+                if *radius == 0. || !radius.is_finite() =>
+            {
+                return Err(format!(
+                    "radius: expected a number other than 0, got {}",
+                    radius
+                ));
+            }
+            ObjectDescription::MovingSphere {
+                start_time,
+                end_time,
+                ..
+            } if end_time < start_time => {
+                return Err(format!(
+                    "end_time: expected a time from start_time {} on, got {}",
+                    start_time, end_time
+                ));
+            }
+            ObjectDescription::Plane { normal, .. } | ObjectDescription::Disk { normal, .. }
+                if normal.is_near_zero() =>
+            {
+                return Err(format!(
+                    "normal: expected a direction other than 0, got {}",
+                    normal
+                ));
+            }
+            ObjectDescription::Disk { radius, .. }
+            | ObjectDescription::Cylinder { radius, .. }
+            | ObjectDescription::Cone { radius, .. }
+            | ObjectDescription::Paraboloid { radius, .. }
+                if *radius <= 0. || !radius.is_finite() =>
+            {
+                return Err(format!(
+                    "radius: expected a number greater than 0, got {}",
+                    radius
+                ));
+            }
+            ObjectDescription::Cylinder { height, .. }
+            | ObjectDescription::Cone { height, .. }
+            | ObjectDescription::Paraboloid { height, .. }
+                if *height <= 0. || !height.is_finite() =>
+            {
+                return Err(format!(
+                    "height: expected a number greater than 0, got {}",
+                    height
+                ));
+            }
+            ObjectDescription::Torus {
+                major_radius,
+                minor_radius,
+                ..
+            } => {
+                for (name, radius) in [
+                    ("major_radius", major_radius),
+                    ("minor_radius", minor_radius),
+                ]
+                .iter()
+                {
+                    if **radius <= 0. || !radius.is_finite() {
+                        return Err(format!(
+                            "{}: expected a number greater than 0, got {}",
+                            name, radius
+                        ));
+                    }
+                }
+            }
+            ObjectDescription::Quad { edges, .. } if edges[0].cross(edges[1]).is_near_zero() => {
+                return Err(format!(
+                    "edges: expected edges that aren't parallel, got {} and {}",
+                    edges[0], edges[1]
+                ));
+            }
+            ObjectDescription::Mesh {
+                positions,
+                triangles,
+                ..
+            } => {
+                if let Some(index) = triangles.iter().flatten().find(|&&i| i >= positions.len()) {
+                    return Err(format!(
+                        "triangles: vertex {} is out of range, there are {} positions",
+                        index,
+                        positions.len()
+                    ));
+                }
+            }
+            ObjectDescription::Group { objects } => {
+                for (i, object) in objects.iter().enumerate() {
+                    self.check_object(object, is_light)
+                        .map_err(|error| format!("objects[{}].{}", i, error))?;
+                }
+            }
+            ObjectDescription::Transformed { transform, object } => {
+                for (i, step) in transform.iter().enumerate() {
+                    step.build()
+                        .map_err(|error| format!("transform[{}]: {}", i, error))?;
+                }
+                self.check_object(object, is_light)
+                    .map_err(|error| format!("object.{}", error))?;
+            }
+            ObjectDescription::Csg { left, right, .. } => {
+                self.check_object(left, is_light)
+                    .map_err(|error| format!("left.{}", error))?;
+                self.check_object(right, is_light)
+                    .map_err(|error| format!("right.{}", error))?;
+            }
+            _ => {}
+        }
+
+        let name = match object.material() {
+            Some(name) => name,
+            None => return Ok(()),
+        };
+        let material = self
+            .materials
+            .get(name)
+            .ok_or_else(|| format!("material: there is no material named '{}'", name))?;
+        if is_light && !material.emits_light() {
+            return Err(format!("material: '{}' doesn't give off light", name));
+        }
+        Ok(())
+    }
+
+    /// The camera for an image with the given width / height ratio
+    pub fn camera(&self, aspect_ratio: f64) -> Camera {
+        self.camera.build(aspect_ratio)
+    }
+
+    /// Make the materials, objects and lights, loading files relative to `directory`
+    pub fn build_world(&self, directory: &Path) -> Result<HittableList, Box<dyn Error>> {
+        let materials = self
+            .materials
+            .iter()
+            .map(|(name, material)| {
+                let built = material
+                    .build(directory)
+                    .map_err(|error| format!("materials.{}: {}", name, error))?;
+                Ok((name.as_str(), built))
+            })
+            .collect::<Result<HashMap<&str, Arc<dyn Material>>, String>>()?;
+
+        let mut world = HittableList::new();
+        for (list, objects) in [("objects", &self.objects), ("lights", &self.lights)].iter() {
+            for (i, object) in objects.iter().enumerate() {
+                let object = object
+                    .build(&materials, directory)
+                    .map_err(|error| format!("{}[{}]: {}", list, i, error))?;
+                world.add(object);
+            }
+        }
+        Ok(world)
+    }
+
+    /// Describe a world and its materials, so that building it again gives the same objects in the same
+    /// order. Objects that are shared between others, like materials, are written once.
+    pub fn from_world(
+        world: &dyn Hittable,
+        camera: CameraDescription,
+        render: RenderDescription,
+    ) -> Result<Self, Box<dyn Error>> {
+        let mut materials = ExportedMaterials::default();
+        let objects = match world.export(&mut materials)? {
+            ObjectDescription::Group { objects } => objects,
+            object => vec![object],
+        };
+        Ok(Self {
+            camera,
+            render,
+            materials: materials.materials,
+            objects,
+            lights: Vec::new(),
+        })
+    }
+
+    /// The scene as JSON, with numbers written so that they read back exactly the same
+    pub fn to_json(&self) -> Result<String, Box<dyn Error>> {
+        Ok(inline_number_arrays(&serde_json::to_string_pretty(self)?))
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), Box<dyn Error>> {
+        let path = path.as_ref();
+        std::fs::write(path, self.to_json()? + "\n")
+            .map_err(|error| format!("can't write '{}': {}", path.display(), error).into())
+    }
+}
+
+// Put arrays of numbers, like vectors, on one line instead of a line per number. Strings never contain
+// the line breaks of pretty printed arrays, so they are left alone.
+fn inline_number_arrays(json: &str) -> String {
+    let mut result = String::with_capacity(json.len());
+    let mut rest = json;
+    while let Some(start) = rest.find("[\n") {
+        result.push_str(&rest[..start]);
+        let after = &rest[start + 1..];
+        let end = after.find(']').unwrap_or(0);
+        let contents = &after[..end];
+        let is_numbers = end > 0
+            && contents
+                .chars()
+                .all(|c| c.is_ascii_digit() || c.is_whitespace() || ".,-+eE".contains(c));
+        if is_numbers {
+            let numbers: Vec<&str> = contents.split(',').map(str::trim).collect();
+            result.push('[');
+            result.push_str(&numbers.join(", "));
+            result.push(']');
+            rest = &after[end + 1..];
+        } else {
+            result.push('[');
+            rest = after;
+        }
+    }
+    result.push_str(rest);
+    result
+}
+
+/// The materials of a world that is being written to a scene file, see `Hittable::export`
+#[derive(Default)]
+pub struct ExportedMaterials {
+    materials: BTreeMap<String, MaterialDescription>,
+    // Names by the address of the material, so that a shared material gets one name
+    names: HashMap<usize, String>,
+}
+
+impl ExportedMaterials {
+    /// The name of a material, describing it the first time it comes up
+    pub fn name(&mut self, material: &Arc<dyn Material>) -> Result<String, Box<dyn Error>> {
+        let address = Arc::as_ptr(material) as *const () as usize;
+        if let Some(name) = self.names.get(&address) {
+            return Ok(name.clone());
+        }
+        let description = material
+            .describe()
+            .ok_or("the world has a material that can't be written to a scene file")?;
+        let name = format!("{}_{}", description.type_name(), self.names.len() + 1);
+        self.names.insert(address, name.clone());
+        self.materials.insert(name.clone(), description);
+        Ok(name)
+    }
+}
+
+impl From<&Camera> for CameraDescription {
+    fn from(camera: &Camera) -> Self {
+        Self {
+            look_from: camera.origin,
+            look_at: camera.look_at,
+            vup: camera.vup,
+            vertical_field_of_view: camera.vertical_field_of_view,
+            aperture: camera.aperture,
+            focus_distance: Some(camera.focus_distance),
+            shutter_open: camera.shutter_open,
+            shutter_close: camera.shutter_close,
+        }
+    }
+}
+
+impl From<&RenderSettings> for RenderDescription {
+    fn from(settings: &RenderSettings) -> Self {
+        Self {
+            width: Some(settings.image_width),
+            height: Some(settings.image_height),
+            samples_per_pixel: Some(settings.samples_per_pixel),
+            max_depth: Some(settings.max_depth),
+            background: Some(settings.background),
+            seed: Some(settings.seed),
+        }
+    }
+}
+
+impl CameraDescription {
+    fn check(&self) -> Result<(), String> {
+        let view_direction = self.look_at - self.look_from;
+        if view_direction.is_near_zero() {
+            return Err(format!(
+                "look_at: the camera looks from and at the same point {}",
+                self.look_from
+            ));
+        }
+        if self.vup.cross(view_direction).is_near_zero() {
+            return Err(format!(
+                "vup: {} can't be parallel to the view direction {}",
+                self.vup, view_direction
+            ));
+        }
+        if self.vertical_field_of_view <= 0. || self.vertical_field_of_view >= 180. {
+            return Err(format!(
+                "vertical_field_of_view: expected an angle between 0 and 180 degrees, got {}",
+                self.vertical_field_of_view
+            ));
+        }
+        if self.aperture < 0. {
+            return Err(format!(
+                "aperture: expected a number of at least 0, got {}",
+                self.aperture
+            ));
+        }
+        if self.shutter_close < self.shutter_open {
+            return Err(format!(
+                "shutter_close: expected a time from shutter_open {} on, got {}",
+                self.shutter_open, self.shutter_close
+            ));
+        }
+        match self.focus_distance {
+            Some(focus_distance) if focus_distance <= 0. => Err(format!(
+                "focus_distance: expected a number greater than 0, got {}",
+                focus_distance
+            )),
+            _ => Ok(()),
+        }
+    }
+
+    /// How far away things are in focus
+    pub fn focus_distance(&self) -> f64 {
+        self.focus_distance
+            .unwrap_or_else(|| (self.look_at - self.look_from).len())
+    }
+
+    pub fn build(&self, aspect_ratio: f64) -> Camera {
+        Camera::new(
+            self.look_from,
+            self.look_at,
+            self.vup,
+            self.vertical_field_of_view,
+            aspect_ratio,
+            self.aperture,
+            self.focus_distance(),
+        )
+        .with_shutter(self.shutter_open, self.shutter_close)
+    }
+}
+
+impl RenderDescription {
+    fn check(&self) -> Result<(), String> {
+        for (name, size) in [("width", self.width), ("height", self.height)].iter() {
+            if let Some(size) = size.filter(|&size| size < 2) {
+                return Err(format!(
+                    "{}: expected at least 2 pixels, got {}",
+                    name, size
+                ));
+            }
+        }
+        if self.samples_per_pixel == Some(0) {
+            return Err("samples_per_pixel: expected at least 1 sample".to_string());
+        }
+        if let Some(max_depth) = self.max_depth.filter(|&max_depth| max_depth < 1) {
+            return Err(format!(
+                "max_depth: expected at least 1 bounce, got {}",
+                max_depth
+            ));
+        }
+        Ok(())
+    }
+}
+
+impl MaterialDescription {
+    fn check(&self) -> Result<(), String> {
+        match self {
+            MaterialDescription::Dielectric { refraction_index } if *refraction_index <= 0. => {
+                Err(format!(
+                    "refraction_index: expected a number greater than 0, got {}",
+                    refraction_index
+                ))
+            }
+            MaterialDescription::Metal { fuzz, .. } if !(0. ..=1.).contains(fuzz) => {
+                Err(format!("fuzz: expected a number from 0 to 1, got {}", fuzz))
+            }
+            MaterialDescription::Lambertian { albedo }
+            | MaterialDescription::Metal { albedo, .. } => {
+                albedo.check().map_err(|error| format!("albedo.{}", error))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    fn type_name(&self) -> &'static str {
+        match self {
+            MaterialDescription::Lambertian { .. } => "lambertian",
+            MaterialDescription::Metal { .. } => "metal",
+            MaterialDescription::Dielectric { .. } => "dielectric",
+            MaterialDescription::DiffuseLight { .. } => "diffuse_light",
+        }
+    }
+
+    fn emits_light(&self) -> bool {
+        matches!(self, MaterialDescription::DiffuseLight { .. })
+    }
+
+    /// Make the material, loading image files relative to `directory`
+    pub fn build(&self, directory: &Path) -> Result<Arc<dyn Material>, Box<dyn Error>> {
+        Ok(match self {
+            MaterialDescription::Lambertian { albedo } => {
+                Arc::new(Lambertian::from_texture(albedo.build(directory)?))
+            }
+            MaterialDescription::Metal { albedo, fuzz } => {
+                Arc::new(Metal::from_texture(albedo.build(directory)?, *fuzz))
+            }
+            MaterialDescription::Dielectric { refraction_index } => {
+                Arc::new(Dielectric::new(*refraction_index))
+            }
+            MaterialDescription::DiffuseLight { emit } => Arc::new(DiffuseLight::new(*emit)),
+        })
+    }
+}
+
+// Colors and patterns look nothing alike, so pick one by the kind of JSON value to get the error of the
+// one that was meant, instead of one that only says that it is neither
+impl TryFrom<serde_json::Value> for TextureDescription {
+    type Error = String;
+
+    fn try_from(value: serde_json::Value) -> Result<Self, Self::Error> {
+        if value.is_object() {
+            PatternDescription::deserialize(value).map(TextureDescription::Pattern)
+        } else {
+            Color::deserialize(value).map(TextureDescription::Solid)
+        }
+        .map_err(|error| error.to_string())
+    }
+}
+
+impl TextureDescription {
+    fn check(&self) -> Result<(), String> {
+        let pattern = match self {
+            TextureDescription::Solid(_) => return Ok(()),
+            TextureDescription::Pattern(pattern) => pattern,
+        };
+        let positive = |name: &str, value: f64| {
+            if value > 0. && value.is_finite() {
+                Ok(())
+            } else {
+                Err(format!(
+                    "{}: expected a number greater than 0, got {}",
+                    name, value
+                ))
+            }
+        };
+        match pattern {
+            PatternDescription::Checker { even, odd, size } => {
+                positive("size", *size)?;
+                check_checker_textures(even, odd)
+            }
+            PatternDescription::UvChecker {
+                even,
+                odd,
+                columns,
+                rows,
+            } => {
+                positive("columns", *columns)?;
+                positive("rows", *rows)?;
+                check_checker_textures(even, odd)
+            }
+            PatternDescription::Noise { scale, .. } => positive("scale", *scale),
+            PatternDescription::Image { .. } => Ok(()),
+        }
+    }
+
+    /// Make the texture, loading image files relative to `directory`
+    pub fn build(&self, directory: &Path) -> Result<Arc<dyn Texture>, Box<dyn Error>> {
+        Ok(match self {
+            TextureDescription::Solid(color) => Arc::new(SolidColor::new(*color)),
+            TextureDescription::Pattern(PatternDescription::Checker { even, odd, size }) => {
+                Arc::new(Checker::new(
+                    even.build(directory)?,
+                    odd.build(directory)?,
+                    CheckerMapping::Space { size: *size },
+                ))
+            }
+            TextureDescription::Pattern(PatternDescription::UvChecker {
+                even,
+                odd,
+                columns,
+                rows,
+            }) => Arc::new(Checker::new(
+                even.build(directory)?,
+                odd.build(directory)?,
+                CheckerMapping::Uv {
+                    columns: *columns,
+                    rows: *rows,
+                },
+            )),
+            TextureDescription::Pattern(PatternDescription::Image { file, wrap }) => {
+                Arc::new(ImageTexture::load(directory.join(file), *wrap)?)
+            }
+            TextureDescription::Pattern(PatternDescription::Noise {
+                pattern,
+                scale,
+                seed,
+                low,
+                high,
+            }) => Arc::new(NoiseTexture::with_colors(
+                *pattern, *scale, *seed, *low, *high,
+            )),
+        })
+    }
+}
+
+fn check_checker_textures(
+    even: &TextureDescription,
+    odd: &TextureDescription,
+) -> Result<(), String> {
+    even.check().map_err(|error| format!("even.{}", error))?;
+    odd.check().map_err(|error| format!("odd.{}", error))
+}
+
+impl TransformStep {
+    pub fn build(&self) -> Result<Transform, Box<dyn Error>> {
+        match *self {
+            TransformStep::Translate(offset) => Ok(Transform::translation(offset)),
+            TransformStep::Rotate { axis, degrees } => {
+                if axis.is_near_zero() {
+                    return Err(format!("rotate: the axis {} has no direction", axis).into());
+                }
+                Ok(Transform::rotation(axis, degrees))
+            }
+            TransformStep::Scale(factors) => {
+                if factors.x == 0. || factors.y == 0. || factors.z == 0. {
+                    return Err(
+                        format!("scale: expected factors other than 0, got {}", factors).into(),
+                    );
+                }
+                Ok(Transform::scaling(factors))
+            }
+            TransformStep::Matrix(matrix) => {
+                Transform::from_matrix(matrix).map_err(|error| format!("matrix: {}", error).into())
+            }
+        }
+    }
+}
+
+impl ObjectDescription {
+    /// The name of the material, for objects that have one
+    pub fn material(&self) -> Option<&str> {
+        match self {
+            ObjectDescription::Sphere { material, .. }
+            | ObjectDescription::MovingSphere { material, .. }
+            | ObjectDescription::Triangle { material, .. }
+            | ObjectDescription::Plane { material, .. }
+            | ObjectDescription::Quad { material, .. }
+            | ObjectDescription::Disk { material, .. }
+            | ObjectDescription::Cylinder { material, .. }
+            | ObjectDescription::Cone { material, .. }
+            | ObjectDescription::Paraboloid { material, .. }
+            | ObjectDescription::Torus { material, .. }
+            | ObjectDescription::Cuboid { material, .. }
+            | ObjectDescription::Mesh { material, .. }
+            | ObjectDescription::Ply { material, .. }
+            | ObjectDescription::Stl { material, .. } => Some(material),
+            ObjectDescription::Group { .. }
+            | ObjectDescription::Transformed { .. }
+            | ObjectDescription::Csg { .. }
+            | ObjectDescription::Obj { .. } => None,
+        }
+    }
+
+    fn build(
+        &self,
+        materials: &HashMap<&str, Arc<dyn Material>>,
+        directory: &Path,
+    ) -> Result<Box<dyn Hittable>, Box<dyn Error>> {
+        let material = || -> Result<Arc<dyn Material>, Box<dyn Error>> {
+            let name = self.material().ok_or("the object has no material")?;
+            let material = materials
+                .get(name)
+                .ok_or_else(|| format!("there is no material named '{}'", name))?;
+            Ok(Arc::clone(material))
+        };
+        Ok(match self {
+            ObjectDescription::Sphere { center, radius, .. } => {
+                Box::new(Sphere::new(*center, *radius, material()?))
+            }
+            ObjectDescription::MovingSphere {
+                start_center,
+                end_center,
+                start_time,
+                end_time,
+                radius,
+                ..
+            } => Box::new(MovingSphere::new(
+                *start_center,
+                *end_center,
+                *start_time,
+                *end_time,
+                *radius,
+                material()?,
+            )),
+            ObjectDescription::Triangle { vertices, .. } => Box::new(Triangle::new(
+                vertices[0],
+                vertices[1],
+                vertices[2],
+                material()?,
+            )),
+            ObjectDescription::Plane { point, normal, .. } => {
+                Box::new(Plane::new(*point, *normal, material()?))
+            }
+            ObjectDescription::Quad { corner, edges, .. } => {
+                Box::new(Quad::new(*corner, edges[0], edges[1], material()?))
+            }
+            ObjectDescription::Disk {
+                center,
+                normal,
+                radius,
+                ..
+            } => Box::new(Disk::new(*center, *normal, *radius, material()?)),
+            ObjectDescription::Cylinder {
+                base,
+                radius,
+                height,
+                ..
+            } => Box::new(Cylinder::new(*base, *radius, *height, material()?)),
+            ObjectDescription::Cone {
+                base,
+                radius,
+                height,
+                ..
+            } => Box::new(Cone::new(*base, *radius, *height, material()?)),
+            ObjectDescription::Paraboloid {
+                vertex,
+                radius,
+                height,
+                ..
+            } => Box::new(Paraboloid::new(*vertex, *radius, *height, material()?)),
+            ObjectDescription::Torus {
+                center,
+                major_radius,
+                minor_radius,
+                ..
+            } => Box::new(Torus::new(
+                *center,
+                *major_radius,
+                *minor_radius,
+                material()?,
+            )),
+            ObjectDescription::Cuboid {
+                minimum, maximum, ..
+            } => Box::new(Cuboid::new(*minimum, *maximum, material()?)),
+            ObjectDescription::Mesh {
+                positions,
+                normals,
+                uvs,
+                triangles,
+                ..
+            } => {
+                let mesh = MeshData {
+                    positions: positions.clone(),
+                    normals: normals.clone(),
+                    uvs: uvs.clone(),
+                    triangles: triangles.clone(),
+                };
+                Box::new(TriangleMesh::new(mesh, material()?)?)
+            }
+            ObjectDescription::Group { objects } => {
+                let mut group = HittableList::new();
+                for (i, object) in objects.iter().enumerate() {
+                    let object = object
+                        .build(materials, directory)
+                        .map_err(|error| format!("objects[{}]: {}", i, error))?;
+                    group.add(object);
+                }
+                Box::new(group)
+            }
+            ObjectDescription::Transformed { transform, object } => {
+                let transform = transform
+                    .iter()
+                    .try_fold(Transform::identity(), |transform, step| {
+                        Ok::<_, Box<dyn Error>>(transform.then(step.build()?))
+                    })?;
+                let object: Arc<dyn Hittable> = Arc::from(
+                    object
+                        .build(materials, directory)
+                        .map_err(|error| format!("object: {}", error))?,
+                );
+                Box::new(Transformed::new(object, transform))
+            }
+            ObjectDescription::Csg {
+                operation,
+                left,
+                right,
+            } => {
+                let left = left
+                    .build(materials, directory)
+                    .map_err(|error| format!("left: {}", error))?;
+                let right = right
+                    .build(materials, directory)
+                    .map_err(|error| format!("right: {}", error))?;
+                Box::new(Csg::new(*operation, left, right))
+            }
+            ObjectDescription::Obj { file } => Box::new(load_obj(directory.join(file))?),
+            ObjectDescription::Ply { file, .. } => {
+                Box::new(load_ply(directory.join(file), material()?)?)
+            }
+            ObjectDescription::Stl { file, .. } => {
+                Box::new(load_stl(directory.join(file), material()?)?)
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+const TEST_SCENE: &str = r#"{
+    "camera": { "look_from": [0, 1, 5], "look_at": [0, 1, 0], "aperture": 0.1 },
+    "render": { "samples_per_pixel": 8, "background": [0, 0, 0] },
+    "materials": {
+        "red": { "type": "lambertian", "albedo": [0.8, 0.1, 0.1] },
+        "lamp": { "type": "diffuse_light", "emit": [4, 4, 4] }
+    },
+    "objects": [
+        { "type": "sphere", "center": [-1, 1, 0], "radius": 0.5, "material": "red" },
+        { "type": "sphere", "center": [1, 1, 0], "radius": 0.5, "material": "red" }
+    ],
+    "lights": [
+        { "type": "triangle", "vertices": [[-1, 3, -1], [1, 3, -1], [0, 3, 1]], "material": "lamp" }
+    ]
+}"#;
+
+// The test scene with `objects` instead of its two spheres, built, and exported to a scene that reads
+// back the same
+#[cfg(test)]
+fn build_test_objects(objects: &str) -> (HittableList, SceneDescription) {
+    let text = TEST_SCENE.replace(
+        r#"{ "type": "sphere", "center": [-1, 1, 0], "radius": 0.5, "material": "red" },
+        { "type": "sphere", "center": [1, 1, 0], "radius": 0.5, "material": "red" }"#,
+        objects,
+    );
+    let scene = parse_scene(&text).unwrap();
+    let world = scene.build_world(Path::new(".")).unwrap();
+    let exported =
+        SceneDescription::from_world(&world, scene.camera.clone(), scene.render.clone()).unwrap();
+    let json = exported.to_json().unwrap();
+    assert_eq!(parse_scene(&json).unwrap(), exported);
+    (world, exported)
+}
+
+// The object a ray hits first and how far along it, when it hits anything
+#[cfg(test)]
+fn first_hit(world: &HittableList, origin: Point3, direction: Vec3) -> Option<(u32, f64)> {
+    let mut record = crate::hittable::HitRecord::new();
+    let ray = crate::ray::Ray::new(origin, direction);
+    if world.hit(ray, 0.001, f64::INFINITY, &mut record) {
+        Some((record.object_id, record.t))
+    } else {
+        None
+    }
+}
+
+#[test]
+fn test_parse_scene() {
+    // Given a scene with two spheres sharing a material, lit by a triangle
+    let text = TEST_SCENE;
+
+    // When I parse it
+    let scene = parse_scene(text).unwrap();
+
+    // Then the camera has defaults for what is left out
+    assert_eq!(scene.camera.vup, Vec3::new(0., 1., 0.));
+    assert_eq!(scene.camera.vertical_field_of_view, 20.);
+    assert_eq!(scene.camera.focus_distance(), 5.);
+
+    // And the render settings are only the ones the scene has
+    assert_eq!(scene.render.samples_per_pixel, Some(8));
+    assert_eq!(scene.render.width, None);
+    assert_eq!(
+        scene.render.background,
+        Some(Background::Solid(Color::new(0., 0., 0.)))
+    );
+
+    // And the world has all objects and lights
+    let world = scene.build_world(Path::new(".")).unwrap();
+    assert_eq!(world.len(), 3);
+}
+
+#[test]
+fn test_parse_scene_errors_say_where() {
+    // Given scenes with mistakes in them
+    let cases = [
+        (
+            TEST_SCENE.replace("\"radius\": 0.5", "\"radius\": \"big\""),
+            "objects[0]: invalid type: string \"big\", expected f64",
+        ),
+        (
+            TEST_SCENE.replace("\"type\": \"sphere\"", "\"type\": \"cube\""),
+            "objects[0].type: unknown variant `cube`",
+        ),
+        (
+            TEST_SCENE.replace(
+                "\"material\": \"red\" }\n    ]",
+                "\"material\": \"blue\" }\n    ]",
+            ),
+            "objects[1].material: there is no material named 'blue'",
+        ),
+        (
+            TEST_SCENE.replace("\"lamp\" }", "\"red\" }"),
+            "lights[0].material: 'red' doesn't give off light",
+        ),
+        (
+            TEST_SCENE.replace("\"albedo\": [0.8, 0.1, 0.1]", "\"albedo\": [0.8, 0.1]"),
+            "materials.red: invalid length 2",
+        ),
+        (
+            TEST_SCENE.replace(
+                "\"albedo\": [0.8, 0.1, 0.1]",
+                "\"albedo\": { \"type\": \"checker\", \"even\": [1, 1, 1], \"odd\": [0, 0, 0], \"size\": 0 }",
+            ),
+            "materials.red.albedo.size: expected a number greater than 0, got 0",
+        ),
+        (
+            TEST_SCENE.replace("\"background\": [0, 0, 0]", "\"background\": \"space\""),
+            "render.background: expected sky, rainbow or a color like [0, 0, 0], got 'space'",
+        ),
+        (
+            TEST_SCENE.replace("\"look_at\": [0, 1, 0]", "\"look_at\": [0, 1, 5]"),
+            "camera.look_at: the camera looks from and at the same point",
+        ),
+        (
+            TEST_SCENE.replacen(
+                "{ \"type\": \"sphere\", \"center\": [-1, 1, 0], \"radius\": 0.5, \"material\": \"red\" }",
+                r#"{ "type": "transformed", "transform": [{ "scale": [0, 1, 1] }], "object": { "type": "sphere", "center": [-1, 1, 0], "radius": 0.5, "material": "red" } }"#,
+                1,
+            ),
+            "objects[0].transform[0]: scale: expected factors other than 0",
+        ),
+        (
+            TEST_SCENE.replace("\"aperture\": 0.1", "\"shutter_open\": 1, \"shutter_close\": 0"),
+            "camera.shutter_close: expected a time from shutter_open 1 on, got 0",
+        ),
+        (
+            TEST_SCENE.replacen(
+                "{ \"type\": \"sphere\", \"center\": [-1, 1, 0], \"radius\": 0.5, \"material\": \"red\" }",
+                r#"{ "type": "quad", "corner": [0, 0, 0], "edges": [[1, 0, 0], [2, 0, 0]], "material": "red" }"#,
+                1,
+            ),
+            "objects[0].edges: expected edges that aren't parallel",
+        ),
+        (
+            TEST_SCENE.replacen(
+                "{ \"type\": \"sphere\", \"center\": [-1, 1, 0], \"radius\": 0.5, \"material\": \"red\" }",
+                r#"{ "type": "torus", "center": [0, 0, 0], "major_radius": 1, "minor_radius": -0.5, "material": "red" }"#,
+                1,
+            ),
+            "objects[0].minor_radius: expected a number greater than 0, got -0.5",
+        ),
+    ];
+
+    for (text, expected) in cases.iter() {
+        // When I parse them
+        let error = parse_scene(text).err().unwrap();
+
+        // Then the error says where the mistake is
+        assert!(
+            error.to_string().starts_with(expected),
+            "'{}' doesn't start with '{}'",
+            error,
+            expected
+        );
+    }
+}
+
+#[test]
+fn test_textured_materials() {
+    // Given a scene whose red material is a checker of red and a smaller checker in surface coordinates
+    let text = TEST_SCENE.replace(
+        "\"albedo\": [0.8, 0.1, 0.1]",
+        r#""albedo": {
+            "type": "checker",
+            "even": [0.8, 0.1, 0.1],
+            "odd": { "type": "uv_checker", "even": [1, 1, 1], "odd": { "type": "noise", "pattern": "marble", "seed": 3 }, "columns": 8, "rows": 4 },
+            "size": 0.25
+        }"#,
+    );
+
+    // When I build it and export what it built
+    let scene = parse_scene(&text).unwrap();
+    let world = scene.build_world(Path::new(".")).unwrap();
+    let exported =
+        SceneDescription::from_world(&world, scene.camera.clone(), scene.render.clone()).unwrap();
+
+    // Then the textures come back the same
+    let albedo = match &exported.materials["lambertian_1"] {
+        MaterialDescription::Lambertian { albedo } => albedo.clone(),
+        material => panic!("expected a lambertian material, got {:?}", material),
+    };
+    match &scene.materials["red"] {
+        MaterialDescription::Lambertian { albedo: original } => assert_eq!(&albedo, original),
+        material => panic!("expected a lambertian material, got {:?}", material),
+    }
+
+    // And a missing image says which file it is
+    let text = TEST_SCENE.replace(
+        "\"albedo\": [0.8, 0.1, 0.1]",
+        "\"albedo\": { \"type\": \"image\", \"file\": \"missing.png\" }",
+    );
+    let error = parse_scene(&text)
+        .unwrap()
+        .build_world(Path::new("."))
+        .err()
+        .unwrap();
+    assert!(error.to_string().starts_with("materials.red: can't open"));
+    assert!(error.to_string().contains("missing.png"));
+}
+
+#[test]
+fn test_transformed_objects() {
+    // Given a scene with a sphere at the origin, made twice as big and moved up
+    let objects = r#"{
+        "type": "transformed",
+        "transform": [{ "scale": [2, 2, 2] }, { "rotate": { "axis": [0, 1, 0], "degrees": 30 } }, { "translate": [0, 10, 0] }],
+        "object": { "type": "sphere", "center": [0, 0, 0], "radius": 0.5, "material": "red" }
+    }"#;
+
+    // When I build it and export it
+    let (world, exported) = build_test_objects(objects);
+
+    // Then a ray from above hits the sphere where it was moved, at its new size
+    let (object_id, t) =
+        first_hit(&world, Point3::new(0., 20., 0.), Vec3::new(0., -1., 0.)).unwrap();
+    assert!(object_id == 1 && (t - 9.).abs() < 1e-9);
+
+    // And it is exported as one matrix
+    match &exported.objects[0] {
+        ObjectDescription::Transformed { transform, object } => {
+            assert_eq!(transform.len(), 1);
+            assert!(matches!(**object, ObjectDescription::Sphere { .. }));
+        }
+        object => panic!("expected a transformed object, got {:?}", object),
+    }
+}
+
+#[test]
+fn test_flat_shapes_and_boxes() {
+    // Given a scene with a floor, a wall, a round table top and a box
+    let objects = r#"{ "type": "plane", "point": [0, 0, 0], "normal": [0, 1, 0], "material": "red" },
+        { "type": "quad", "corner": [-2, 0, -2], "edges": [[4, 0, 0], [0, 3, 0]], "material": "red" },
+        { "type": "disk", "center": [0, 1, 0], "normal": [0, 1, 0], "radius": 0.5, "material": "red" },
+        { "type": "box", "minimum": [1, 0, 0], "maximum": [1.5, 0.5, 0.5], "material": "red" }"#;
+
+    // When I build it and export it
+    let (world, exported) = build_test_objects(objects);
+
+    // Then rays hit the floor, the wall, the table top and the top of the box
+    let down = Vec3::new(0., -1., 0.);
+    assert_eq!(
+        first_hit(&world, Point3::new(5., 2., 5.), down),
+        Some((1, 2.))
+    );
+    assert_eq!(
+        first_hit(&world, Point3::new(0., 2.5, 5.), Vec3::new(0., 0., -1.)),
+        Some((2, 7.))
+    );
+    assert_eq!(
+        first_hit(&world, Point3::new(0., 2., 0.), down),
+        Some((3, 1.))
+    );
+    assert_eq!(
+        first_hit(&world, Point3::new(1.25, 2., 0.25), down),
+        Some((4, 1.5))
+    );
+
+    // And they are exported as they were written, with the box between the same corners
+    let types: Vec<_> = exported.objects[..4]
+        .iter()
+        .map(|object| serde_json::to_value(object).unwrap()["type"].clone())
+        .collect();
+    assert_eq!(types, ["plane", "quad", "disk", "box"]);
+    match &exported.objects[3] {
+        ObjectDescription::Cuboid {
+            minimum, maximum, ..
+        } => {
+            assert_eq!(*minimum, Point3::new(1., 0., 0.));
+            assert_eq!(*maximum, Point3::new(1.5, 0.5, 0.5));
+        }
+        object => panic!("expected a box, got {:?}", object),
+    }
+}
+
+#[test]
+fn test_round_solids() {
+    // Given a scene with a cylinder, a cone, a paraboloid and a torus in a row away from the light
+    let objects = r#"{ "type": "cylinder", "base": [3, 0, 0], "radius": 1, "height": 2, "material": "red" },
+        { "type": "cone", "base": [6, 0, 0], "radius": 1, "height": 2, "material": "red" },
+        { "type": "paraboloid", "vertex": [9, 0, 0], "radius": 1, "height": 2, "material": "red" },
+        { "type": "torus", "center": [12, 1, 0], "major_radius": 0.75, "minor_radius": 0.25, "material": "red" }"#;
+
+    // When I build it and export it
+    let (world, exported) = build_test_objects(objects);
+
+    // Then rays from above hit the top of each of them
+    let hit = |x: f64| first_hit(&world, Point3::new(x, 5., 0.), Vec3::new(0., -1., 0.));
+    assert_eq!(hit(3.), Some((1, 3.)));
+    assert_eq!(hit(6.), Some((2, 3.)));
+    assert_eq!(hit(9.5), Some((3, 3.)));
+    let (torus, t) = hit(12.75).unwrap();
+    assert!(torus == 4 && (t - 3.75).abs() < 1e-9);
+
+    // And they are exported as they were written
+    assert!(matches!(
+        exported.objects[..4],
+        [
+            ObjectDescription::Cylinder { height, .. },
+            ObjectDescription::Cone { .. },
+            ObjectDescription::Paraboloid { .. },
+            ObjectDescription::Torus { minor_radius, .. },
+        ] if height == 2. && minor_radius == 0.25
+    ));
+}
+
+#[test]
+fn test_csg_objects() {
+    // Given a scene with a hollow ball, and a lens where two balls overlap
+    let objects = r#"{
+        "type": "csg",
+        "operation": "difference",
+        "left": { "type": "sphere", "center": [-1, 1, 0], "radius": 0.5, "material": "red" },
+        "right": { "type": "sphere", "center": [-1, 1, 0], "radius": 0.25, "material": "red" }
+    },
+    {
+        "type": "csg",
+        "operation": "intersection",
+        "left": { "type": "sphere", "center": [1, 1, 0], "radius": 0.5, "material": "red" },
+        "right": { "type": "sphere", "center": [1.5, 1, 0], "radius": 0.5, "material": "red" }
+    }"#;
+
+    // When I build it and export it
+    let (world, exported) = build_test_objects(objects);
+
+    // Then a ray from inside the hollow hits the inside of the shell, and rays only hit the lens
+    // where the balls overlap
+    let along_x = Vec3::new(1., 0., 0.);
+    assert_eq!(
+        first_hit(&world, Point3::new(-1., 1., 0.), along_x),
+        Some((1, 0.25))
+    );
+    assert_eq!(
+        first_hit(&world, Point3::new(0., 1., 0.), along_x),
+        Some((2, 1.))
+    );
+    assert_eq!(
+        first_hit(&world, Point3::new(1.25, 1., 0.), along_x),
+        Some((2, 0.25))
+    );
+    assert_eq!(
+        first_hit(&world, Point3::new(0.75, 5., 0.), Vec3::new(0., -1., 0.)),
+        None
+    );
+
+    // And the combinations are exported as they were written
+    assert!(matches!(
+        exported.objects[..2],
+        [
+            ObjectDescription::Csg {
+                operation: CsgOperation::Difference,
+                ..
+            },
+            ObjectDescription::Csg {
+                operation: CsgOperation::Intersection,
+                ..
+            },
+        ]
+    ));
+}
+
+#[test]
+fn test_export_round_trips() {
+    // Given the random scene
+    let world = crate::scenes::random_scene(&mut crate::rng::rng_from_seed(7));
+    let camera = Camera::new(
+        Point3::new(13., 2., 3.),
+        Point3::new(0., 0., 0.),
+        Vec3::new(0., 1., 0.),
+        20.,
+        1.5,
+        0.1,
+        10.,
+    );
+    let settings = RenderSettings::new(40, 20, 4, 8);
+
+    // When I export it, and export what the JSON of that builds again
+    let scene = SceneDescription::from_world(&world, (&camera).into(), (&settings).into()).unwrap();
+    let json = scene.to_json().unwrap();
+    let rebuilt = parse_scene(&json).unwrap();
+    let world_again = rebuilt.build_world(Path::new(".")).unwrap();
+    let scene_again = SceneDescription::from_world(
+        &world_again,
+        (&rebuilt.camera(1.5)).into(),
+        rebuilt.render.clone(),
+    )
+    .unwrap();
+
+    // Then nothing changes on the way, not even the last bit of a number
+    assert_eq!(scene_again, scene);
+    assert_eq!(scene.objects.len(), world.len());
+    assert_eq!(scene.camera.look_from, Point3::new(13., 2., 3.));
+    assert_eq!(scene.render.samples_per_pixel, Some(4));
+}
+
+#[test]
+fn test_export_keeps_shared_materials_shared() {
+    // Given a world with two spheres that share a material, and a light
+    let scene = parse_scene(TEST_SCENE).unwrap();
+    let world = scene.build_world(Path::new(".")).unwrap();
+
+    // When I export it
+    let exported =
+        SceneDescription::from_world(&world, scene.camera.clone(), scene.render.clone()).unwrap();
+
+    // Then the spheres still share one material
+    assert_eq!(exported.materials.len(), 2);
+    assert_eq!(
+        exported.objects[0].material(),
+        exported.objects[1].material()
+    );
+    assert_ne!(
+        exported.objects[0].material(),
+        exported.objects[2].material()
+    );
+}
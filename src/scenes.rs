@@ -0,0 +1,169 @@
+use std::sync::Arc;
+
+use rand::Rng;
+
+use crate::{
+    color::Color,
+    csg::{Csg, CsgOperation},
+    dielectric::Dielectric,
+    diffuse_light::DiffuseLight,
+    hittable_list::HittableList,
+    lambertian::Lambertian,
+    material::Material,
+    metal::Metal,
+    point3::Point3,
+    sphere::Sphere,
+};
+
+/// Three spheres of different materials on a ground sphere, with a hollow glass sphere on the left
+pub fn controlled_scene() -> HittableList {
+    let mut world = HittableList::new();
+
+    let ground_material = Lambertian::new(Color::new(0.8, 0.8, 0.));
+    let blue_ball_material = Lambertian::new(Color::new(0.1, 0.2, 0.5));
+    // let ball_material = Lambertian::new(Color::new(0.7, 0.3, 0.3));
+    // let metal_material = Metal::new(Color::new(0.8, 0.8, 0.8), 0.3);
+    let yellow_metal_material = Metal::new(Color::new(0.8, 0.6, 0.2), 0.1);
+    let refracting_material = Dielectric::new(1.5);
+
+    let ground = Sphere::new(
+        Point3::new(0., -100.5, -1.),
+        100.,
+        Arc::new(ground_material),
+    );
+    let left = Csg::new(
+        CsgOperation::Difference,
+        Box::new(Sphere::new(
+            Point3::new(-1., 0., -1.),
+            0.5,
+            Arc::new(refracting_material),
+        )),
+        Box::new(Sphere::new(
+            Point3::new(-1., 0., -1.),
+            0.4,
+            Arc::new(refracting_material),
+        )),
+    );
+    let center = Sphere::new(Point3::new(0., 0., -1.), 0.5, Arc::new(blue_ball_material));
+    let right = Sphere::new(
+        Point3::new(1., 0., -1.),
+        0.5,
+        Arc::new(yellow_metal_material),
+    );
+
+    world.add(Box::new(left));
+    world.add(Box::new(center));
+    world.add(Box::new(right));
+    // world.add(Box::new(Sphere::new(
+    //     Point3::new(-1., 0., -2.),
+    //     0.5,
+    //     Arc::new(Lambertian::new(Color::new(0.3, 0.3, 0.7))),
+    // )));
+    // world.add(Box::new(Sphere::new(
+    //     Point3::new(-2., 0., -3.),
+    //     0.5,
+    //     Arc::new(Lambertian::new(Color::new(0.3, 0.7, 0.3))),
+    // )));
+    world.add(Box::new(ground));
+
+    world
+}
+
+/// The final scene of the book: three big spheres surrounded by small spheres of random materials
+pub fn random_scene<R: Rng + ?Sized>(rng: &mut R) -> HittableList {
+    let mut world = HittableList::new();
+
+    let ground_material = Arc::new(Lambertian::new(Color::new(0.5, 0.5, 0.5)));
+    let ground = Box::new(Sphere::new(
+        Point3::new(0., -1000., 0.),
+        1000.,
+        ground_material,
+    ));
+    world.add(ground);
+
+    for a in -11..11 {
+        for b in -11..11 {
+            let material_choice = rng.gen::<f64>();
+            let center = Point3::new(
+                f64::from(a) + 0.9 * rng.gen::<f64>(),
+                0.2,
+                f64::from(b) + 0.9 * rng.gen::<f64>(),
+            );
+
+            // Skip if the item is out of view
+            if (center - Point3::new(4., 0.2, 0.)).len() <= 0.9 {
+                continue;
+            };
+
+            let material: Arc<dyn Material> = if material_choice < 0.8 {
+                // Diffuse
+                let albedo = Color::random(rng) * Color::random(rng);
+                Arc::new(Lambertian::new(albedo))
+            } else if material_choice < 0.95 {
+                // Metal
+                let albedo = Color::random_in_range(rng, 0.5, 1.);
+                let fuzz = rng.gen_range(0.0..0.5);
+                Arc::new(Metal::new(albedo, fuzz))
+            } else {
+                // Glass
+                Arc::new(Dielectric::new(1.5))
+            };
+
+            let object = Box::new(Sphere::new(center, 0.2, material));
+            world.add(object);
+        }
+    }
+
+    let big_glass = Box::new(Sphere::new(
+        Point3::new(0., 1., 0.),
+        1.,
+        Arc::new(Dielectric::new(1.5)),
+    ));
+    world.add(big_glass);
+
+    let big_diffuse = Box::new(Sphere::new(
+        Point3::new(-4., 1., 0.),
+        1.,
+        Arc::new(Lambertian::new(Color::new(0.4, 0.2, 0.1))),
+    ));
+    world.add(big_diffuse);
+
+    let big_metal = Box::new(Sphere::new(
+        Point3::new(4., 1., 0.),
+        1.,
+        Arc::new(Metal::new(Color::new(0.7, 0.6, 0.5), 0.)),
+    ));
+    world.add(big_metal);
+
+    world
+}
+
+/// A diffuse sphere on the ground, lit only by a glowing sphere above it and a dimmer one beside it.
+/// Render it with a black background to see the light sources do all the work.
+pub fn simple_light_scene() -> HittableList {
+    let mut world = HittableList::new();
+
+    world.add(Box::new(Sphere::new(
+        Point3::new(0., -1000., 0.),
+        1000.,
+        Arc::new(Lambertian::new(Color::new(0.5, 0.5, 0.5))),
+    )));
+    world.add(Box::new(Sphere::new(
+        Point3::new(0., 2., 0.),
+        2.,
+        Arc::new(Lambertian::new(Color::new(0.8, 0.3, 0.2))),
+    )));
+
+    world.add(Box::new(Sphere::new(
+        Point3::new(0., 7., 0.),
+        2.,
+        Arc::new(DiffuseLight::new(Color::new(4., 4., 4.))),
+    )));
+    world.add(Box::new(Sphere::new(
+        Point3::new(4., 1.5, -3.),
+        0.75,
+        Arc::new(DiffuseLight::new(Color::new(2., 2., 3.))),
+    )));
+
+    world
+}
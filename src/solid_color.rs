@@ -0,0 +1,23 @@
+use crate::{color::Color, point3::Point3, scene_file::TextureDescription, texture::Texture};
+
+/// The same color everywhere
+#[derive(Debug, Clone, Copy)]
+pub struct SolidColor {
+    color: Color,
+}
+
+impl SolidColor {
+    pub fn new(color: Color) -> Self {
+        Self { color }
+    }
+}
+
+impl Texture for SolidColor {
+    fn value(&self, _u: f64, _v: f64, _point: Point3) -> Color {
+        self.color
+    }
+
+    fn describe(&self) -> Option<TextureDescription> {
+        Some(TextureDescription::Solid(self.color))
+    }
+}
@@ -1,73 +1,197 @@
-use std::rc::Rc;
+use std::error::Error;
+use std::f64::consts::PI;
+use std::sync::Arc;
 
 use material::Material;
 
 use crate::{
+    aabb::Aabb,
     hittable::{HitRecord, Hittable},
     material,
     point3::Point3,
     ray::Ray,
+    scene_file::{ExportedMaterials, ObjectDescription},
+    vec3::Vec3,
 };
 
 #[derive(Clone)]
 pub struct Sphere {
     center: Point3,
     radius: f64,
-    material: Rc<dyn Material>,
+    material: Arc<dyn Material>,
 }
 
 impl Sphere {
-    pub fn new(center: Point3, radius: f64, material: Rc<dyn Material>) -> Self {
-        return Self {
+    pub fn new(center: Point3, radius: f64, material: Arc<dyn Material>) -> Self {
+        Self {
             center,
             radius,
             material,
-        };
+        }
     }
 }
 
-impl Hittable for Sphere {
-    // Whether a ray passes through this sphere
-    //
-    // If it does not hit, it returns -1. If it does, it returns distance t that must be travelled along the given ray to reach the sphere.
-    //
-    // A sphere at origin 0, 0, 0 is the collection of points where x^2+y^2+z^2=R^2
-    //
-    // Expressed in vector form where P is a point and C is the center of the sphere: (P−C)⋅(P−C)=r^2
-    //
-    // Using a ray P(t)=A+tb instead of a point, where A is its origin, b its direction and t the
-    // distance along that direction, the expression becomes (P(t)−C)⋅(P(t)−C)=r^2
-    //
-    // Expanding this equation and moving everything to the left yields: (t^2)b⋅b+2tb⋅(A−C)+(A−C)⋅(A−C)−r^2=0
-    //
-    // The function below checks whether t > 0 in (t^2)b⋅b+2tb⋅(A−C)+(A−C)⋅(A−C)−r^2=0
-    fn hit(&self, ray: Ray, t_min: f64, t_max: f64, record: &mut HitRecord) -> bool {
-        let oc = ray.origin - self.center;
-        let a = ray.direction.len_squared();
-        let half_b = oc.dot(ray.direction);
-        let c = oc.len_squared() - self.radius * self.radius;
+// Surface coordinates of a point on the unit sphere: u goes once around the y axis starting from -x,
+// and v from the bottom to the top
+fn sphere_uv(point: Point3) -> (f64, f64) {
+    let theta = (-point.y).clamp(-1., 1.).acos();
+    let phi = f64::atan2(-point.z, point.x) + PI;
+    (phi / (2. * PI), theta / PI)
+}
 
-        let discriminant = half_b * half_b - a * c;
-        if discriminant < 0. {
-            return false;
-        };
-        let sqrtd = f64::sqrt(discriminant);
+// Whether a ray passes through a sphere, filling in the record where it does
+//
+// If it does not hit, it returns -1. If it does, it returns distance t that must be travelled along the given ray to reach the sphere.
+//
+// A sphere at origin 0, 0, 0 is the collection of points where x^2+y^2+z^2=R^2
+//
+// Expressed in vector form where P is a point and C is the center of the sphere: (P−C)⋅(P−C)=r^2
+//
+// Using a ray P(t)=A+tb instead of a point, where A is its origin, b its direction and t the
+// distance along that direction, the expression becomes (P(t)−C)⋅(P(t)−C)=r^2
+//
+// Expanding this equation and moving everything to the left yields: (t^2)b⋅b+2tb⋅(A−C)+(A−C)⋅(A−C)−r^2=0
+//
+// The function below checks whether t > 0 in (t^2)b⋅b+2tb⋅(A−C)+(A−C)⋅(A−C)−r^2=0
+pub(crate) fn hit_sphere(
+    center: Point3,
+    radius: f64,
+    material: &Arc<dyn Material>,
+    ray: Ray,
+    t_min: f64,
+    t_max: f64,
+    record: &mut HitRecord,
+) -> bool {
+    let oc = ray.origin - center;
+    let a = ray.direction.len_squared();
+    let half_b = oc.dot(ray.direction);
+    let c = oc.len_squared() - radius * radius;
 
-        // Find the nearest root that lies in the acceptable range.
-        let mut root = (-half_b - sqrtd) / a;
+    let discriminant = half_b * half_b - a * c;
+    if discriminant < 0. {
+        return false;
+    };
+    let sqrtd = f64::sqrt(discriminant);
+
+    // Find the nearest root that lies in the acceptable range.
+    let mut root = (-half_b - sqrtd) / a;
+    if root < t_min || t_max < root {
+        root = (-half_b + sqrtd) / a;
         if root < t_min || t_max < root {
-            root = (-half_b + sqrtd) / a;
-            if root < t_min || t_max < root {
-                return false;
-            };
+            return false;
         };
+    };
+
+    record.t = root;
+    record.point = ray.at(record.t);
+    let outward_normal = (record.point - center) / radius;
+    record.set_face_normal(ray, outward_normal);
+    let (u, v) = sphere_uv((record.point - center) / radius.abs());
+    record.u = u;
+    record.v = v;
+    record.material = Arc::clone(material);
+
+    true
+}
 
-        record.t = root;
-        record.point = ray.at(record.t);
-        let outward_normal = (record.point - self.center) / self.radius;
-        record.set_face_normal(ray, outward_normal);
-        record.material = Rc::clone(&self.material);
+impl Hittable for Sphere {
+    fn hit(&self, ray: Ray, t_min: f64, t_max: f64, record: &mut HitRecord) -> bool {
+        hit_sphere(
+            self.center,
+            self.radius,
+            &self.material,
+            ray,
+            t_min,
+            t_max,
+            record,
+        )
+    }
 
-        return true;
+    fn bounding_box(&self, output_box: &mut Aabb) -> bool {
+        // Spheres with a negative radius face inwards, but take up the same space
+        let radius = Vec3::new(self.radius.abs(), self.radius.abs(), self.radius.abs());
+        *output_box = Aabb::new(self.center - radius, self.center + radius);
+        true
     }
+
+    fn export(
+        &self,
+        materials: &mut ExportedMaterials,
+    ) -> Result<ObjectDescription, Box<dyn Error>> {
+        Ok(ObjectDescription::Sphere {
+            center: self.center,
+            radius: self.radius,
+            material: materials.name(&self.material)?,
+        })
+    }
+}
+
+#[test]
+fn test_sphere_uv() {
+    // Given points on the unit sphere
+    let points = [
+        (Point3::new(-1., 0., 0.), (0., 0.5)),
+        (Point3::new(0., 0., 1.), (0.25, 0.5)),
+        (Point3::new(1., 0., 0.), (0.5, 0.5)),
+        (Point3::new(0., 0., -1.), (0.75, 0.5)),
+        (Point3::new(0., -1., 0.), (0.5, 0.)),
+        (Point3::new(0., 1., 0.), (0.5, 1.)),
+    ];
+
+    for (point, (u, v)) in points.iter() {
+        // When I get their surface coordinates
+        let uv = sphere_uv(*point);
+
+        // Then u goes around the sphere and v from the bottom to the top
+        assert!(
+            (uv.0 - u).abs() < 1e-12 && (uv.1 - v).abs() < 1e-12,
+            "{} has {:?} instead of {:?}",
+            point,
+            uv,
+            (u, v)
+        );
+    }
+}
+
+#[test]
+fn test_sphere_hit_has_uv() {
+    // Given a sphere
+    let sphere = Sphere::new(
+        Point3::new(0., 0., -2.),
+        0.5,
+        Arc::new(crate::lambertian::Lambertian::new(Vec3::new(0.5, 0.5, 0.5))),
+    );
+
+    // When a ray hits it head on from +z
+    let mut record = HitRecord::new();
+    let ray = Ray::new(Point3::new(0., 0., 0.), Vec3::new(0., 0., -1.));
+    assert!(sphere.hit(ray, 0.001, f64::INFINITY, &mut record));
+
+    // Then the hit is at the middle of its surface coordinates
+    assert!((record.u - 0.25).abs() < 1e-12 && (record.v - 0.5).abs() < 1e-12);
+}
+
+#[test]
+fn test_inward_facing_sphere_in_a_bvh() {
+    // Given a sphere with a negative radius, alone in a BVH
+    let sphere = || {
+        Sphere::new(
+            Point3::new(0., 0., -2.),
+            -0.5,
+            Arc::new(crate::lambertian::Lambertian::new(Vec3::new(0.5, 0.5, 0.5))),
+        )
+    };
+    let bvh = crate::bvh::BvhNode::new(vec![Box::new(sphere())]);
+
+    // When a ray hits it directly and through the BVH
+    let ray = Ray::new(Point3::new(0., 0., 0.), Vec3::new(0., 0., -1.));
+    let mut direct = HitRecord::new();
+    let mut through_bvh = HitRecord::new();
+    assert!(sphere().hit(ray, 0.001, f64::INFINITY, &mut direct));
+    assert!(bvh.hit(ray, 0.001, f64::INFINITY, &mut through_bvh));
+
+    // Then both hit it at the same place, facing the same way
+    assert_eq!(through_bvh.t, direct.t);
+    assert_eq!(through_bvh.normal, direct.normal);
+    assert_eq!(through_bvh.is_front_face, direct.is_front_face);
 }
@@ -0,0 +1,213 @@
+use std::collections::HashMap;
+use std::convert::TryFrom;
+use std::error::Error;
+use std::fs::File;
+use std::io::BufReader;
+use std::path::Path;
+use std::sync::Arc;
+
+use crate::{
+    material::Material,
+    point3::Point3,
+    triangle_mesh::{MeshData, TriangleMesh},
+};
+
+// Sizes in a binary STL: the header, the triangle count, and each triangle with its normal and attributes
+const HEADER_SIZE: usize = 80;
+const TRIANGLE_SIZE: usize = 50;
+
+/// Load an STL file as a mesh of one material, see `read_stl`
+pub fn load_stl(
+    path: impl AsRef<Path>,
+    material: Arc<dyn Material>,
+) -> Result<TriangleMesh, Box<dyn Error>> {
+    let path = path.as_ref();
+    let in_file = |error: Box<dyn Error>| format!("{}: {}", path.display(), error);
+    let file = File::open(path).map_err(|error| in_file(error.into()))?;
+    let mesh = read_stl(BufReader::new(file)).map_err(in_file)?;
+    Ok(TriangleMesh::new(mesh, material).map_err(in_file)?)
+}
+
+/// Read an ASCII or binary STL file
+///
+/// Triangles that share a corner share its vertex, and are shaded flat. The stored facet normals are
+/// ignored in favour of the order of the vertices, which is counterclockwise seen from outside.
+pub fn read_stl(mut reader: impl std::io::Read) -> Result<MeshData, Box<dyn Error>> {
+    let mut data = Vec::new();
+    reader.read_to_end(&mut data)?;
+
+    let mut mesh = Mesh::default();
+    // Binary files may start with "solid" too, but then their size gives them away
+    if data.starts_with(b"solid") && !has_binary_size(&data) {
+        read_ascii(&data, &mut mesh)?;
+    } else {
+        read_binary(&data, &mut mesh)?;
+    }
+    Ok(mesh.data)
+}
+
+fn has_binary_size(data: &[u8]) -> bool {
+    match data.get(HEADER_SIZE..HEADER_SIZE + 4) {
+        Some(count) => {
+            let count = u32::from_le_bytes(<[u8; 4]>::try_from(count).unwrap()) as usize;
+            data.len() == HEADER_SIZE + 4 + count * TRIANGLE_SIZE
+        }
+        None => false,
+    }
+}
+
+// A mesh that is being read, with every corner position stored once
+#[derive(Default)]
+struct Mesh {
+    data: MeshData,
+    vertices: HashMap<[u64; 3], usize>,
+}
+
+impl Mesh {
+    fn add_triangle(&mut self, corners: [Point3; 3]) {
+        let mut triangle = [0; 3];
+        for (vertex, corner) in triangle.iter_mut().zip(corners.iter()) {
+            let key = [corner.x.to_bits(), corner.y.to_bits(), corner.z.to_bits()];
+            let positions = &mut self.data.positions;
+            *vertex = *self.vertices.entry(key).or_insert_with(|| {
+                positions.push(*corner);
+                positions.len() - 1
+            });
+        }
+        self.data.triangles.push(triangle);
+    }
+}
+
+fn read_binary(data: &[u8], mesh: &mut Mesh) -> Result<(), Box<dyn Error>> {
+    let count = data
+        .get(HEADER_SIZE..HEADER_SIZE + 4)
+        .ok_or("the file ends in the middle of its header")?;
+    let count = u32::from_le_bytes(<[u8; 4]>::try_from(count).unwrap()) as usize;
+    let triangles = &data[HEADER_SIZE + 4..];
+    if triangles.len() < count * TRIANGLE_SIZE {
+        return Err(format!(
+            "the file should have {} triangles, but ends after {}",
+            count,
+            triangles.len() / TRIANGLE_SIZE
+        )
+        .into());
+    }
+
+    for triangle in triangles.chunks_exact(TRIANGLE_SIZE).take(count) {
+        let value = |i: usize| {
+            let bytes = <[u8; 4]>::try_from(&triangle[4 * i..4 * i + 4]).unwrap();
+            f64::from(f32::from_le_bytes(bytes))
+        };
+        // The normal comes first, then the three corners
+        let corner = |i: usize| Point3::new(value(3 * i), value(3 * i + 1), value(3 * i + 2));
+        mesh.add_triangle([corner(1), corner(2), corner(3)]);
+    }
+    Ok(())
+}
+
+fn read_ascii(data: &[u8], mesh: &mut Mesh) -> Result<(), Box<dyn Error>> {
+    let text = std::str::from_utf8(data).map_err(|_| "the file is neither binary nor text")?;
+    let mut corners = Vec::with_capacity(3);
+    for (i, line) in text.lines().enumerate() {
+        let words: Vec<&str> = line.split_whitespace().collect();
+        let in_line = |message: String| format!("line {}: {}", i + 1, message);
+        match words.as_slice() {
+            ["vertex", coordinates @ ..] => {
+                let coordinates = coordinates
+                    .iter()
+                    .map(|coordinate| {
+                        coordinate.parse::<f64>().map_err(|_| {
+                            in_line(format!("expected a number, got '{}'", coordinate))
+                        })
+                    })
+                    .collect::<Result<Vec<f64>, _>>()?;
+                if coordinates.len() != 3 {
+                    return Err(in_line(format!(
+                        "expected 3 coordinates, got {}",
+                        coordinates.len()
+                    ))
+                    .into());
+                }
+                corners.push(Point3::new(coordinates[0], coordinates[1], coordinates[2]));
+            }
+            ["endloop"] => {
+                if corners.len() != 3 {
+                    return Err(in_line(format!(
+                        "a facet needs 3 vertices, got {}",
+                        corners.len()
+                    ))
+                    .into());
+                }
+                mesh.add_triangle([corners[0], corners[1], corners[2]]);
+                corners.clear();
+            }
+            // Solids, facets with their normals and loops only group the vertices
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+const TEST_SQUARE: &str = "solid square
+  facet normal 0 0 1
+    outer loop
+      vertex 0 0 0
+      vertex 1 0 0
+      vertex 1 1 0
+    endloop
+  endfacet
+  facet normal 0 0 1
+    outer loop
+      vertex 0 0 0
+      vertex 1 1 0
+      vertex 0 1 0
+    endloop
+  endfacet
+endsolid square
+";
+
+#[test]
+fn test_read_ascii_stl() {
+    // Given an ASCII STL of a square made of two triangles
+    let stl = TEST_SQUARE;
+
+    // When I read it
+    let mesh = read_stl(stl.as_bytes()).unwrap();
+
+    // Then the triangles share the corners on their common edge
+    assert_eq!(mesh.positions.len(), 4);
+    assert_eq!(mesh.positions[3], Point3::new(0., 1., 0.));
+    assert_eq!(mesh.triangles, vec![[0, 1, 2], [0, 2, 3]]);
+}
+
+#[test]
+fn test_read_binary_stl_starting_with_solid() {
+    // Given a binary STL of a triangle, with a header that starts like an ASCII one
+    let mut stl = b"solid but binary".to_vec();
+    stl.resize(HEADER_SIZE, b' ');
+    stl.extend_from_slice(&1_u32.to_le_bytes());
+    for value in [0., 0., 1., 0., 0., 0., 2., 0., 0., 0., 2., 0.].iter() {
+        stl.extend_from_slice(&f32::to_le_bytes(*value));
+    }
+    stl.extend_from_slice(&[0, 0]);
+
+    // When I read it
+    let mesh = read_stl(&stl[..]).unwrap();
+
+    // Then it has the triangle
+    assert_eq!(mesh.positions[2], Point3::new(0., 2., 0.));
+    assert_eq!(mesh.triangles, vec![[0, 1, 2]]);
+}
+
+#[test]
+fn test_read_broken_ascii_stl() {
+    // Given an ASCII STL with a facet of two vertices
+    let stl = TEST_SQUARE.replacen("      vertex 1 1 0\n", "", 1);
+
+    // When I read it
+    let error = read_stl(stl.as_bytes()).err().unwrap();
+
+    // Then the error says where the facet ends
+    assert_eq!(error.to_string(), "line 6: a facet needs 3 vertices, got 2");
+}
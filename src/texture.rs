@@ -0,0 +1,12 @@
+use crate::{color::Color, point3::Point3, scene_file::TextureDescription};
+
+/// A color that varies over a surface, looked up by the surface coordinates `u` and `v` of a hit or by
+/// the point itself. Textures are shared between render threads, so they have to be `Send + Sync`.
+pub trait Texture: Send + Sync {
+    fn value(&self, u: f64, v: f64, point: Point3) -> Color;
+
+    /// The texture as it is written in scene files, or `None` for textures that can't be
+    fn describe(&self) -> Option<TextureDescription> {
+        None
+    }
+}
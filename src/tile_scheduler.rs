@@ -0,0 +1,323 @@
+// Sys
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+
+// 3rd party
+use rayon::ThreadPool;
+
+// Project
+use crate::framebuffer::{Framebuffer, Pixel};
+
+/// A rectangle of pixels, `x` and `y` being the column and row of its top left pixel
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Tile {
+    pub x: usize,
+    pub y: usize,
+    pub width: usize,
+    pub height: usize,
+}
+
+impl Tile {
+    pub fn new(x: usize, y: usize, width: usize, height: usize) -> Self {
+        Self {
+            x,
+            y,
+            width,
+            height,
+        }
+    }
+}
+
+/// The order in which tiles are handed out to the render threads
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TileOrder {
+    /// Row by row, from the top left to the bottom right
+    Scanline,
+    /// Outward from the center of the image, so the interesting part shows up first
+    Spiral,
+    /// Along a Hilbert curve, so consecutive tiles are mostly adjacent, except where the curve leaves
+    /// the image and comes back in
+    Hilbert,
+}
+
+/// Splits an image into tiles and renders them on a thread pool, compositing the results into one framebuffer
+#[derive(Debug, Clone, Copy)]
+pub struct TileScheduler {
+    image_width: usize,
+    image_height: usize,
+    tile_size: usize,
+    order: TileOrder,
+}
+
+impl TileScheduler {
+    pub fn new(
+        image_width: usize,
+        image_height: usize,
+        tile_size: usize,
+        order: TileOrder,
+    ) -> Self {
+        Self {
+            image_width,
+            image_height,
+            tile_size: tile_size.max(1),
+            order,
+        }
+    }
+
+    fn columns(&self) -> usize {
+        self.image_width.div_ceil(self.tile_size)
+    }
+
+    fn rows(&self) -> usize {
+        self.image_height.div_ceil(self.tile_size)
+    }
+
+    // The tile at the given column and row of the tile grid, clipped to the image
+    fn tile_at(&self, column: usize, row: usize) -> Tile {
+        let x = column * self.tile_size;
+        let y = row * self.tile_size;
+        Tile::new(
+            x,
+            y,
+            self.tile_size.min(self.image_width - x),
+            self.tile_size.min(self.image_height - y),
+        )
+    }
+
+    /// All tiles covering the image, in the order they will be rendered
+    pub fn tiles(&self) -> Vec<Tile> {
+        let (columns, rows) = (self.columns(), self.rows());
+        let grid_positions = match self.order {
+            TileOrder::Scanline => (0..rows)
+                .flat_map(|row| (0..columns).map(move |column| (column, row)))
+                .collect(),
+            TileOrder::Spiral => spiral_order(columns, rows),
+            TileOrder::Hilbert => hilbert_order(columns, rows),
+        };
+
+        grid_positions
+            .into_iter()
+            .map(|(column, row)| self.tile_at(column, row))
+            .collect()
+    }
+
+    /// Render every tile on the pool and return the framebuffer they were composited into
+    ///
+    /// `render_tile` fills the pixels of a tile, row by row. `on_tile` is called with the number of
+    /// tiles that are left whenever a tile has been composited.
+    pub fn run(
+        &self,
+        pool: &ThreadPool,
+        render_tile: impl Fn(Tile, &mut [Pixel]) + Sync,
+        on_tile: impl Fn(usize) + Sync,
+    ) -> Framebuffer {
+        let tiles = self.tiles();
+        let next_tile = AtomicUsize::new(0);
+        let tiles_remaining = AtomicUsize::new(tiles.len());
+        let framebuffer = Mutex::new(Framebuffer::new(self.image_width, self.image_height));
+
+        // Every worker keeps taking the next tile in line until there are none left
+        pool.broadcast(|_| {
+            let mut pixels = Vec::with_capacity(self.tile_size * self.tile_size);
+            while let Some(&tile) = tiles.get(next_tile.fetch_add(1, Ordering::Relaxed)) {
+                pixels.clear();
+                pixels.resize(tile.width * tile.height, Pixel::default());
+                render_tile(tile, &mut pixels);
+
+                {
+                    let mut framebuffer = framebuffer.lock().unwrap();
+                    for (i, pixel) in pixels.iter().enumerate() {
+                        *framebuffer.pixel_mut(tile.x + i % tile.width, tile.y + i / tile.width) =
+                            *pixel;
+                    }
+                }
+
+                on_tile(tiles_remaining.fetch_sub(1, Ordering::Relaxed) - 1);
+            }
+        });
+
+        framebuffer.into_inner().unwrap()
+    }
+}
+
+// Walk outward from the center of the grid: right, down, left, up, with legs growing by one every two turns
+fn spiral_order(columns: usize, rows: usize) -> Vec<(usize, usize)> {
+    let mut order = Vec::with_capacity(columns * rows);
+    let (mut column, mut row) = ((columns as i64 - 1) / 2, (rows as i64 - 1) / 2);
+    let directions = [(1, 0), (0, 1), (-1, 0), (0, -1)];
+    let mut leg_length = 1;
+    let mut direction = 0;
+
+    while order.len() < columns * rows {
+        for _ in 0..2 {
+            let (dx, dy) = directions[direction % 4];
+            for _ in 0..leg_length {
+                if column >= 0 && row >= 0 && (column as usize) < columns && (row as usize) < rows {
+                    order.push((column as usize, row as usize));
+                }
+                column += dx;
+                row += dy;
+            }
+            direction += 1;
+        }
+        leg_length += 1;
+    }
+
+    order
+}
+
+// Visit the grid along a Hilbert curve covering the smallest power of two square around it
+fn hilbert_order(columns: usize, rows: usize) -> Vec<(usize, usize)> {
+    let size = columns.max(rows).next_power_of_two();
+    (0..size * size)
+        .map(|distance| hilbert_position(size, distance))
+        .filter(|&(column, row)| column < columns && row < rows)
+        .collect()
+}
+
+// Convert a distance along the Hilbert curve of a `size` by `size` grid into a grid position
+fn hilbert_position(size: usize, distance: usize) -> (usize, usize) {
+    let (mut x, mut y) = (0, 0);
+    let mut t = distance;
+    let mut s = 1;
+    while s < size {
+        let rx = 1 & (t / 2);
+        let ry = 1 & (t ^ rx);
+
+        // Rotate the quadrant
+        if ry == 0 {
+            if rx == 1 {
+                x = s - 1 - x;
+                y = s - 1 - y;
+            }
+            std::mem::swap(&mut x, &mut y);
+        }
+
+        x += s * rx;
+        y += s * ry;
+        t /= 4;
+        s *= 2;
+    }
+    (x, y)
+}
+
+#[cfg(test)]
+fn assert_covers_image_once(tiles: &[Tile], image_width: usize, image_height: usize) {
+    let mut covered = vec![vec![0; image_width]; image_height];
+    for tile in tiles {
+        for row in covered.iter_mut().skip(tile.y).take(tile.height) {
+            for count in row.iter_mut().skip(tile.x).take(tile.width) {
+                *count += 1;
+            }
+        }
+    }
+    assert!(covered.iter().flatten().all(|&count| count == 1));
+}
+
+#[test]
+fn test_tiles_cover_image() {
+    for &order in &[TileOrder::Scanline, TileOrder::Spiral, TileOrder::Hilbert] {
+        // Given a scheduler whose tiles don't fit the image exactly
+        let scheduler = TileScheduler::new(37, 21, 8, order);
+
+        // When I ask for its tiles
+        let tiles = scheduler.tiles();
+
+        // Then every pixel is in exactly one tile
+        assert_eq!(tiles.len(), 5 * 3);
+        assert_covers_image_once(&tiles, 37, 21);
+    }
+}
+
+#[test]
+fn test_scanline_order() {
+    // Given a scheduler with scanline ordering
+    let scheduler = TileScheduler::new(20, 20, 10, TileOrder::Scanline);
+
+    // When I ask for its tiles
+    let tiles = scheduler.tiles();
+
+    // Then they go row by row
+    assert_eq!(
+        tiles,
+        vec![
+            Tile::new(0, 0, 10, 10),
+            Tile::new(10, 0, 10, 10),
+            Tile::new(0, 10, 10, 10),
+            Tile::new(10, 10, 10, 10),
+        ]
+    );
+}
+
+#[test]
+fn test_spiral_order() {
+    // Given a scheduler with spiral ordering on a 3 by 3 grid
+    let scheduler = TileScheduler::new(3, 3, 1, TileOrder::Spiral);
+
+    // When I ask for its tiles
+    let positions: Vec<(usize, usize)> = scheduler.tiles().iter().map(|t| (t.x, t.y)).collect();
+
+    // Then they start at the center and wind outward
+    assert_eq!(
+        positions,
+        vec![
+            (1, 1),
+            (2, 1),
+            (2, 2),
+            (1, 2),
+            (0, 2),
+            (0, 1),
+            (0, 0),
+            (1, 0),
+            (2, 0)
+        ]
+    );
+}
+
+#[test]
+fn test_hilbert_order() {
+    // Given a scheduler with Hilbert ordering on a grid that is not a power of two
+    let scheduler = TileScheduler::new(6, 5, 1, TileOrder::Hilbert);
+
+    // When I ask for its tiles
+    let tiles = scheduler.tiles();
+
+    // Then it starts in the top left corner and the tiles cover the image
+    assert_eq!(tiles[0], Tile::new(0, 0, 1, 1));
+    assert_covers_image_once(&tiles, 6, 5);
+}
+
+#[test]
+fn test_run_composites_tiles() {
+    use crate::color::Color;
+
+    // Given a scheduler and a pool
+    let scheduler = TileScheduler::new(7, 5, 3, TileOrder::Hilbert);
+    let pool = rayon::ThreadPoolBuilder::new()
+        .num_threads(3)
+        .build()
+        .unwrap();
+
+    // When I run it with tiles that color each pixel by its position
+    let framebuffer = scheduler.run(
+        &pool,
+        |tile, pixels| {
+            for (i, pixel) in pixels.iter_mut().enumerate() {
+                let x = tile.x + i % tile.width;
+                let y = tile.y + i / tile.width;
+                *pixel = Pixel::new(Color::new(x as f64, y as f64, 0.));
+            }
+        },
+        |_| {},
+    );
+
+    // Then every pixel ends up in its place in the framebuffer
+    for y in 0..5 {
+        for x in 0..7 {
+            assert_eq!(
+                framebuffer.pixel(x, y).color,
+                Color::new(x as f64, y as f64, 0.)
+            );
+        }
+    }
+}
@@ -0,0 +1,198 @@
+use std::error::Error;
+use std::f64::consts::PI;
+use std::sync::Arc;
+
+use crate::{
+    aabb::Aabb,
+    cylinder::{around_y_axis, record_closest},
+    hittable::{HitRecord, Hittable},
+    material::Material,
+    point3::Point3,
+    polynomial::{solve_quadratic, solve_quartic},
+    ray::Ray,
+    scene_file::{ExportedMaterials, ObjectDescription},
+    vec3::Vec3,
+};
+
+/// A ring around the y axis through `center`, like a tire or a donut: a tube of `minor_radius` going
+/// around a circle of `major_radius`
+///
+/// The texture coordinates go around the ring from the -x side and around the tube from its inside.
+#[derive(Clone)]
+pub struct Torus {
+    center: Point3,
+    major_radius: f64,
+    minor_radius: f64,
+    material: Arc<dyn Material>,
+}
+
+impl Torus {
+    pub fn new(
+        center: Point3,
+        major_radius: f64,
+        minor_radius: f64,
+        material: Arc<dyn Material>,
+    ) -> Self {
+        Self {
+            center,
+            major_radius,
+            minor_radius,
+            material,
+        }
+    }
+}
+
+impl Hittable for Torus {
+    // The surface is where (x² + y² + z² + R² - r²)² = 4R²(x² + z²), a quartic along the ray
+    fn hit(&self, ray: Ray, t_min: f64, t_max: f64, record: &mut HitRecord) -> bool {
+        let (major, minor) = (self.major_radius, self.minor_radius);
+        let bound = major + minor;
+
+        // The coefficients lose their digits far from the torus, so the ray is first moved up to
+        // where it enters the sphere around it, which also skips the quartic for most rays that miss
+        let oc = ray.origin - self.center;
+        let a = ray.direction.len_squared();
+        let half_b = oc.dot(ray.direction);
+        let entry = match solve_quadratic(a, 2. * half_b, oc.len_squared() - bound * bound) {
+            Some((entry, exit)) if exit >= t_min && entry <= t_max => entry.max(0.),
+            _ => return false,
+        };
+        let o = oc + entry * ray.direction;
+        let d = ray.direction;
+
+        let od = o.dot(d);
+        let k = o.len_squared() + major * major - minor * minor;
+        let four_r2 = 4. * major * major;
+        let roots = solve_quartic(
+            a * a,
+            4. * a * od,
+            4. * od * od + 2. * a * k - four_r2 * (d.x * d.x + d.z * d.z),
+            4. * od * k - 2. * four_r2 * (o.x * d.x + o.z * d.z),
+            k * k - four_r2 * (o.x * o.x + o.z * o.z),
+        );
+
+        let hits = roots.into_iter().map(|t| {
+            let point = o + t * d;
+            // The normal points away from the nearest point on the circle through the tube
+            let around = Vec3::new(point.x, 0., point.z);
+            let ring = if around.is_near_zero() {
+                Vec3::new(0., 0., 0.)
+            } else {
+                major * around.unit_vector()
+            };
+            let normal = (point - ring).unit_vector();
+            let tube = f64::atan2(point.y, around.len() - major);
+            (
+                t + entry,
+                normal,
+                around_y_axis(point),
+                (tube + PI) / (2. * PI),
+            )
+        });
+
+        record_closest(hits, ray, t_min, t_max, &self.material, record)
+    }
+
+    fn bounding_box(&self, output_box: &mut Aabb) -> bool {
+        let extent = Vec3::new(
+            self.major_radius + self.minor_radius,
+            self.minor_radius,
+            self.major_radius + self.minor_radius,
+        );
+        *output_box = Aabb::new(self.center - extent, self.center + extent);
+        true
+    }
+
+    fn export(
+        &self,
+        materials: &mut ExportedMaterials,
+    ) -> Result<ObjectDescription, Box<dyn Error>> {
+        Ok(ObjectDescription::Torus {
+            center: self.center,
+            major_radius: self.major_radius,
+            minor_radius: self.minor_radius,
+            material: materials.name(&self.material)?,
+        })
+    }
+}
+
+#[test]
+fn test_torus_is_hit_on_both_sides_of_its_hole() {
+    // Given a torus around the y axis, with its tube from 2 to 4 out, far away from the camera
+    let torus = Torus::new(
+        Point3::new(0., 0., -1000.),
+        3.,
+        1.,
+        Arc::new(crate::lambertian::Lambertian::new(Vec3::new(0.5, 0.5, 0.5))),
+    );
+    let hit = |origin: Point3, direction: Vec3, t_min: f64| {
+        let mut record = HitRecord::new();
+        let hit = torus.hit(
+            Ray::new(origin, direction),
+            t_min,
+            f64::INFINITY,
+            &mut record,
+        );
+        (hit, record)
+    };
+
+    // When rays come through it along x, through the hole along y and from far away along z
+    let (across_hit, across) = hit(Point3::new(-10., 0., -1000.), Vec3::new(1., 0., 0.), 0.001);
+    let (past_first_hit, past_first) =
+        hit(Point3::new(-10., 0., -1000.), Vec3::new(1., 0., 0.), 6.5);
+    let (hole_hit, _) = hit(Point3::new(0., -10., -1000.), Vec3::new(0., 1., 0.), 0.001);
+    let (far_hit, far) = hit(Point3::new(0., 0., 0.), Vec3::new(0., 0., -1.), 0.001);
+
+    // Then the ray along x hits the outside of the tube, and then leaves it from the inside
+    assert!(across_hit && past_first_hit && !hole_hit && far_hit);
+    assert!((across.t - 6.).abs() < 1e-9);
+    assert!((across.normal - Vec3::new(-1., 0., 0.)).len() < 1e-9);
+    assert!(across.u.abs() < 1e-9 && (across.v - 0.5).abs() < 1e-9);
+    assert!((past_first.t - 8.).abs() < 1e-9);
+    assert!(!past_first.is_front_face);
+
+    // And the ray from far away hits the near side of the ring precisely
+    assert!((far.t - 996.).abs() < 1e-9);
+    assert!((far.normal - Vec3::new(0., 0., 1.)).len() < 1e-9);
+}
+
+#[test]
+fn test_torus_at_any_scale_and_grazing_rays() {
+    for &scale in [1e-5, 1e-3, 1., 1e3, 1e5].iter() {
+        // Given a torus with its tube from 2 to 4 out, made smaller or bigger along with the rays
+        let torus = Torus::new(
+            Point3::new(0., 0., -10. * scale),
+            3. * scale,
+            scale,
+            Arc::new(crate::lambertian::Lambertian::new(Vec3::new(0.5, 0.5, 0.5))),
+        );
+        let hit = |height: f64, direction: Vec3| {
+            let mut record = HitRecord::new();
+            let origin = Point3::new(-10. * scale, height * scale, -10. * scale);
+            let ray = Ray::new(origin, direction);
+            if torus.hit(ray, 1e-3 * scale, f64::INFINITY, &mut record) {
+                Some(record.t / scale)
+            } else {
+                None
+            }
+        };
+
+        // When rays come down at it slanted, and go across it just below the top of the tube and just
+        // above it
+        let slanted = hit(4., Vec3::new(2., -1., 0.)).unwrap();
+        let grazing = hit(1. - 1e-6, Vec3::new(1., 0., 0.)).unwrap();
+        let above = hit(1. + 1e-6, Vec3::new(1., 0., 0.));
+
+        // Then they hit it as far along as they would at any other size, and the ray that only just
+        // passes over the tube misses it
+        assert!((slanted - 3.2).abs() < 1e-9, "{} at {}", slanted, scale);
+        let grazing_width = (1. - (1. - 1e-6_f64).powi(2)).sqrt();
+        assert!(
+            (grazing - (7. - grazing_width)).abs() < 1e-9,
+            "{} at {}",
+            grazing,
+            scale
+        );
+        assert_eq!(above, None);
+    }
+}
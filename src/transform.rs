@@ -0,0 +1,221 @@
+use std::error::Error;
+
+use crate::{aabb::Aabb, mat4::Mat4, point3::Point3, vec3::Vec3};
+
+/// An affine transform and the one that undoes it, to move objects around, turn and resize them
+///
+/// Transforms are chained with `then`, so `Transform::scaling(s).then(Transform::translation(t))`
+/// first scales and then moves.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Transform {
+    matrix: Mat4,
+    inverse: Mat4,
+}
+
+impl Transform {
+    pub fn identity() -> Self {
+        Self {
+            matrix: Mat4::identity(),
+            inverse: Mat4::identity(),
+        }
+    }
+
+    /// A transform by any matrix that can be undone, with a last row of 0, 0, 0, 1
+    pub fn from_matrix(matrix: Mat4) -> Result<Self, Box<dyn Error>> {
+        if matrix.rows[3] != [0., 0., 0., 1.] {
+            return Err(format!(
+                "expected an affine matrix with a last row of [0, 0, 0, 1], got {:?}",
+                matrix.rows[3]
+            )
+            .into());
+        }
+        let inverse = matrix
+            .inverse()
+            .ok_or("the matrix flattens space, so it can't be undone")?;
+        Ok(Self { matrix, inverse })
+    }
+
+    pub fn translation(offset: Vec3) -> Self {
+        let matrix = |offset: Vec3| {
+            Mat4::new([
+                [1., 0., 0., offset.x],
+                [0., 1., 0., offset.y],
+                [0., 0., 1., offset.z],
+                [0., 0., 0., 1.],
+            ])
+        };
+        Self {
+            matrix: matrix(offset),
+            inverse: matrix(-offset),
+        }
+    }
+
+    /// A rotation counterclockwise around `axis`, seen from where it points to
+    pub fn rotation(axis: Vec3, degrees: f64) -> Self {
+        let axis = axis.unit_vector();
+        let (sin, cos) = degrees.to_radians().sin_cos();
+        let (x, y, z) = (axis.x, axis.y, axis.z);
+        let t = 1. - cos;
+        let matrix = Mat4::new([
+            [
+                t * x * x + cos,
+                t * x * y - sin * z,
+                t * x * z + sin * y,
+                0.,
+            ],
+            [
+                t * x * y + sin * z,
+                t * y * y + cos,
+                t * y * z - sin * x,
+                0.,
+            ],
+            [
+                t * x * z - sin * y,
+                t * y * z + sin * x,
+                t * z * z + cos,
+                0.,
+            ],
+            [0., 0., 0., 1.],
+        ]);
+        // Rotations are undone by rotating back, which is the transpose
+        Self {
+            matrix,
+            inverse: matrix.transpose(),
+        }
+    }
+
+    /// Resize by a factor along each axis, where none of them can be 0
+    pub fn scaling(factors: Vec3) -> Self {
+        let matrix = |factors: Vec3| {
+            Mat4::new([
+                [factors.x, 0., 0., 0.],
+                [0., factors.y, 0., 0.],
+                [0., 0., factors.z, 0.],
+                [0., 0., 0., 1.],
+            ])
+        };
+        Self {
+            matrix: matrix(factors),
+            inverse: matrix(Vec3::new(1. / factors.x, 1. / factors.y, 1. / factors.z)),
+        }
+    }
+
+    /// This transform followed by `next`
+    pub fn then(self, next: Transform) -> Self {
+        Self {
+            matrix: next.matrix * self.matrix,
+            inverse: self.inverse * next.inverse,
+        }
+    }
+
+    pub fn inverse(self) -> Self {
+        Self {
+            matrix: self.inverse,
+            inverse: self.matrix,
+        }
+    }
+
+    pub fn matrix(&self) -> Mat4 {
+        self.matrix
+    }
+
+    pub fn apply_to_point(&self, point: Point3) -> Point3 {
+        self.matrix.transform_point(point)
+    }
+
+    pub fn apply_to_vector(&self, vector: Vec3) -> Vec3 {
+        self.matrix.transform_vector(vector)
+    }
+
+    /// Transform a surface normal, which has to stay perpendicular to the surface rather than turn with
+    /// it, so it goes through the transpose of the inverse. The result isn't of unit length.
+    pub fn apply_to_normal(&self, normal: Vec3) -> Vec3 {
+        self.inverse.transpose().transform_vector(normal)
+    }
+
+    /// The smallest box around the transformed corners of a box
+    pub fn apply_to_box(&self, aabb: Aabb) -> Aabb {
+        let mut corners = (0..8).map(|i| {
+            let pick = |bit: u8, axis: u8| {
+                if i & (1 << bit) == 0 {
+                    aabb.minimum[axis]
+                } else {
+                    aabb.maximum[axis]
+                }
+            };
+            let corner = self.apply_to_point(Point3::new(pick(0, 0), pick(1, 1), pick(2, 2)));
+            Aabb::new(corner, corner)
+        });
+        let first = corners.next().unwrap();
+        corners.fold(first, Aabb::surrounding)
+    }
+}
+
+#[cfg(test)]
+fn assert_near(actual: Vec3, expected: Vec3) {
+    assert!(
+        (actual - expected).len() < 1e-12,
+        "{} isn't {}",
+        actual,
+        expected
+    );
+}
+
+#[test]
+fn test_chained_transforms() {
+    // Given a transform that doubles sizes, turns a quarter around the y axis and moves up
+    let transform = Transform::scaling(Vec3::new(2., 2., 2.))
+        .then(Transform::rotation(Vec3::new(0., 1., 0.), 90.))
+        .then(Transform::translation(Vec3::new(0., 1., 0.)));
+
+    // When I apply it to a point on the x axis, and to a direction
+    let point = transform.apply_to_point(Point3::new(1., 0., 0.));
+    let vector = transform.apply_to_vector(Vec3::new(1., 0., 0.));
+
+    // Then the point ends up on the -z axis twice as far out and moved up, and the direction isn't moved
+    assert_near(point, Point3::new(0., 1., -2.));
+    assert_near(vector, Vec3::new(0., 0., -2.));
+
+    // And the inverse takes it back
+    assert_near(
+        transform.inverse().apply_to_point(point),
+        Point3::new(1., 0., 0.),
+    );
+}
+
+#[test]
+fn test_normals_stay_perpendicular() {
+    // Given a transform that stretches along x, and the surface normal of a slope
+    let transform = Transform::scaling(Vec3::new(4., 1., 1.));
+    let normal = Vec3::new(1., 1., 0.);
+    let along_surface = Vec3::new(1., -1., 0.);
+
+    // When I transform both
+    let transformed_normal = transform.apply_to_normal(normal);
+    let transformed_surface = transform.apply_to_vector(along_surface);
+
+    // Then the normal is still perpendicular to the surface
+    assert!(transformed_normal.dot(transformed_surface).abs() < 1e-12);
+}
+
+#[test]
+fn test_transformed_box() {
+    // Given a unit cube
+    let cube = Aabb::new(Point3::new(0., 0., 0.), Point3::new(1., 1., 1.));
+
+    // When I turn it 45 degrees around the z axis
+    let turned = Transform::rotation(Vec3::new(0., 0., 1.), 45.).apply_to_box(cube);
+
+    // Then the box around it is wider, to fit the corners
+    let half_diagonal = 2_f64.sqrt() / 2.;
+    assert_near(turned.minimum, Point3::new(-half_diagonal, 0., 0.));
+    assert_near(
+        turned.maximum,
+        Point3::new(half_diagonal, 2. * half_diagonal, 1.),
+    );
+
+    // And a matrix that isn't affine is rejected
+    let mut matrix = Mat4::identity();
+    matrix.rows[3][0] = 1.;
+    assert!(Transform::from_matrix(matrix).is_err());
+}
@@ -0,0 +1,146 @@
+use std::error::Error;
+use std::sync::Arc;
+
+use crate::{
+    aabb::Aabb,
+    hittable::{HitRecord, Hittable},
+    point3::Point3,
+    ray::Ray,
+    scene_file::{ExportedMaterials, ObjectDescription, TransformStep},
+    transform::Transform,
+};
+
+/// An instance of an object, moved, turned or resized by a transform
+///
+/// The object is shared, so a mesh can be placed any number of times while its triangles are stored
+/// once. Rays are taken into the space of the object to hit it, and the hit is brought back.
+pub struct Transformed<H: Hittable + ?Sized> {
+    object: Arc<H>,
+    transform: Transform,
+    // The box around the transformed object, worked out once for the BVH
+    bounding_box: Option<Aabb>,
+}
+
+impl<H: Hittable + ?Sized> Transformed<H> {
+    pub fn new(object: Arc<H>, transform: Transform) -> Self {
+        let origin = Point3::new(0., 0., 0.);
+        let mut object_box = Aabb::new(origin, origin);
+        let bounding_box = if object.bounding_box(&mut object_box) {
+            Some(transform.apply_to_box(object_box))
+        } else {
+            None
+        };
+        Self {
+            object,
+            transform,
+            bounding_box,
+        }
+    }
+
+    pub fn object(&self) -> &Arc<H> {
+        &self.object
+    }
+
+    pub fn transform(&self) -> Transform {
+        self.transform
+    }
+}
+
+impl<H: Hittable + ?Sized> Hittable for Transformed<H> {
+    fn hit(&self, ray: Ray, t_min: f64, t_max: f64, record: &mut HitRecord) -> bool {
+        // The direction isn't normalized, so t along the object ray is the same t along the ray
+        let inverse = self.transform.inverse();
+        let object_ray = Ray::at_time(
+            inverse.apply_to_point(ray.origin),
+            inverse.apply_to_vector(ray.direction),
+            ray.time,
+        );
+        if !self.object.hit(object_ray, t_min, t_max, record) {
+            return false;
+        }
+
+        // The normal already faces the ray, and keeps facing it through the transform
+        record.point = self.transform.apply_to_point(record.point);
+        record.normal = self.transform.apply_to_normal(record.normal).unit_vector();
+        true
+    }
+
+    fn bounding_box(&self, output_box: &mut Aabb) -> bool {
+        match self.bounding_box {
+            Some(bounding_box) => {
+                *output_box = bounding_box;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Written with the object in full, even when it is shared with other instances
+    fn export(
+        &self,
+        materials: &mut ExportedMaterials,
+    ) -> Result<ObjectDescription, Box<dyn Error>> {
+        Ok(ObjectDescription::Transformed {
+            transform: vec![TransformStep::Matrix(self.transform.matrix())],
+            object: Box::new(self.object.export(materials)?),
+        })
+    }
+}
+
+#[cfg(test)]
+fn unit_sphere() -> Arc<crate::sphere::Sphere> {
+    Arc::new(crate::sphere::Sphere::new(
+        Point3::new(0., 0., 0.),
+        1.,
+        Arc::new(crate::lambertian::Lambertian::new(crate::vec3::Vec3::new(
+            0.5, 0.5, 0.5,
+        ))),
+    ))
+}
+
+#[test]
+fn test_transformed_sphere_is_hit_where_it_was_moved() {
+    // Given a unit sphere stretched to twice its size along x and moved 5 along x
+    let sphere = Transformed::new(
+        unit_sphere(),
+        Transform::scaling(crate::vec3::Vec3::new(2., 1., 1.))
+            .then(Transform::translation(crate::vec3::Vec3::new(5., 0., 0.))),
+    );
+
+    // When a ray comes at it along the x axis
+    let mut record = HitRecord::new();
+    let ray = Ray::new(Point3::new(0., 0., 0.), crate::vec3::Vec3::new(1., 0., 0.));
+    assert!(sphere.hit(ray, 0.001, f64::INFINITY, &mut record));
+
+    // Then it hits the stretched side facing it, with a normal pointing back
+    assert!((record.t - 3.).abs() < 1e-12);
+    assert!((record.point - Point3::new(3., 0., 0.)).len() < 1e-12);
+    assert!((record.normal - crate::vec3::Vec3::new(-1., 0., 0.)).len() < 1e-12);
+    assert!(record.is_front_face);
+}
+
+#[test]
+fn test_instances_share_the_object() {
+    // Given a sphere placed twice
+    let sphere = unit_sphere();
+    let left = Transformed::new(
+        Arc::clone(&sphere),
+        Transform::translation(crate::vec3::Vec3::new(-3., 0., 0.)),
+    );
+    let right = Transformed::new(
+        Arc::clone(&sphere),
+        Transform::translation(crate::vec3::Vec3::new(3., 0., 0.)),
+    );
+
+    // When I get the boxes around the instances
+    let mut left_box = Aabb::new(Point3::new(0., 0., 0.), Point3::new(0., 0., 0.));
+    let mut right_box = left_box;
+    assert!(left.bounding_box(&mut left_box));
+    assert!(right.bounding_box(&mut right_box));
+
+    // Then they are where the instances are, while there is one sphere
+    assert_eq!(left_box.minimum, Point3::new(-4., -1., -1.));
+    assert_eq!(right_box.maximum, Point3::new(4., 1., 1.));
+    assert!(Arc::ptr_eq(left.object(), right.object()));
+    assert_eq!(Arc::strong_count(&sphere), 3);
+}
@@ -0,0 +1,203 @@
+use std::error::Error;
+use std::sync::Arc;
+
+use crate::{
+    aabb::Aabb,
+    hittable::{HitRecord, Hittable},
+    material::Material,
+    point3::Point3,
+    ray::Ray,
+    scene_file::{ExportedMaterials, ObjectDescription},
+    vec3::Vec3,
+};
+
+// How thick the bounding box of a triangle is at least, so that triangles facing along an axis can be hit
+pub(crate) const BOUNDING_BOX_PADDING: f64 = 1e-4;
+
+/// A single flat triangle. The texture coordinates of a hit are how far it is towards the second and
+/// the third vertex. For many triangles sharing vertices, use a `TriangleMesh`.
+#[derive(Clone)]
+pub struct Triangle {
+    vertices: [Point3; 3],
+    material: Arc<dyn Material>,
+}
+
+impl Triangle {
+    pub fn new(a: Point3, b: Point3, c: Point3, material: Arc<dyn Material>) -> Self {
+        Self {
+            vertices: [a, b, c],
+            material,
+        }
+    }
+}
+
+// Where a ray crosses a triangle, and how far that is along its edges
+pub(crate) struct TriangleHit {
+    pub t: f64,
+    /// Weight of the second vertex
+    pub b1: f64,
+    /// Weight of the third vertex
+    pub b2: f64,
+}
+
+// Möller–Trumbore intersection: solve origin + t * direction = v0 + b1 * (v1 - v0) + b2 * (v2 - v0)
+// for t, b1 and b2 with Cramer's rule, where the point is inside when b1, b2 and 1 - b1 - b2 are all
+// positive. Points on an edge count as inside, so there are no gaps between triangles sharing it.
+pub(crate) fn intersect_triangle(
+    ray: Ray,
+    vertices: [Point3; 3],
+    t_min: f64,
+    t_max: f64,
+) -> Option<TriangleHit> {
+    let edge1 = vertices[1] - vertices[0];
+    let edge2 = vertices[2] - vertices[0];
+    let p = ray.direction.cross(edge2);
+    let determinant = edge1.dot(p);
+
+    // The ray is parallel to the triangle, or the triangle has no area
+    if determinant.abs() < 1e-12 * edge1.len_squared().max(edge2.len_squared()) {
+        return None;
+    }
+    let inverse_determinant = 1. / determinant;
+
+    let s = ray.origin - vertices[0];
+    let b1 = s.dot(p) * inverse_determinant;
+    if !(0. ..=1.).contains(&b1) {
+        return None;
+    }
+    let q = s.cross(edge1);
+    let b2 = ray.direction.dot(q) * inverse_determinant;
+    if b2 < 0. || b1 + b2 > 1. {
+        return None;
+    }
+
+    let t = edge2.dot(q) * inverse_determinant;
+    if t < t_min || t_max < t {
+        return None;
+    }
+    Some(TriangleHit { t, b1, b2 })
+}
+
+// The box around a triangle, padded to be hittable when the triangle lies along an axis
+pub(crate) fn triangle_bounding_box(vertices: [Point3; 3]) -> Aabb {
+    Aabb::new(vertices[0], vertices[0])
+        .surrounding(Aabb::new(vertices[1], vertices[1]))
+        .surrounding(Aabb::new(vertices[2], vertices[2]))
+        .padded(BOUNDING_BOX_PADDING)
+}
+
+// The normal of the side the vertices go around counterclockwise
+pub(crate) fn triangle_normal(vertices: [Point3; 3]) -> Vec3 {
+    (vertices[1] - vertices[0])
+        .cross(vertices[2] - vertices[0])
+        .unit_vector()
+}
+
+impl Hittable for Triangle {
+    fn hit(&self, ray: Ray, t_min: f64, t_max: f64, record: &mut HitRecord) -> bool {
+        let hit = match intersect_triangle(ray, self.vertices, t_min, t_max) {
+            Some(hit) => hit,
+            None => return false,
+        };
+
+        record.t = hit.t;
+        record.point = ray.at(hit.t);
+        record.u = hit.b1;
+        record.v = hit.b2;
+        record.set_face_normal(ray, triangle_normal(self.vertices));
+        record.material = Arc::clone(&self.material);
+
+        true
+    }
+
+    fn bounding_box(&self, output_box: &mut Aabb) -> bool {
+        *output_box = triangle_bounding_box(self.vertices);
+        true
+    }
+
+    fn export(
+        &self,
+        materials: &mut ExportedMaterials,
+    ) -> Result<ObjectDescription, Box<dyn Error>> {
+        Ok(ObjectDescription::Triangle {
+            vertices: self.vertices,
+            material: materials.name(&self.material)?,
+        })
+    }
+}
+
+#[cfg(test)]
+fn test_triangle() -> Triangle {
+    use crate::{color::Color, lambertian::Lambertian};
+
+    Triangle::new(
+        Point3::new(0., 0., 0.),
+        Point3::new(1., 0., 0.),
+        Point3::new(0., 1., 0.),
+        Arc::new(Lambertian::new(Color::new(0.5, 0.5, 0.5))),
+    )
+}
+
+#[test]
+fn test_triangle_hit() {
+    // Given a triangle facing +z
+    let triangle = test_triangle();
+
+    // When a ray comes at it from the front
+    let ray = Ray::new(Point3::new(0.25, 0.5, 2.), Vec3::new(0., 0., -1.));
+    let mut record = HitRecord::new();
+
+    // Then it hits the front face, with how far along the edges it hit as texture coordinates
+    assert!(triangle.hit(ray, 0.001, f64::INFINITY, &mut record));
+    assert_eq!(record.t, 2.);
+    assert_eq!(record.point, Point3::new(0.25, 0.5, 0.));
+    assert_eq!(record.normal, Vec3::new(0., 0., 1.));
+    assert!(record.is_front_face);
+    assert_eq!((record.u, record.v), (0.25, 0.5));
+}
+
+#[test]
+fn test_triangle_hit_from_behind() {
+    // Given a triangle facing +z
+    let triangle = test_triangle();
+
+    // When a ray comes at it from behind
+    let ray = Ray::new(Point3::new(0.25, 0.25, -1.), Vec3::new(0., 0., 1.));
+    let mut record = HitRecord::new();
+
+    // Then it hits the back face, with the normal facing the ray
+    assert!(triangle.hit(ray, 0.001, f64::INFINITY, &mut record));
+    assert!(!record.is_front_face);
+    assert_eq!(record.normal, Vec3::new(0., 0., -1.));
+}
+
+#[test]
+fn test_triangle_miss() {
+    // Given a triangle facing +z
+    let triangle = test_triangle();
+    let mut record = HitRecord::new();
+
+    // When rays pass beside it, along it, or hit it out of range
+    let beside = Ray::new(Point3::new(0.6, 0.6, 1.), Vec3::new(0., 0., -1.));
+    let along = Ray::new(Point3::new(-1., 0.25, 0.), Vec3::new(1., 0., 0.));
+    let too_far = Ray::new(Point3::new(0.25, 0.25, 5.), Vec3::new(0., 0., -1.));
+
+    // Then none of them hit it
+    assert!(!triangle.hit(beside, 0.001, f64::INFINITY, &mut record));
+    assert!(!triangle.hit(along, 0.001, f64::INFINITY, &mut record));
+    assert!(!triangle.hit(too_far, 0.001, 4., &mut record));
+}
+
+#[test]
+fn test_triangle_bounding_box_is_hittable() {
+    // Given a triangle lying flat in the z = 0 plane
+    let triangle = test_triangle();
+    let mut bounding_box = Aabb::new(Point3::new(0., 0., 0.), Point3::new(0., 0., 0.));
+
+    // When I get its bounding box
+    assert!(triangle.bounding_box(&mut bounding_box));
+
+    // Then a ray straight at the triangle hits the box as well
+    let ray = Ray::new(Point3::new(0.25, 0.25, 1.), Vec3::new(0., 0., -1.));
+    assert!(bounding_box.hit(ray, 0.001, f64::INFINITY));
+}
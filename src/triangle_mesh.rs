@@ -0,0 +1,268 @@
+use std::error::Error;
+use std::sync::Arc;
+
+use crate::{
+    aabb::Aabb,
+    bvh::BvhNode,
+    hittable::{HitRecord, Hittable},
+    material::Material,
+    point3::Point3,
+    ray::Ray,
+    scene_file::{ExportedMaterials, ObjectDescription},
+    triangle::{intersect_triangle, triangle_bounding_box, triangle_normal},
+    vec3::Vec3,
+};
+
+/// The vertex buffers of a triangle mesh, which all of its triangles share
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MeshData {
+    pub positions: Vec<Point3>,
+    /// A normal for every position to smoothly shade the triangles, or none to shade them flat
+    pub normals: Vec<Vec3>,
+    /// Texture coordinates for every position, or none
+    pub uvs: Vec<(f64, f64)>,
+    /// Indices of the three vertices of every triangle, counterclockwise seen from the front
+    pub triangles: Vec<[usize; 3]>,
+}
+
+/// Triangles that share vertices, with normals and texture coordinates interpolated across them
+///
+/// The triangles are kept in a `BvhNode` of their own, and report their position in `MeshData::triangles`
+/// plus one as `object_id` when the mesh isn't part of a `HittableList`.
+pub struct TriangleMesh {
+    mesh: Arc<MeshData>,
+    material: Arc<dyn Material>,
+    triangles: BvhNode,
+}
+
+// One triangle of a mesh
+struct MeshTriangle {
+    mesh: Arc<MeshData>,
+    material: Arc<dyn Material>,
+    index: usize,
+}
+
+impl TriangleMesh {
+    pub fn new(mesh: MeshData, material: Arc<dyn Material>) -> Result<Self, Box<dyn Error>> {
+        let vertex_count = mesh.positions.len();
+        if !mesh.normals.is_empty() && mesh.normals.len() != vertex_count {
+            return Err(format!(
+                "a mesh with {} positions needs as many normals, got {}",
+                vertex_count,
+                mesh.normals.len()
+            )
+            .into());
+        }
+        if !mesh.uvs.is_empty() && mesh.uvs.len() != vertex_count {
+            return Err(format!(
+                "a mesh with {} positions needs as many texture coordinates, got {}",
+                vertex_count,
+                mesh.uvs.len()
+            )
+            .into());
+        }
+        if let Some(index) = mesh
+            .triangles
+            .iter()
+            .flatten()
+            .find(|&&i| i >= vertex_count)
+        {
+            return Err(format!(
+                "a triangle uses vertex {}, but the mesh only has {} positions",
+                index, vertex_count
+            )
+            .into());
+        }
+
+        let mesh = Arc::new(mesh);
+        let triangles = (0..mesh.triangles.len())
+            .map(|index| {
+                Box::new(MeshTriangle {
+                    mesh: Arc::clone(&mesh),
+                    material: Arc::clone(&material),
+                    index,
+                }) as Box<dyn Hittable>
+            })
+            .collect();
+        Ok(Self {
+            mesh,
+            material,
+            triangles: BvhNode::new(triangles),
+        })
+    }
+}
+
+impl MeshTriangle {
+    fn vertices(&self) -> [Point3; 3] {
+        let [a, b, c] = self.mesh.triangles[self.index];
+        [
+            self.mesh.positions[a],
+            self.mesh.positions[b],
+            self.mesh.positions[c],
+        ]
+    }
+}
+
+impl Hittable for MeshTriangle {
+    fn hit(&self, ray: Ray, t_min: f64, t_max: f64, record: &mut HitRecord) -> bool {
+        let vertices = self.vertices();
+        let hit = match intersect_triangle(ray, vertices, t_min, t_max) {
+            Some(hit) => hit,
+            None => return false,
+        };
+        let [a, b, c] = self.mesh.triangles[self.index];
+        let weights = [1. - hit.b1 - hit.b2, hit.b1, hit.b2];
+
+        record.t = hit.t;
+        record.point = ray.at(hit.t);
+        record.material = Arc::clone(&self.material);
+
+        // Which side was hit follows the actual surface, the interpolated normal only changes the shading
+        record.set_face_normal(ray, triangle_normal(vertices));
+        if !self.mesh.normals.is_empty() {
+            let normals = &self.mesh.normals;
+            let shading_normal =
+                weights[0] * normals[a] + weights[1] * normals[b] + weights[2] * normals[c];
+            if !shading_normal.is_near_zero() {
+                let shading_normal = shading_normal.unit_vector();
+                record.normal = if record.is_front_face {
+                    shading_normal
+                } else {
+                    -shading_normal
+                };
+            }
+        }
+
+        if self.mesh.uvs.is_empty() {
+            record.u = hit.b1;
+            record.v = hit.b2;
+        } else {
+            let uvs = &self.mesh.uvs;
+            record.u = weights[0] * uvs[a].0 + weights[1] * uvs[b].0 + weights[2] * uvs[c].0;
+            record.v = weights[0] * uvs[a].1 + weights[1] * uvs[b].1 + weights[2] * uvs[c].1;
+        }
+
+        true
+    }
+
+    fn bounding_box(&self, output_box: &mut Aabb) -> bool {
+        *output_box = triangle_bounding_box(self.vertices());
+        true
+    }
+}
+
+impl Hittable for TriangleMesh {
+    fn hit(&self, ray: Ray, t_min: f64, t_max: f64, record: &mut HitRecord) -> bool {
+        self.triangles.hit(ray, t_min, t_max, record)
+    }
+
+    fn bounding_box(&self, output_box: &mut Aabb) -> bool {
+        self.triangles.bounding_box(output_box)
+    }
+
+    fn export(
+        &self,
+        materials: &mut ExportedMaterials,
+    ) -> Result<ObjectDescription, Box<dyn Error>> {
+        Ok(ObjectDescription::Mesh {
+            positions: self.mesh.positions.clone(),
+            normals: self.mesh.normals.clone(),
+            uvs: self.mesh.uvs.clone(),
+            triangles: self.mesh.triangles.clone(),
+            material: materials.name(&self.material)?,
+        })
+    }
+}
+
+// A unit square in the z = 0 plane made of two triangles, with normals tilting outwards
+#[cfg(test)]
+fn test_square() -> MeshData {
+    MeshData {
+        positions: vec![
+            Point3::new(0., 0., 0.),
+            Point3::new(1., 0., 0.),
+            Point3::new(1., 1., 0.),
+            Point3::new(0., 1., 0.),
+        ],
+        normals: vec![
+            Vec3::new(-1., -1., 1.),
+            Vec3::new(1., -1., 1.),
+            Vec3::new(1., 1., 1.),
+            Vec3::new(-1., 1., 1.),
+        ],
+        uvs: vec![(0., 0.), (2., 0.), (2., 2.), (0., 2.)],
+        triangles: vec![[0, 1, 2], [0, 2, 3]],
+    }
+}
+
+#[cfg(test)]
+fn test_material() -> Arc<dyn Material> {
+    use crate::{color::Color, lambertian::Lambertian};
+
+    Arc::new(Lambertian::new(Color::new(0.5, 0.5, 0.5)))
+}
+
+#[test]
+fn test_mesh_interpolates_normals_and_uvs() {
+    // Given a smoothly shaded square
+    let mesh = TriangleMesh::new(test_square(), test_material()).unwrap();
+
+    // When rays hit it in the middle and near a corner, from the front and from behind
+    let mut center = HitRecord::new();
+    let mut corner = HitRecord::new();
+    let mut behind = HitRecord::new();
+    let down = Vec3::new(0., 0., -1.);
+    let at_center = Ray::new(Point3::new(0.5, 0.5, 1.), down);
+    let near_corner = Ray::new(Point3::new(0.9, 0.1, 1.), down);
+    let up = Ray::new(Point3::new(0.5, 0.5, -1.), Vec3::new(0., 0., 1.));
+    assert!(mesh.hit(at_center, 0.001, 2., &mut center));
+    assert!(mesh.hit(near_corner, 0.001, 2., &mut corner));
+    assert!(mesh.hit(up, 0.001, 2., &mut behind));
+
+    // Then the normals and texture coordinates are blended from the vertices around the hit
+    assert!((center.normal - Vec3::new(0., 0., 1.)).len() < 1e-12);
+    assert!((center.u - 1.).abs() < 1e-12 && (center.v - 1.).abs() < 1e-12);
+    assert!(corner.normal.x > 0.5 && corner.normal.y < -0.5);
+    assert!((corner.u - 1.8).abs() < 1e-12 && (corner.v - 0.2).abs() < 1e-12);
+    assert!(!behind.is_front_face);
+    assert!((behind.normal - Vec3::new(0., 0., -1.)).len() < 1e-12);
+}
+
+#[test]
+fn test_mesh_has_no_gaps_between_triangles() {
+    // Given a flat shaded square
+    let data = MeshData {
+        normals: Vec::new(),
+        ..test_square()
+    };
+    let mesh = TriangleMesh::new(data, test_material()).unwrap();
+
+    // When rays hit it along the diagonal shared by both triangles
+    for i in 1..100 {
+        let along_diagonal = f64::from(i) / 100.;
+        let ray = Ray::new(
+            Point3::new(along_diagonal, along_diagonal, 1.),
+            Vec3::new(0., 0., -1.),
+        );
+        let mut record = HitRecord::new();
+
+        // Then every one of them hits, with the normal of the flat surface
+        assert!(mesh.hit(ray, 0.001, f64::INFINITY, &mut record));
+        assert_eq!(record.normal, Vec3::new(0., 0., 1.));
+    }
+}
+
+#[test]
+fn test_mesh_rejects_bad_indices() {
+    // Given mesh data with a triangle that uses a vertex that doesn't exist
+    let data = MeshData {
+        triangles: vec![[0, 1, 4]],
+        ..test_square()
+    };
+
+    // When I make a mesh of it
+    let result = TriangleMesh::new(data, test_material());
+
+    // Then I get an error
+    assert!(result.is_err());
+}
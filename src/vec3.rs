@@ -7,9 +7,12 @@ use std::ops::{
 };
 
 // 3rd party
-use rand::{random, thread_rng, Rng};
+use rand::Rng;
+use serde::{Deserialize, Serialize};
 
-#[derive(Copy, Clone, Debug, PartialEq)]
+/// Stored as `[x, y, z]` in scene files
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(from = "[f64; 3]", into = "[f64; 3]")]
 pub struct Vec3 {
     pub x: f64,
     pub y: f64,
@@ -21,12 +24,11 @@ impl Vec3 {
         Self { x, y, z }
     }
 
-    pub fn random() -> Self {
-        Self::new(random::<f64>(), random::<f64>(), random::<f64>())
+    pub fn random<R: Rng + ?Sized>(rng: &mut R) -> Self {
+        Self::new(rng.gen::<f64>(), rng.gen::<f64>(), rng.gen::<f64>())
     }
 
-    pub fn random_in_range(min: f64, max: f64) -> Self {
-        let mut rng = thread_rng();
+    pub fn random_in_range<R: Rng + ?Sized>(rng: &mut R, min: f64, max: f64) -> Self {
         Self::new(
             rng.gen_range(min..max),
             rng.gen_range(min..max),
@@ -34,23 +36,22 @@ impl Vec3 {
         )
     }
 
-    pub fn random_in_unit_sphere() -> Self {
+    pub fn random_in_unit_sphere<R: Rng + ?Sized>(rng: &mut R) -> Self {
         // Rejection method, keep generating a random points on inside a unit cube until we get one whose distance is less than 1, then return that one
         loop {
-            let point = Self::random_in_range(-1., 1.);
+            let point = Self::random_in_range(rng, -1., 1.);
             if point.len_squared() < 1. {
                 return point;
             };
         }
     }
 
-    pub fn random_unit_vector() -> Self {
-        Self::random_in_unit_sphere().unit_vector()
+    pub fn random_unit_vector<R: Rng + ?Sized>(rng: &mut R) -> Self {
+        Self::random_in_unit_sphere(rng).unit_vector()
     }
 
-    pub fn random_in_unit_disk() -> Self {
+    pub fn random_in_unit_disk<R: Rng + ?Sized>(rng: &mut R) -> Self {
         // Rejection method, keep generating a random points on a unit square until we get one whose distance is less than 1, then return that one
-        let mut rng = thread_rng();
         loop {
             let point = Vec3::new(rng.gen_range(-1.0..1.0), rng.gen_range(-1.0..1.0), 0.);
             if point.len_squared() < 1. {
@@ -101,6 +102,18 @@ impl Vec3 {
     }
 }
 
+impl From<[f64; 3]> for Vec3 {
+    fn from([x, y, z]: [f64; 3]) -> Self {
+        Self::new(x, y, z)
+    }
+}
+
+impl From<Vec3> for [f64; 3] {
+    fn from(vec3: Vec3) -> Self {
+        [vec3.x, vec3.y, vec3.z]
+    }
+}
+
 impl Display for Vec3 {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(f, "{:?}", self)
@@ -0,0 +1,429 @@
+use assert_cmd::Command;
+use predicates::prelude::*;
+
+fn ray_tracing() -> Command {
+    Command::cargo_bin("ray-tracing").unwrap()
+}
+
+#[test]
+fn test_renders_ppm_to_stdout() {
+    ray_tracing()
+        .args([
+            "--width",
+            "8",
+            "--height",
+            "4",
+            "--spp",
+            "1",
+            "--max-depth",
+            "2",
+        ])
+        .args(["--scene", "controlled"])
+        .assert()
+        .success()
+        .stdout(predicate::str::starts_with("P3\n8 4\n255\n"));
+}
+
+#[test]
+fn test_derives_height_from_width() {
+    ray_tracing()
+        .args(["--width", "9", "--spp", "1", "--max-depth", "2"])
+        .args(["--scene", "controlled"])
+        .assert()
+        .success()
+        .stdout(predicate::str::starts_with("P3\n9 6\n255\n"));
+}
+
+#[test]
+fn test_writes_output_file() {
+    let path = std::env::temp_dir().join("ray-tracing-test-writes-output-file.ppm");
+
+    ray_tracing()
+        .args([
+            "--width",
+            "4",
+            "--height",
+            "2",
+            "--spp",
+            "1",
+            "--max-depth",
+            "2",
+        ])
+        .args(["--scene", "controlled", "--output"])
+        .arg(&path)
+        .assert()
+        .success()
+        .stdout(predicate::str::is_empty());
+
+    let contents = std::fs::read(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    assert!(contents.starts_with(b"P3\n4 2\n255\n"));
+}
+
+#[test]
+fn test_rejects_too_small_width() {
+    ray_tracing()
+        .args(["--width", "1"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("--width"));
+}
+
+#[test]
+fn test_rejects_zero_samples() {
+    ray_tracing()
+        .args(["--spp", "0"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("--spp"));
+}
+
+#[test]
+fn test_rejects_unknown_scene() {
+    ray_tracing()
+        .args(["--scene", "cornell"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "possible values: random, controlled",
+        ));
+}
+
+#[test]
+fn test_rejects_malformed_vector() {
+    ray_tracing()
+        .args(["--camera-look-from", "1,2"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "expected three comma separated numbers like 13,2,3, got '1,2'",
+        ));
+}
+
+#[test]
+fn test_rejects_up_vector_along_view_direction() {
+    ray_tracing()
+        .args(["--camera-look-from", "0,5,0", "--camera-look-at", "0,0,0"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "can't be parallel to the view direction",
+        ));
+}
+
+#[test]
+fn test_rejects_shutter_closing_before_it_opens() {
+    ray_tracing()
+        .args([
+            "--camera-shutter-open",
+            "1",
+            "--camera-shutter-close",
+            "0.5",
+        ])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "the shutter closes at 0.5 before it opens at 1",
+        ));
+}
+
+#[test]
+fn test_rejects_unknown_extension() {
+    ray_tracing()
+        .args(["--output", "render.xyz"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("use --format to pick one"));
+}
+
+#[test]
+fn test_same_seed_gives_same_image() {
+    let render = |threads: &str| {
+        ray_tracing()
+            .args([
+                "--width",
+                "6",
+                "--height",
+                "4",
+                "--spp",
+                "2",
+                "--max-depth",
+                "3",
+            ])
+            .args(["--seed", "1234", "--threads", threads])
+            .output()
+            .unwrap()
+    };
+
+    let first = render("1");
+    let second = render("2");
+
+    assert!(first.status.success());
+    assert_eq!(first.stdout, second.stdout);
+    assert!(String::from_utf8_lossy(&first.stderr).contains("Seed: 1234"));
+}
+
+#[test]
+fn test_writes_binary_ppm() {
+    let output = ray_tracing()
+        .args([
+            "--width",
+            "4",
+            "--height",
+            "2",
+            "--spp",
+            "1",
+            "--max-depth",
+            "2",
+        ])
+        .args(["--scene", "controlled", "--format", "ppm16"])
+        .output()
+        .unwrap();
+
+    assert!(output.status.success());
+    assert!(output.stdout.starts_with(b"P6\n4 2\n65535\n"));
+    assert_eq!(
+        output.stdout.len(),
+        b"P6\n4 2\n65535\n".len() + 4 * 2 * 3 * 2
+    );
+}
+
+#[test]
+fn test_infers_png_from_extension() {
+    let path = std::env::temp_dir().join("ray-tracing-test-infers-png-from-extension.png");
+
+    ray_tracing()
+        .args([
+            "--width",
+            "4",
+            "--height",
+            "2",
+            "--spp",
+            "1",
+            "--max-depth",
+            "2",
+        ])
+        .args(["--scene", "controlled", "--output"])
+        .arg(&path)
+        .assert()
+        .success();
+
+    let contents = std::fs::read(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    assert!(contents.starts_with(b"\x89PNG\r\n\x1a\n"));
+}
+
+#[test]
+fn test_writes_linear_pfm() {
+    let output = ray_tracing()
+        .args([
+            "--width",
+            "4",
+            "--height",
+            "2",
+            "--spp",
+            "1",
+            "--max-depth",
+            "2",
+        ])
+        .args(["--scene", "controlled", "--format", "pfm"])
+        .output()
+        .unwrap();
+
+    assert!(output.status.success());
+    assert!(output.stdout.starts_with(b"PF\n4 2\n-1.0\n"));
+    assert_eq!(
+        output.stdout.len(),
+        b"PF\n4 2\n-1.0\n".len() + 4 * 2 * 3 * 4
+    );
+}
+
+#[test]
+fn test_writes_exr_with_passes() {
+    let output = ray_tracing()
+        .args([
+            "--width",
+            "4",
+            "--height",
+            "2",
+            "--spp",
+            "1",
+            "--max-depth",
+            "2",
+        ])
+        .args([
+            "--scene",
+            "controlled",
+            "--format",
+            "exr",
+            "--passes",
+            "beauty,depth",
+        ])
+        .output()
+        .unwrap();
+
+    assert!(output.status.success());
+    assert!(output.stdout.starts_with(&[0x76, 0x2f, 0x31, 0x01]));
+    let contents = String::from_utf8_lossy(&output.stdout);
+    assert!(contents.contains("depth.Z"));
+    assert!(!contents.contains("normal.X"));
+}
+
+// Render a tiny image of the controlled scene to a file in the temp directory
+fn render_to(name: &str, seed: &str) -> std::path::PathBuf {
+    let path = std::env::temp_dir().join(name);
+    ray_tracing()
+        .args(["--width", "8", "--height", "4", "--spp", "1"])
+        .args(["--max-depth", "2", "--scene", "controlled", "--seed", seed])
+        .arg("--output")
+        .arg(&path)
+        .assert()
+        .success();
+    path
+}
+
+#[test]
+fn test_diff_of_the_same_render() {
+    let png = render_to("ray-tracing-test-diff-same.png", "1");
+    let pfm = render_to("ray-tracing-test-diff-same.pfm", "1");
+
+    let assert = ray_tracing().arg("diff").arg(&png).arg(&pfm).assert();
+
+    std::fs::remove_file(&png).unwrap();
+    std::fs::remove_file(&pfm).unwrap();
+    assert
+        .success()
+        .stdout(predicate::str::contains("RMSE: 0.00"))
+        .stdout(predicate::str::contains("PSNR: "))
+        .stdout(predicate::str::contains("FLIP: 0.0"));
+}
+
+#[test]
+fn test_diff_writes_difference_images_and_fails_above_max_flip() {
+    let reference = render_to("ray-tracing-test-diff-reference.ppm", "1");
+    let image = render_to("ray-tracing-test-diff-image.ppm", "2");
+    let difference = std::env::temp_dir().join("ray-tracing-test-diff-difference.pfm");
+    let error_map = std::env::temp_dir().join("ray-tracing-test-diff-error-map.png");
+
+    let assert = ray_tracing()
+        .arg("diff")
+        .arg(&reference)
+        .arg(&image)
+        .arg("--difference")
+        .arg(&difference)
+        .arg("--error-map")
+        .arg(&error_map)
+        .args(["--max-flip", "0"])
+        .assert();
+
+    let difference_contents = std::fs::read(&difference).unwrap();
+    let error_map_contents = std::fs::read(&error_map).unwrap();
+    for path in [reference, image, difference, error_map].iter() {
+        std::fs::remove_file(path).unwrap();
+    }
+    assert
+        .failure()
+        .stdout(predicate::str::contains("FLIP: "))
+        .stderr(predicate::str::contains("--max-flip 0"));
+    assert!(difference_contents.starts_with(b"PF\n8 4\n"));
+    assert!(error_map_contents.starts_with(b"\x89PNG"));
+}
+
+#[test]
+fn test_diff_rejects_images_of_different_sizes() {
+    let small = render_to("ray-tracing-test-diff-small.ppm", "1");
+    let large = std::env::temp_dir().join("ray-tracing-test-diff-large.ppm");
+    ray_tracing()
+        .args([
+            "--width",
+            "9",
+            "--height",
+            "4",
+            "--spp",
+            "1",
+            "--max-depth",
+            "2",
+        ])
+        .args(["--scene", "controlled", "--output"])
+        .arg(&large)
+        .assert()
+        .success();
+
+    let assert = ray_tracing().arg("diff").arg(&small).arg(&large).assert();
+
+    std::fs::remove_file(&small).unwrap();
+    std::fs::remove_file(&large).unwrap();
+    assert
+        .failure()
+        .stderr(predicate::str::contains("can't compare a 9x4 image"));
+}
+
+#[test]
+fn test_rejects_malformed_background() {
+    ray_tracing()
+        .args(["--width", "4", "--background", "night"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("expected sky, rainbow or a color"));
+}
+
+#[test]
+fn test_scene_file_renders_like_the_builtin_scene() {
+    let scene_file = concat!(env!("CARGO_MANIFEST_DIR"), "/scenes/controlled.json");
+    let render = |scene: &str| {
+        ray_tracing()
+            .args(["--width", "8", "--height", "4", "--spp", "2"])
+            .args(["--max-depth", "4", "--seed", "5", "--scene", scene])
+            .output()
+            .unwrap()
+    };
+
+    let builtin = render("controlled");
+    let from_file = render(scene_file);
+
+    assert!(builtin.status.success());
+    assert!(from_file.status.success());
+    assert_eq!(builtin.stdout, from_file.stdout);
+}
+
+#[test]
+fn test_rejects_broken_scene_file() {
+    let path = std::env::temp_dir().join("ray-tracing-test-broken-scene.json");
+    std::fs::write(
+        &path,
+        r#"{ "camera": { "look_from": [0, 0, 1], "look_at": [0, 0, 0] },
+             "objects": [{ "type": "sphere", "center": [0, 0, 0], "radius": 1, "material": "red" }] }"#,
+    )
+    .unwrap();
+
+    let assert = ray_tracing().arg("--scene").arg(&path).assert();
+
+    std::fs::remove_file(&path).unwrap();
+    assert.failure().stderr(predicate::str::contains(
+        "objects[0].material: there is no material named 'red'",
+    ));
+}
+
+#[test]
+fn test_saved_scene_renders_like_the_original() {
+    let path = std::env::temp_dir().join("ray-tracing-test-saved-scene.json");
+    ray_tracing()
+        .args(["--scene", "random", "--seed", "9", "--save-scene"])
+        .arg(&path)
+        .assert()
+        .success();
+    let render = |scene: &str| {
+        ray_tracing()
+            .args(["--width", "8", "--height", "4", "--spp", "1"])
+            .args(["--max-depth", "3", "--seed", "9", "--scene", scene])
+            .output()
+            .unwrap()
+    };
+
+    let original = render("random");
+    let saved = render(path.to_str().unwrap());
+
+    std::fs::remove_file(&path).unwrap();
+    assert!(saved.status.success());
+    assert_eq!(original.stdout, saved.stdout);
+}
@@ -0,0 +1,130 @@
+//! Renders small, seeded versions of the scenes and compares them with the reference images in
+//! `tests/golden`. Run with `UPDATE_GOLDEN=1` to write new references after an intended change.
+
+use std::path::{Path, PathBuf};
+
+use ray_tracing::{
+    diff_images, read_image, read_png, render,
+    rng::rng_from_seed,
+    scenes::{controlled_scene, random_scene, simple_light_scene},
+    write_png, Background, Camera, Color, Framebuffer, HittableList, PngFormat, Point3,
+    RenderSettings, Vec3, DEFAULT_PIXELS_PER_DEGREE,
+};
+
+// How much a render may differ from its reference, to allow for floating point differences between
+// platforms. Renders that didn't change match their reference exactly.
+const MAX_FLIP: f64 = 0.01;
+const MAX_RMSE: f64 = 0.01;
+
+fn camera(look_from: Point3, look_at: Point3, aperture: f64, focus_distance: f64) -> Camera {
+    Camera::new(
+        look_from,
+        look_at,
+        Vec3::new(0., 1., 0.),
+        20.,
+        3. / 2.,
+        aperture,
+        focus_distance,
+    )
+}
+
+// Render the scene the way it would be saved as a PNG, so it can be compared with a PNG reference
+fn render_as_png(world: &HittableList, camera: &Camera, settings: &RenderSettings) -> Framebuffer {
+    let framebuffer = render(world, camera, settings, |_| {}).unwrap();
+    let mut data = Vec::new();
+    write_png(&framebuffer.gamma_corrected(), PngFormat::Rgb8, &mut data).unwrap();
+    read_png(data.as_slice()).unwrap().linearized()
+}
+
+fn write_png_file(framebuffer: &Framebuffer, path: &Path) {
+    let file = std::fs::File::create(path).unwrap();
+    write_png(framebuffer, PngFormat::Rgb8, std::io::BufWriter::new(file)).unwrap();
+}
+
+// Compare a render with the reference of the same name, writing the render and where it differs
+// next to the build output when it doesn't match
+fn assert_matches_golden(name: &str, framebuffer: &Framebuffer) {
+    let reference_path = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/golden")
+        .join(format!("{}.png", name));
+    if std::env::var_os("UPDATE_GOLDEN").is_some() {
+        write_png_file(&framebuffer.gamma_corrected(), &reference_path);
+        return;
+    }
+
+    let reference = read_image(&reference_path).unwrap_or_else(|error| {
+        panic!(
+            "can't read reference image {}: {}, run with UPDATE_GOLDEN=1 to create it",
+            reference_path.display(),
+            error
+        )
+    });
+    let diff = diff_images(&reference, framebuffer, DEFAULT_PIXELS_PER_DEGREE).unwrap();
+    if diff.flip <= MAX_FLIP && diff.rmse <= MAX_RMSE {
+        return;
+    }
+
+    let failure_path = |suffix: &str| -> PathBuf {
+        Path::new(env!("CARGO_TARGET_TMPDIR")).join(format!("{}-{}.png", name, suffix))
+    };
+    write_png_file(&framebuffer.gamma_corrected(), &failure_path("render"));
+    write_png_file(
+        &diff.difference.gamma_corrected(),
+        &failure_path("difference"),
+    );
+    write_png_file(&diff.error_map, &failure_path("error-map"));
+    panic!(
+        "{} doesn't match its reference: FLIP {:.4} (max {}), RMSE {:.4} (max {}). \
+         The render and its error map are in {}",
+        name,
+        diff.flip,
+        MAX_FLIP,
+        diff.rmse,
+        MAX_RMSE,
+        env!("CARGO_TARGET_TMPDIR")
+    );
+}
+
+#[test]
+fn test_golden_controlled_scene() {
+    // Given a small render of the controlled scene, seen from the default camera of the command line
+    let mut settings = RenderSettings::new(48, 32, 16, 10);
+    settings.seed = 11;
+    let camera = camera(Point3::new(-2., 2., 1.), Point3::new(0., 0., -1.), 0., 3.4);
+
+    // When I render it
+    let framebuffer = render_as_png(&controlled_scene(), &camera, &settings);
+
+    // Then it looks like the reference
+    assert_matches_golden("controlled-scene", &framebuffer);
+}
+
+#[test]
+fn test_golden_random_scene() {
+    // Given a small render of the random scene, with depth of field
+    let mut settings = RenderSettings::new(48, 32, 8, 8);
+    settings.seed = 11;
+    let world = random_scene(&mut rng_from_seed(settings.seed));
+    let camera = camera(Point3::new(13., 2., 3.), Point3::new(0., 0., 0.), 0.1, 10.);
+
+    // When I render it
+    let framebuffer = render_as_png(&world, &camera, &settings);
+
+    // Then it looks like the reference
+    assert_matches_golden("random-scene", &framebuffer);
+}
+
+#[test]
+fn test_golden_simple_light_scene() {
+    // Given a small render of the scene lit by glowing spheres, against a black background
+    let mut settings = RenderSettings::new(48, 32, 32, 10);
+    settings.seed = 11;
+    settings.background = Background::Solid(Color::new(0., 0., 0.));
+    let camera = camera(Point3::new(26., 3., 6.), Point3::new(0., 2., 0.), 0., 10.);
+
+    // When I render it
+    let framebuffer = render_as_png(&simple_light_scene(), &camera, &settings);
+
+    // Then it looks like the reference
+    assert_matches_golden("simple-light-scene", &framebuffer);
+}
@@ -0,0 +1,89 @@
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use ray_tracing::{
+    hittable::{HitRecord, Hittable},
+    load_obj, load_ply, load_stl, Color, Lambertian, Material, Point3, Ray, Vec3,
+};
+
+fn fixture(name: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/fixtures")
+        .join(name)
+}
+
+fn gray() -> Arc<dyn Material> {
+    Arc::new(Lambertian::new(Color::new(0.5, 0.5, 0.5)))
+}
+
+// Shoot rays at a tetrahedron with corners at the origin and on the axes, checking the faces they hit
+fn assert_is_tetrahedron(mesh: &dyn Hittable) {
+    let rays = [
+        // Towards the slanted face
+        (Point3::new(1., 1., 1.), Vec3::new(-1., -1., -1.), 2. / 3.),
+        // Up at the face in the z = 0 plane
+        (Point3::new(0.2, 0.2, -1.), Vec3::new(0., 0., 1.), 1.),
+        // Along the x axis at the face in the x = 0 plane
+        (Point3::new(-2., 0.2, 0.2), Vec3::new(1., 0., 0.), 2.),
+    ];
+    for (origin, direction, t) in rays.iter() {
+        let mut record = HitRecord::new();
+        assert!(mesh.hit(
+            Ray::new(*origin, *direction),
+            0.001,
+            f64::INFINITY,
+            &mut record
+        ));
+        assert!(
+            (record.t - t).abs() < 1e-6,
+            "hit at {} instead of {}",
+            record.t,
+            t
+        );
+        assert!(record.is_front_face);
+    }
+
+    let mut record = HitRecord::new();
+    let beside = Ray::new(Point3::new(1., 1., -1.), Vec3::new(0., 0., 1.));
+    assert!(!mesh.hit(beside, 0.001, f64::INFINITY, &mut record));
+}
+
+#[test]
+fn test_loads_ply_fixtures() {
+    // Given a tetrahedron stored as ASCII and as binary PLY
+    for name in ["tetrahedron_ascii.ply", "tetrahedron_binary.ply"].iter() {
+        // When I load it
+        let mesh = load_ply(fixture(name), gray()).unwrap();
+
+        // Then rays hit its faces from outside
+        assert_is_tetrahedron(&mesh);
+    }
+}
+
+#[test]
+fn test_loads_stl_fixtures() {
+    // Given a tetrahedron stored as ASCII and as binary STL
+    for name in ["tetrahedron_ascii.stl", "tetrahedron_binary.stl"].iter() {
+        // When I load it
+        let mesh = load_stl(fixture(name), gray()).unwrap();
+
+        // Then rays hit its faces from outside
+        assert_is_tetrahedron(&mesh);
+    }
+}
+
+#[test]
+fn test_load_errors_name_the_file() {
+    // Given an STL file
+    let path = fixture("tetrahedron_ascii.stl");
+
+    // When I load it as PLY
+    let error = load_ply(&path, gray()).err().unwrap();
+
+    // Then the error says which file couldn't be read
+    assert!(error.to_string().starts_with(&path.display().to_string()));
+
+    // And loading a missing OBJ says which file is missing
+    let error = load_obj(fixture("missing.obj")).err().unwrap();
+    assert!(error.to_string().contains("missing.obj"));
+}
@@ -0,0 +1,190 @@
+use ray_tracing::{
+    render,
+    rng::rng_from_seed,
+    scenes::{controlled_scene, random_scene, simple_light_scene},
+    write_ppm, Background, BvhNode, Camera, Color, DiffuseLight, HittableList, MovingSphere,
+    Point3, RenderSettings, TileOrder, Vec3,
+};
+use std::sync::Arc;
+
+fn camera(aspect_ratio: f64) -> Camera {
+    Camera::new(
+        Point3::new(13., 2., 3.),
+        Point3::new(0., 0., 0.),
+        Vec3::new(0., 1., 0.),
+        20.,
+        aspect_ratio,
+        0.1,
+        10.,
+    )
+}
+
+#[test]
+fn test_render_controlled_scene() {
+    // Given a small image of the controlled scene
+    let settings = RenderSettings::new(8, 4, 4, 10);
+    let camera = Camera::new(
+        Point3::new(0., 0., 1.),
+        Point3::new(0., 0., -1.),
+        Vec3::new(0., 1., 0.),
+        90.,
+        2.,
+        0.,
+        2.,
+    );
+    let world = controlled_scene();
+
+    // When I render it
+    let framebuffer = render(&world, &camera, &settings, |_| {}).unwrap();
+
+    // Then I get a framebuffer of the size I asked for
+    assert_eq!((framebuffer.width(), framebuffer.height()), (8, 4));
+
+    // And the middle of the image sees an object, with sky in the top corners
+    let center = framebuffer.pixel(4, 2);
+    assert_eq!(center.alpha, 1.);
+    assert_ne!(center.object_id, 0);
+    assert!(center.depth < 2.);
+    assert_eq!(framebuffer.pixel(0, 0).alpha, 0.);
+    assert_eq!(framebuffer.pixel(0, 0).object_id, 0);
+
+    // And I can write it as a PPM image
+    let mut result = Vec::new();
+    write_ppm(&framebuffer.gamma_corrected(), &mut result).unwrap();
+    assert!(result.starts_with(b"P3\n8 4\n255\n"));
+}
+
+#[test]
+fn test_render_is_deterministic() {
+    // Given the random scene generated from a seed
+    let world = random_scene(&mut rng_from_seed(7));
+    let mut settings = RenderSettings::new(12, 8, 2, 5);
+    settings.seed = 7;
+
+    // When I render it on one thread, and on several threads with other tiles
+    settings.threads = 1;
+    let single_threaded = render(&world, &camera(1.5), &settings, |_| {}).unwrap();
+
+    settings.threads = 3;
+    settings.tile_size = 5;
+    settings.tile_order = TileOrder::Hilbert;
+    let multi_threaded = render(&world, &camera(1.5), &settings, |_| {}).unwrap();
+
+    // Then both images are exactly the same
+    assert_eq!(single_threaded, multi_threaded);
+
+    // And generating the scene again from the same seed gives the same image too
+    let regenerated_world = random_scene(&mut rng_from_seed(7));
+    let regenerated = render(&regenerated_world, &camera(1.5), &settings, |_| {}).unwrap();
+    assert_eq!(single_threaded, regenerated);
+}
+
+#[test]
+fn test_render_depends_on_seed() {
+    // Given a scene
+    let world = controlled_scene();
+    let mut settings = RenderSettings::new(8, 4, 2, 5);
+
+    // When I render it with two different seeds
+    settings.seed = 1;
+    let first = render(&world, &camera(2.), &settings, |_| {}).unwrap();
+    settings.seed = 2;
+    let second = render(&world, &camera(2.), &settings, |_| {}).unwrap();
+
+    // Then the noise is different
+    assert_ne!(first, second);
+}
+
+#[test]
+fn test_render_light_against_black_background() {
+    // Given the scene lit by glowing spheres, against a black background
+    let world = simple_light_scene();
+    let mut settings = RenderSettings::new(4, 4, 2, 5);
+    settings.background = Background::Solid(Color::new(0., 0., 0.));
+    let camera_at = |look_from: Point3, look_at: Point3| {
+        Camera::new(look_from, look_at, Vec3::new(1., 0., 0.), 5., 1., 0., 1.)
+    };
+
+    // When I render the big light up close, and the empty space above it
+    let light = render(
+        &world,
+        &camera_at(Point3::new(0., 7., 10.), Point3::new(0., 7., 0.)),
+        &settings,
+        |_| {},
+    )
+    .unwrap();
+    let space = render(
+        &world,
+        &camera_at(Point3::new(0., 20., 0.), Point3::new(0., 30., 0.)),
+        &settings,
+        |_| {},
+    )
+    .unwrap();
+
+    // Then the light shows its own color, and the space shows the background
+    assert!(light
+        .pixels()
+        .iter()
+        .all(|pixel| pixel.color == Color::new(4., 4., 4.) && pixel.alpha == 1.));
+    assert!(space
+        .pixels()
+        .iter()
+        .all(|pixel| pixel.color == Color::new(0., 0., 0.) && pixel.alpha == 0.));
+}
+
+#[test]
+fn test_render_with_bvh_matches_list() {
+    // Given the random scene, as a list and as a BVH
+    let list = random_scene(&mut rng_from_seed(5));
+    let bvh = BvhNode::from(random_scene(&mut rng_from_seed(5)));
+    let mut settings = RenderSettings::new(12, 8, 2, 5);
+    settings.seed = 5;
+
+    // When I render both
+    let from_list = render(&list, &camera(1.5), &settings, |_| {}).unwrap();
+    let from_bvh = render(&bvh, &camera(1.5), &settings, |_| {}).unwrap();
+
+    // Then the images are exactly the same, passes included
+    assert_eq!(from_list, from_bvh);
+}
+
+#[test]
+fn test_motion_blur() {
+    // Given a glowing sphere that moves across the middle of the image while the shutter is open
+    let mut world = HittableList::new();
+    world.add(Box::new(MovingSphere::new(
+        Point3::new(-2.2, 0., 0.),
+        Point3::new(2.2, 0., 0.),
+        0.,
+        1.,
+        0.5,
+        Arc::new(DiffuseLight::new(Color::new(1., 1., 1.))),
+    )));
+    let mut settings = RenderSettings::new(9, 3, 64, 2);
+    settings.background = Background::Solid(Color::new(0., 0., 0.));
+    let camera = Camera::new(
+        Point3::new(0., 0., 3.),
+        Point3::new(0., 0., 0.),
+        Vec3::new(0., 1., 0.),
+        40.,
+        3.,
+        0.,
+        3.,
+    );
+
+    // When I render it with the shutter closed at the start, and open the whole time
+    let still = render(&world, &camera, &settings, |_| {}).unwrap();
+    let blurred = render(&world, &camera.with_shutter(0., 1.), &settings, |_| {}).unwrap();
+
+    // Then the still image only has the sphere where it starts
+    let start = still.pixel(1, 1).color.x;
+    assert!(start > 0.);
+    assert_eq!(still.pixel(4, 1).color.x, 0.);
+    assert_eq!(still.pixel(6, 1).color.x, 0.);
+
+    // And the blurred one smears it along its path, fainter than where it stands still
+    for x in [1, 4, 6].iter() {
+        let light = blurred.pixel(*x, 1).color.x;
+        assert!(light > 0. && light < start, "pixel {} has {}", x, light);
+    }
+}